@@ -0,0 +1,70 @@
+use criterion::{ criterion_group, criterion_main, Criterion };
+
+use brainfuck::{ Modifiers, CompileOptions, compile_program, execute_bf };
+use brainfuck::bench::bundled_programs;
+use brainfuck::source::read_source;
+
+fn programs() -> Vec<(String, Vec<char>)> {
+    bundled_programs().expect("Couldn't list the benchmark programs")
+        .into_iter()
+        .map(|path| {
+            let name = std::path::Path::new(&path).file_stem().unwrap()
+                .to_string_lossy().into_owned();
+            let source = read_source(&path).expect("Couldn't read benchmark program");
+            (name, source)
+        })
+        .collect()
+}
+
+fn compile(c: &mut Criterion) {
+    let std_lib = read_source("std.bf").ok();
+    let mut group = c.benchmark_group("compile");
+    for (name, source) in programs() {
+        group.bench_function(&name, |b| b.iter(|| {
+            compile_program(source.clone(), std_lib.clone(), CompileOptions::default()).ok().expect("Benchmark program didn't compile")
+        }));
+    }
+    group.finish();
+}
+
+// Generated code with a lot of deeply nested loops, to keep an eye on how
+// the size of loop bodies affects codegen
+fn nested_loops(depth: usize) -> Vec<char> {
+    let mut source = String::new();
+    for _ in 0..depth {
+        source.push_str("[ \"ab\" +10 >\n");
+    }
+    for _ in 0..depth {
+        source.push_str("-] ");
+    }
+    source.chars().collect()
+}
+
+fn codegen(c: &mut Criterion) {
+    let mut group = c.benchmark_group("codegen");
+    for &depth in &[10, 100, 1000] {
+        let source = nested_loops(depth);
+        group.bench_function(format!("nested_loops_{}", depth), |b| b.iter(|| {
+            compile_program(source.clone(), None, CompileOptions::default()).ok().expect("Generated program didn't compile")
+        }));
+    }
+    group.finish();
+}
+
+fn run(c: &mut Criterion) {
+    let std_lib = read_source("std.bf").ok();
+    let modifiers = Modifiers::new();
+    let mut group = c.benchmark_group("run");
+    // The programs take a while to run, so fewer samples keeps the suite bearable
+    group.sample_size(10);
+    for (name, source) in programs() {
+        let program = compile_program(source, std_lib.clone(), CompileOptions::default()).ok().expect("Benchmark program didn't compile");
+        group.bench_function(&name, |b| b.iter(|| {
+            execute_bf(&program, &modifiers, &[], &mut std::io::sink()).unwrap()
+        }));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compile, codegen, run);
+criterion_main!(benches);
@@ -0,0 +1,123 @@
+; Prints the prime factors of every number from 2 to 150 using trial division.
+; Generated, so it is plain brainfuck apart from the pragma.
+#pragma cells 16
+
+[-]++>[-]+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++>>
+>>>[-]+>[-]++<<<<<<[->>>>>>>>>[-]++++++++++>>>>>[-]<<<<<<<<<<<<<<<[->>>>>>>>>>>>
+>>>>>+<<+<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]
+>[-]<<<[->>+>+<<<]>>[-<<+>>]>>>[-]<<<<<<<<<<[->>>>>>>+>>>+<<<<<<<<<<]>>>>>>>[-<<
+<<<<<+>>>>>>>]>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<<<<<[-]>>>>>>>>[-]>[-]>[-<
+<<<<<<<<<+>>>>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[->+<<+>]>[-<+>]>[-]<<<[->>+>+<
+<<]>>[-<<+>>]>>>[-]<<<<<<<<<<[->>>>>>>+>>>+<<<<<<<<<<]>>>>>>>[-<<<<<<<+>>>>>>>]>
+[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<<<<[-]>>>>>>>[-]>[-]>[-<<<<<<<<<+>>>>>>>>
+>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[->+<<+>]>[-<+>]>[-]<<<[->>+>+<<<]>>[-<<+>>]>>>[-]
+<<<<<<<<<<[->>>>>>>+>>>+<<<<<<<<<<]>>>>>>>[-<<<<<<<+>>>>>>>]>[->+>-[>+>>]>[+[-<+
+>]>+>>]<<<<<<]<<[-]<<<[-]>>>>>>[-]>[-]>[-<<<<<<<<+>>>>>>>>]>[-<<<<<<+>>>>>>]<<<<
+<<<[-]>[->+<<+>]>[-<+>]>[-]<<<[->>+>+<<<]>>[-<<+>>]>>>[-]<<<<<<<<<<[->>>>>>>+>>>
++<<<<<<<<<<]>>>>>>>[-<<<<<<<+>>>>>>>]>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<<[-
+]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[->+<<+>]>[-<+>]<<[-
+]>[-]<<<<<<[-]>>>>>>>[-]>>>>>>>>[-]<<<<<<<<<<<[->>>>>>>>>>>>+<+<<<<<<<<<<<]>>>>>
+>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<[[-]<<<<<<<<[-]+>>>>>>>>][-]<<<<<<<<[->>>>>>
+>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<<<<<++++++++++++++++++
+++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]>>>>>>>>>>>[-]<<<<<<<<<
+<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[
+-]<<<<<<<<[-]+>>>>>>>>][-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>
+>>>>>>>]<[[-]<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>
+>>>>>>]<<<<<<<<<<<<[-]>>>>>>>>>>>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<
+<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<[-]+>>>>>>>>][-]<<
+<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<<<<<<<++
+++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>>>]<<<<<<<<<<<<<[-]>>>
+>>[-]+>>>>>>>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<
+[[-]<<<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>>>
+>]<<<<<<<<<<<<<<[-]>>>>>>[-]<<<[-]++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++.[-]<<<<<<<<<<<<[-]<<[->>>>>>>>>>>>>>+<<<<<<<<<<<<+<<]>>>>>>>>>>>>>>
+[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<<<[-]++>>>>>>>>>>[-]<<<<<<<<<<<[->>>>>>
+>>>+>>+<<<<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]>[-]<<<<<[->>>>+>+<<<<<]>>>>[-
+<<<<+>>>>]>>>[-]+<<[<[-]+>>>>>>[-]<<<<[->>>+>+<<<<]>>>[-<<<+>>>]>[[-]<<<<<<[-]>>
+>>>>]<<<<<<[[-]>>>[-]<<[-]+>[-]+<<]>>-<-]>[-]<<<<<[-]>>>>[-]<<<<<<<<<[->>>>>>>>>
+>+<+<<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<[-<<<<<<<<<[->>>>>>>>>>+<<<<<+<
+<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<]<<<[-]<<<<<<<[->>>>>>>>>>+<<<+<<<<<<<]>
+>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<<<+>>>>[-]<<<<<[->>>>>>>>>+<<<<+<<<<<]>>>>>>>>
+>[-<<<<<<<<<+>>>>>>>>>]<<<<<<[-]<<[->>>>>>>>+<<<<<<+<<]>>>>>>>>[-<<<<<<<<+>>>>>>
+>>]<<<<<[-]+<[>>>>>>[-]+<[-]<<<[->>+>+<<<]>>[-<<+>>]>[[-]>[-]<]>[[-]<<<<<[-]<[-]
++>>[-]+>>>>]<<<<-<<-]>>[-]<<[-]>[->+<<+>]>[-<+>]<<[[-]>>>[-]<<<]>[-]<<<[-]<[-]>>
+>>>>[>>>>[-]<<<<<<<<<<<<<<<<[->>>>>>>>>>+>>>>>>+<<<<<<<<<<<<<<<<]>>>>>>>>>>[-<<<
+<<<<<<<+>>>>>>>>>>]>>>>>>>>[-]<<<<<<<<<<<<<<<<<[->>>>>>>>>+>>>>>>>>+<<<<<<<<<<<<
+<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]>>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<<
+<<<<<<<<<<[-]>[-]>>>>>>>>>>>>>>[-]>[-]>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]>[-<<
+<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>]<<<<<<<<<<[-]<<<<<<<[->>>>>>+>+<<<<<<<]>>>>>
+>[-<<<<<<+>>>>>>][-]+>[[-]<[-]<<<<<<<<+>>>>>>>>>]<[[-]>>[-]+++++++++++++++++++++
++++++++++++.[-][-]++++++++++>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>
+>>>>>>>>>>>+<<+<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<
+<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<[-]>>>>>>>[->>+<<<<<<<<<+>>>>>>>]>>[-
+<<+>>]<<<<<<<[-]<<<<<<<[->>>>>>>>>>>>>>+<<<<<<<+<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<
+<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]>>>>>>>>[-]<<<<<
+<<<<[-]>>[-]>[-]>[-<<<<+>>>>]>[->>>>+<<<<]>>>[-]>[->+<<+>]>[-<+>]<<<<<<<<<[-]>>>
+>>>>[->>+<<<<<<<<<+>>>>>>>]>>[-<<+>>]<<<<<<<[-]<<<<<<<[->>>>>>>>>>>>>>+<<<<<<<+<
+<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<[->+>-[>+>>]>[+[-<
++>]>+>>]<<<<<<]>>>>>>>>[-]<<<<<<<<<<[-]>>>[-]>[-]>[-<<<<<+>>>>>]>[->>>>+<<<<]>>>
+[-]>[->+<<+>]>[-<+>]<<<<<<<<<[-]>>>>>>>[->>+<<<<<<<<<+>>>>>>>]>>[-<<+>>]<<<<<<<[
+-]<<<<<<<[->>>>>>>>>>>>>>+<<<<<<<+<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>
+>>>>>>>]<<<<<<<<<[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]>>>>>>>>[-]<<<<<<<<<<<[-]>>>>[
+-]>[-]>[-<<<<<<+>>>>>>]>[->>>>+<<<<]>>>[-]>[->+<<+>]>[-<+>]<<<<<<<<<[-]>>>>>>>[-
+>>+<<<<<<<<<+>>>>>>>]>>[-<<+>>]<<<<<<<[-]<<<<<<<[->>>>>>>>>>>>>>+<<<<<<<+<<<<<<<
+]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<[->+>-[>+>>]>[+[-<+>]>+>
+>]<<<<<<]>>>>>>>>[-]<<<<<<<<<<<<<<<<[-]>>>>>>>>>[-]>[-]>[-<<<<<<<<<<<+>>>>>>>>>>
+>]>[->>>>+<<<<]>>>[-]>[->+<<+>]>[-<+>]<<[-]>[-]<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>[-]
+>[-]<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>
+>>>>[-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<[[-]<[-]+>][-]<[->>+<+<]>>[-<<+>>
+]<[[-]<<<<<<<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>
+>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>
+>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<[-]+>][-
+]<[->>+<+<]>>[-<<+>>]<[[-]<<<<<<<<<<<<<+++++++++++++++++++++++++++++++++++++++++
++++++++.>>>>>>>>>>>>>]<<<<<<<<<<<<<[-]>>>>>>>>>>>>>[-]<<<<<<<<<<<<[->>>>>>>>>>>>
+>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<[-]+>][-]<[->>
++<+<]>>[-<<+>>]<[[-]<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++
+.>>>>>>>>>>>>]<<<<<<<<<<<<[-]>>>>>>>>>>>[-]+>[-]<[->>+<+<]>>[-<<+>>]<[[-]<<<<<<<
+<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]>
+>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>[->>>>>>+<<<<<<<<+>>]>>>>>>[-<<<<<<+>>
+>>>>]>]<<<<<<<[-]>[-]>>>>>>>[-]<<<<<<<<<<[->>>>>>>>+>>+<<<<<<<<<<]>>>>>>>>[-<<<<
+<<<<+>>>>>>>>]>[-]<<<<[->>>+>+<<<<]>>>[-<<<+>>>]>>>>[-]+<<<[<[-]+>>>>>[-]<<<[->>
+>>+<+<<<]>>>>[-<<<<+>>>>]<[[-]<<<<<[-]>>>>>]<<<<<[[-]>>>>[-]<<<[-]+>[-]+<<]>>-<-
+]>[-]<<<<[-]>>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]
+<[-<<<<<<<<[->>>>>>>>>+<<<<+<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<]<<[-]<<<<<<<[
+->>>>>>>>>+<<+<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<<+>>>[-]<<<<[->>>>>>>+<<<+
+<<<<]>>>>>>>[-<<<<<<<+>>>>>>>]<<<<<[-]<[->>>>>>+<<<<<+<]>>>>>>[-<<<<<<+>>>>>>]<<
+<<[-]+<[>>>>>[-]+>[-]<<<<[->>>>>+<+<<<<]>>>>>[-<<<<<+>>>>>]<[[-]<[-]>]<[[-]<<<<[
+-]<[-]+>>[-]+>>>]<<<-<<-]>>[-]<<[-]>[->+<<+>]>[-<+>]<<[[-]>>>>[-]<<<<]>[-]<<[-]<
+[-]>>>>>>]<<<[-]<<<<<<<<<[->>>>>>>>>>+<+<<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>
+>>>]<<[-]<<<[->>>>>+<<+<<<]>>>>>[-<<<<<+>>>>>]>>[-]+<<<<[>>[-]+>>>[-]<<<<[->>>>>
++<+<<<<]>>>>>[-<<<<<+>>>>>]<[[-]<<<[-]>>>]<<<[[-]>>[-]<<<<[-]+>[-]+>]<-<-]>[-]<[
+-]>>>>[-<<<+<+>>>>]<<<[->>>+<<<]<[[-]>[-]++++++++++++++++++++++++++++++++.[-][-]
+++++++++++>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>+
+>>+<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<+>>
+>>>>>>>>>>>>>>>>>>>>>]<<<<<<<[-]>>>>>>>>>[-<<+<<<<<<<+>>>>>>>>>]<<[->>+<<]<<<<<[
+-]<<<<<<<<<[->>>>>>>>>>>>>>+<<<<<+<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>
+>>>>>>>>>]<<<<<<<[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]>>>>>>>>[-]<<<<<<<<<<<<<<[-]>>
+>>>>>[-]>[-]>[-<<<<<<<<<+>>>>>>>>>]>[->>>>+<<<<]>>>>>[-]<[-<+>>+<]<[->+<]<<<<<<<
+[-]>>>>>>>>>[-<<+<<<<<<<+>>>>>>>>>]<<[->>+<<]<<<<<[-]<<<<<<<<<[->>>>>>>>>>>>>>+<
+<<<<+<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<[->+>-[>+>>]
+>[+[-<+>]>+>>]<<<<<<]>>>>>>>>[-]<<<<<<<<<<<[-]>>>>[-]>[-]>[-<<<<<<+>>>>>>]>[->>>
+>+<<<<]>>>>>[-]<[-<+>>+<]<[->+<]<<<<<<<[-]>>>>>>>>>[-<<+<<<<<<<+>>>>>>>>>]<<[->>
++<<]<<<<<[-]<<<<<<<<<[->>>>>>>>>>>>>>+<<<<<+<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<
+<<<<+>>>>>>>>>>>>>>]<<<<<<<[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]>>>>>>>>[-]<<<<<<<<<
+<[-]>>>[-]>[-]>[-<<<<<+>>>>>]>[->>>>+<<<<]>>>>>[-]<[-<+>>+<]<[->+<]<<<<<<<[-]>>>
+>>>>>>[-<<+<<<<<<<+>>>>>>>>>]<<[->>+<<]<<<<<[-]<<<<<<<<<[->>>>>>>>>>>>>>+<<<<<+<
+<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<[->+>-[>+>>]>[+[-<
++>]>+>>]<<<<<<]>>>>>>>>[-]<<<<<<<<<[-]>>[-]>[-]>[-<<<<+>>>>]>[->>>>+<<<<]>>>>>[-
+]<[-<+>>+<]<[->+<]>>[-]<[-]<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>[-]<<<<<<<<<<<<[-]>>>
+>[->>>>>>>>>>>+<<<<<<<<<<<<<<<+>>>>]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<<<<<<<
+<<<<<<<<[[-]>>>>>>>>>>>>[-]+<<<<<<<<<<<<][-]>>>>>>>>>>>>[->>>+<<<<<<<<<<<<<<<+>>
+>>>>>>>>>>]>>>[-<<<+>>>]<<<<<<<<<<<<<<<[[-]>>>>+++++++++++++++++++++++++++++++++
++++++++++++++++.<<<<]>>>>[-]<<<<[-]>>>[->>>>>>>>>>>>+<<<<<<<<<<<<<<<+>>>]>>>>>>>
+>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<<<<<<<<<<<<[[-]>>>>>>>>>>>>[-]+<<<<<<<<<<<<
+][-]>>>>>>>>>>>>[->>>+<<<<<<<<<<<<<<<+>>>>>>>>>>>>]>>>[-<<<+>>>]<<<<<<<<<<<<<<<[
+[-]>>>++++++++++++++++++++++++++++++++++++++++++++++++.<<<]>>>[-]<<<[-]>>[->>>>>
+>>>>>>>>+<<<<<<<<<<<<<<<+>>]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<<<<<<<<<
+<<<<<<[[-]>>>>>>>>>>>>[-]+<<<<<<<<<<<<][-]>>>>>>>>>>>>[->>>+<<<<<<<<<<<<<<<+>>>>
+>>>>>>>>]>>>[-<<<+>>>]<<<<<<<<<<<<<<<[[-]>>+++++++++++++++++++++++++++++++++++++
++++++++++++.<<]>>[-]>>>>>>>>>>[-]+<<<<<<<<<<<<[-]>>>>>>>>>>>>[->>>+<<<<<<<<<<<<<
+<<+>>>>>>>>>>>>]>>>[-<<<+>>>]<<<<<<<<<<<<<<<[[-]<+++++++++++++++++++++++++++++++
++++++++++++++++++.>]<[-]>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<]>>>>[-][-]++++++++++.[-]
+<<<<<<<<<<<<<<+>]
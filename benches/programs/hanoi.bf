@@ -0,0 +1,415 @@
+; Solves the towers of hanoi with 12 disks iteratively, printing every move.
+; A binary counter picks the disk to move, the smallest disk cycles between the pegs
+; and every other move is the only legal one that does not touch the smallest disk.
+; Generated, so it is plain brainfuck apart from the pragma.
+#pragma cells 16
+
+[-]+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++>>[-]+++<<[->[-]+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>]<[[-]>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>
+>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[[-]>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]+<<<[-]>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<
+<<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<+>>
+>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>+<<+<<<<<<<<<<<<<<<<<
+<]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]<[-]<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<[-]+>>[>[-]+>[-]<<<[->>>>+<+<
+<<]>>>>[-<<<<+>>>>]<[[-]<[-]>]<[[-]<<<[-]>>[-]+<[-]+>>]<<->-]<[-]>[-]<<[->+>+<<]
+>[-<+>]>[[-]<<<<<<<<<<<<<<<<<<<--->>>>>>>>>>>>>>>>>>>]<<[-]<<<<<[-]+>>>>>[-]++++
+++++++>>>[-]<<<<<<<<[->>>>>>>>>>+<<+<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]>
+[-]<<<[->>+>+<<<]>>[-<<+>>]>>>[-]<<<<<<<<[->>>>>+>>>+<<<<<<<<]>>>>>[-<<<<<+>>>>>
+]>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<
+<<<<<+>>>>>>]<<<<<<<[-]>[->+<<+>]>[-<+>]>[-]<<<[->>+>+<<<]>>[-<<+>>]>>>[-]<<<<<<
+<<[->>>>>+>>>+<<<<<<<<]>>>>>[-<<<<<+>>>>>]>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-
+]<<<[-]>>>>>>[-]>[-]>[-<<<<<<<<+>>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[->+<<+>]>[
+-<+>]<<[-]>[-]<<<<[-]>>>>>[-]>>>>>>>>[-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<
+<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<[-]+>>>>>>>>][-]<<<<<
+<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<<<<<<++++++
+++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>>]<<<<<<<<<<<<[-]>>>>[-]+>
+>>>>>>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<
+<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<
+[-]>>>[-]<<<<<<<<<<[-]>>>>>>[-]+++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++.--------------------------.[-]<<++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++.----------------------------------------------------
+------------->>[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++
+++.------------------------------.[-]<<<<<<<<<<<<<<<<<<+++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++.---------------------------------------
+-------------------------->>>>>>>>>>>>>>>>>>[-]++++++++++.[-]<<[-]<]<<][-]<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>]<[[-]>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>
+>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<
+<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[[-]>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<
+<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]+<<<<[-]>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<<+<<<<<<<<<<<<<
+<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<[-]++
++>>>>>>>>>>>>>>>[->>+<<<<<<<<<<<<<<<<<->>>>>>>>>>>>>>>]>>[-<<+>>]<<<<<<<<<<<<<<<
+<<<[->>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<-<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<
+<<+>>>>>>>>>>>>>>>>>>]<<<<<<[-]++>>>>>>[-]++++++++++>>>[-]<<<<<<<<<[->>>>>+>>>>+
+<<<<<<<<<]>>>>>[-<<<<<+>>>>>]>>>>>>[-]<<[-<<<<+>>>>>>+<<]<<<<[->>>>+<<<<]>>>>>>>
+>[-]<<<<<<<[-<+>>>>>>>>+<<<<<<<]<[->+<]>>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<
+<[-]<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[-<<<+>>>>+<
+]<<<[->>>+<<<]>>>>>>[-]<<[-<<<<+>>>>>>+<<]<<<<[->>>>+<<<<]>>>>>>>>[-]<<<<<<<[-<+
+>>>>>>>>+<<<<<<<]<[->+<]>>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<[-]>>[-]>>[-]>
+[-]>[-<<<<+>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[-<<<+>>>>+<]<<<[->>>+<<<]>>>>[-]<[
+-]<<[-]<[-]>>>>>>>>>>>>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>
+>>>>>>>>]<[[-]<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>][-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+
+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<+++++++
++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>]<<<<<<<<[-]<<<<<[-]+>>>>>>>>>
+>>>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<
+<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<+++++++++++++++++++++++++++++++++++++++++++++
++++.>>>>>>>>>>>]<<<<<<<<<<<[-]<<[-]<<<<<[-]>>>>>>>>>>[-]++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++.--------------------------.[-]<<<<<<+++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++++.-----------------------
+------------------------------------------>>>>>>[-]+++++++++++++++++++++++++++++
++++.+++++++++++++.+++++++++++++++++.------------------------------.[-]<<<<<<<<<<
+<<<<<<<<<<<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.---
+-------------------------------------------------------------->>>>>>>>>>>>>>>>>>
+>>>[-]++++++++++.[-]<<<<<<[-]<]<<][-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[[-]>[-]<<<<<<
+<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>
+>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>][-
+]+<[[-]>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<
+<<<<<<<<<<<<<<<<<[-]+<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<[-
+>>>>>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<
+<<<<<+>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<[-]+++>>>>>>>>>>>>>>[->>>>>>+<<<<
+<<<<<<<<<<<<<<<<->>>>>>>>>>>>>>]>>>>>>[-<<<<<<+>>>>>>]<<<<<<<<<<<<<<<<<<<<<<[->>
+>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<-<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<
+<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<[-]+++>>>>>>>>>>[-]++++++++++<<[-]<
+<<<<<<<[->>>>>>+>>+<<<<<<<<]>>>>>>[-<<<<<<+>>>>>>]>>>>>[-]<<<[-<<+>>>>>+<<<]<<[-
+>>+<<]>>>>>>>[-]<<<[-<<<<+>>>>>>>+<<<]<<<<[->>>>+<<<<]>>>>>[->+>-[>+>>]>[+[-<+>]
+>+>>]<<<<<<]<<[-]<<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]
+>[-<<<+>>+>]<<<[->>>+<<<]>>>>>[-]<<<[-<<+>>>>>+<<<]<<[->>+<<]>>>>>>>[-]<<<[-<<<<
++>>>>>>>+<<<]<<<<[->>>>+<<<<]>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<<<<[-]>
+>>>>>>[-]>[-]>[-<<<<<<<<<+>>>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[-<<<+>>+>]<<<[-
+>>>+<<<]>>[-]>[-]>[-]<<<<[-]>>>>>>>>>>>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<
+<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<<[-]+>>>>
+>>>>>>>>][-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<
+<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++
+++++.>>>>>>>>>>>>>]<<<<<<<<<<<<<[-]>[-]+>>>>>>>>>>>>[-]<<<<<<<<<<<<[->>>>>>>>>>>
+>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<+++
++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]<[-]<<<<
+<<[-]>>>>>[-]++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.--------
+------------------.[-]<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++.----------------------------------------------------------------->[-]++
+++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.-----------------
+-------------.[-]<<<<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++.--------------------------------------------------------------
+--->>>>>>>>>>>>>>>[-]++++++++++.[-]<[-]<]<<][-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[[-]
+>[-]<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<
+<<]>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>
+>][-]+<[[-]>[-]<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<
+<<<<<<<<<<<<<<<<<<[-]+<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<[
+->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<
+<<<<<<<<<<<<[-]+++>>>>>>>>>>>>>[->+<<<<<<<<<<<<<<->>>>>>>>>>>>>]>[-<+>]<<<<<<<<<
+<<<<<<<<[->>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<-<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<
+<<<+>>>>>>>>>>>>>>>>>]<<<<<[-]++++>>>>>[-]++++++++++>>>>[-]<<<<<<<<<[->>>>>>>>>>
++<+<<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]>[-]<<[->+>+<<]>[-<+>]>>>[-]<<<<<
+<<<[->>>>>+>>>+<<<<<<<<]>>>>>[-<<<<<+>>>>>]>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<
+[-]<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[->>+<+<]>>[-
+<<+>>]>[-]<<[->+>+<<]>[-<+>]>>>[-]<<<<<<<<[->>>>>+>>>+<<<<<<<<]>>>>>[-<<<<<+>>>>
+>]>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<[-]<<[-]>>>>>>[-]>[-]>[-<<<<<<<<+>>>>>>>>
+]>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[->>+<+<]>>[-<<+>>]<[-]<[-]<<<[-]>>>>>[-]>>>>>>>>[
+-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>
+>>>>>>>]<[[-]<<<<<<<<[-]+>>>>>>>>][-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<
+<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++
+++++.>>>>>>>>>>>>]<<<<<<<<<<<<[-]>>>>[-]+>>>>>>>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<
+<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<<<<<++++++++++++++++++++++++++++++
+++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]>>>[-]<<<<<<<<<<[-]>>>>>>[-]+++++++
++++++++++++++++++++++++++++++++++++++++++++++++++++.--------------------------.[
+-]<<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.----------
+------------------------------------------------------->>[-]++++++++++++++++++++
+++++++++++++.+++++++++++++.+++++++++++++++++.------------------------------.[-]<
+<<<<<<<<<<<<<<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.
+----------------------------------------------------------------->>>>>>>>>>>>>>>
+[-]++++++++++.[-]<<[-]<]<<][-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[[-]>[-]<<<<<<<<<<<<<
+<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>
+>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>][-]+<[[-]>[-]<<<<<<<<
+<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<<<<<<<[-]+<<<<
+<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<[->>>>>>>>>>>>>>+<<+<<<<<<<
+<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<<<<<<[-]+++>>>>>>>>
+>>>>[->>+<<<<<<<<<<<<<<->>>>>>>>>>>>]>>[-<<+>>]<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>
+>>>>>+<<<<<<<<<<<<<<-<<<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>
+>>>]<<<<<<[-]+++++>>>>>>[-]++++++++++>>[-]<<<<<<<<[->>>>>+>>>+<<<<<<<<]>>>>>[-<<
+<<<+>>>>>]>>>>>>[-]<<<[-<<<+>>>>>>+<<<]<<<[->>>+<<<]>>>>>>>>[-]<<<<<<<[-<+>>>>>>
+>>+<<<<<<<]<[->+<]>>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<<[-]>>>>>[-]>[-]>
+[-<<<<<<<+>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[-<<<<+>>>+>]<<<<[->>>>+<<<<]>>>>>
+>[-]<<<[-<<<+>>>>>>+<<<]<<<[->>>+<<<]>>>>>>>>[-]<<<<<<<[-<+>>>>>>>>+<<<<<<<]<[->
++<]>>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]>[-]>>[-]>[-]>[-<<<<+>>>>]>[-<<<<
+<<+>>>>>>]<<<<<<<[-]>[-<<<<+>>>+>]<<<<[->>>>+<<<<]>>>[-]>[-]<<<[-]<[-]>>>>>>>>>>
+>>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<
+<<<<<<[-]+>>>>>>>>>>>>>][-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>
+>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<++++++++++++++++++++++++++++
+++++++++++++++++++++.>>>>>>>>]<<<<<<<<[-]<<<<<[-]+>>>>>>>>>>>>>[-]<<<<<<<<<<<<<[
+->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[
+[-]<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<
+<<<<<<[-]<<[-]<<<<<[-]>>>>>>>>>>[-]+++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++.--------------------------.[-]<<<<<<++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++++++.--------------------------------------------
+--------------------->>>>>>[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++
+++++++++++++++.------------------------------.[-]<<<<<<<<<<<<<<<<<<+++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++.---------------------------
+-------------------------------------->>>>>>>>>>>>>>>>>>[-]++++++++++.[-]<<<<<<[
+-]<]<<][-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[[-]>[-]<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>
+>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<
+<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>][-]+<[[-]>[-]<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>
+>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<<<<<<[-]+<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>[-]<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<<<<<<+<<<<<<<<<<<]>>>>>>>>>>>>>>>>>
+[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<[-]+++>>>>>>>>>>>[->>>>>>
++<<<<<<<<<<<<<<<<<->>>>>>>>>>>]>>>>>>[-<<<<<<+>>>>>>]<<<<<<<<<<<<<<<<<<<<<<[->>>
+>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<-<<<<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<
+<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<[-]++++++>>>>>>>>>>[-]++++++++++<[-]
+<<<<<<<<<[->>>>>>+>>>+<<<<<<<<<]>>>>>>[-<<<<<<+>>>>>>]>>>>>[-]<<[-<<<+>>>>>+<<]<
+<<[->>>+<<<]>>>>>>>[-]<<<[-<<<<+>>>>>>>+<<<]<<<<[->>>>+<<<<]>>>>>[->+>-[>+>>]>[+
+[-<+>]>+>>]<<<<<<]<<<[-]<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<<+>>>>>>>]<<
+<<<<[-]<[-<<+>>>+<]<<[->>+<<]>>>>>[-]<<[-<<<+>>>>>+<<]<<<[->>>+<<<]>>>>>>>[-]<<<
+[-<<<<+>>>>>>>+<<<]<<<<[->>>>+<<<<]>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<[-]<
+<<[-]>>>>>>>[-]>[-]>[-<<<<<<<<<+>>>>>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[-<<+>>>+
+<]<<[->>+<<]>>>[-]<[-]>>[-]<<<<[-]>>>>>>>>>>>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<
++<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<<[-
+]+>>>>>>>>>>>>][-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<
+<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++
+++++++++++.>>>>>>>>>>>>>]<<<<<<<<<<<<<[-]>[-]+>>>>>>>>>>>>[-]<<<<<<<<<<<<[->>>>>
+>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<
+<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]<[
+-]<<<<<<[-]>>>>>[-]++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.--
+------------------------.[-]<+++++++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++.-----------------------------------------------------------------
+>[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.-----------
+-------------------.[-]<<<<<<<<<<<<+++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++.-----------------------------------------------------------
+------>>>>>>>>>>>>[-]++++++++++.[-]<[-]<]<<][-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[[-]
+>[-]<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<]>>>>>>
+>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>][-]+<[[-]>[-]<<<
+<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<<<<<[-]+<<<<<
+<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<[->>>>>>>>>>>+<+<<<<<<<<<<]>
+>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<<<<<<<<<<<[-]+++>>>>>>>>>>[->+<<<<<<<<<<<->
+>>>>>>>>>]>[-<+>]<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<<<<<<<<<<<-<<<<<<]>>>>>>>
+>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<[-]+++++++>>>>>[-]+++++++++
++>>>[-]<<<<<<<<[->>>>>>>>>>+<<+<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]>[-]<<
+<[->>+>+<<<]>>[-<<+>>]>>>[-]<<<<<<<<[->>>>>+>>>+<<<<<<<<]>>>>>[-<<<<<+>>>>>]>[->
++>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<
++>>>>>>]<<<<<<<[-]>[->+<<+>]>[-<+>]>[-]<<<[->>+>+<<<]>>[-<<+>>]>>>[-]<<<<<<<<[->
+>>>>+>>>+<<<<<<<<]>>>>>[-<<<<<+>>>>>]>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<<<[
+-]>>>>>>[-]>[-]>[-<<<<<<<<+>>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[->+<<+>]>[-<+>]
+<<[-]>[-]<<<<[-]>>>>>[-]>>>>>>>>[-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<<<]>>
+>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<[-]+>>>>>>>>][-]<<<<<<<<[-
+>>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<<<<<<+++++++++++
++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>>]<<<<<<<<<<<<[-]>>>>[-]+>>>>>>
+>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<<
+<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]>>
+>[-]<<<<<<<<<<[-]>>>>>>[-]++++++++++++++++++++++++++++++++++++++++++++++++++++++
+++++.--------------------------.[-]<<+++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++.---------------------------------------------------------
+-------->>[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.--
+----------------------------.[-]<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++.--------------------------------------------------
+--------------->>>>>>>>>>>>[-]++++++++++.[-]<<[-]<]<<][-]<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>]<[[-]>[-]<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<
+]>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>][-]+<[[-]>[-
+]<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<<<<[-]+<<<<
+<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<[->>>>>>>>>>>+<<+<<<<<<<<<]
+>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<<<<<<<<<<<[-]+++>>>>>>>>>[->>+<<<<<<<<<<<-
+>>>>>>>>>]>>[-<<+>>]<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>+<<<<<<<<<<<-<<<<<<<]>
+>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>]<<<<<<[-]++++++++>>>>>>
+[-]++++++++++>>>[-]<<<<<<<<<[->>>>>+>>>>+<<<<<<<<<]>>>>>[-<<<<<+>>>>>]>>>>>>[-]<
+<[-<<<<+>>>>>>+<<]<<<<[->>>>+<<<<]>>>>>>>>[-]<<<<<<<[-<+>>>>>>>>+<<<<<<<]<[->+<]
+>>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<[-]<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]
+>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[-<<<+>>>>+<]<<<[->>>+<<<]>>>>>>[-]<<[-<<<<+>>>>>>+
+<<]<<<<[->>>>+<<<<]>>>>>>>>[-]<<<<<<<[-<+>>>>>>>>+<<<<<<<]<[->+<]>>>>>>[->+>-[>+
+>>]>[+[-<+>]>+>>]<<<<<<]<<<[-]>>[-]>>[-]>[-]>[-<<<<+>>>>]>[-<<<<<<<+>>>>>>>]<<<<
+<<[-]<[-<<<+>>>>+<]<<<[->>>+<<<]>>>>[-]<[-]<<[-]<[-]>>>>>>>>>>>>>[-]<<<<<<<<[->>
+>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<<<<<<<<<<<[-]+>>>>>>>>
+>>>>>][-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<
+<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++
+++.>>>>>>>>]<<<<<<<<[-]<<<<<[-]+>>>>>>>>>>>>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+
+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<++++
+++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]<<[-]<<<<
+<[-]>>>>>>>>>>[-]++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.----
+----------------------.[-]<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++.--------------------------------------------------------------
+--->>>>>>[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.---
+---------------------------.[-]<<<<<<<<<<<<<<<++++++++++++++++++++++++++++++++++
++++++++++++++++++++++++++++++++.------------------------------------------------
+----------------->>>>>>>>>>>>>>>[-]++++++++++.[-]<<<<<<[-]<]<<][-]<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>]<[[-]>[-]<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<
+<<<<<<]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>][-]+<[[-]
+>[-]<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<<<[-]+<<<<
+<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<[->>>>>>>>>>>>>>+<<<<<<+<<<
+<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<<<<<<[-]+++>>>>>>>>
+[->>>>>>+<<<<<<<<<<<<<<->>>>>>>>]>>>>>>[-<<<<<<+>>>>>>]<<<<<<<<<<<<<<<<<<<<<<[->
+>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<-<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<
+<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<[-]+++++++++>>>>>>>>>>[-]+++++++++
++<<[-]<<<<<<<<[->>>>>>+>>+<<<<<<<<]>>>>>>[-<<<<<<+>>>>>>]>>>>>[-]<<<[-<<+>>>>>+<
+<<]<<[->>+<<]>>>>>>>[-]<<<[-<<<<+>>>>>>>+<<<]<<<<[->>>>+<<<<]>>>>>[->+>-[>+>>]>[
++[-<+>]>+>>]<<<<<<]<<[-]<<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<+>>>>>>]<<<
+<<<<[-]>[-<<<+>>+>]<<<[->>>+<<<]>>>>>[-]<<<[-<<+>>>>>+<<<]<<[->>+<<]>>>>>>>[-]<<
+<[-<<<<+>>>>>>>+<<<]<<<<[->>>>+<<<<]>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]<
+<<<[-]>>>>>>>[-]>[-]>[-<<<<<<<<<+>>>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[-<<<+>>+
+>]<<<[->>>+<<<]>>[-]>[-]>[-]<<<<[-]>>>>>>>>>>>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+
+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<<[
+-]+>>>>>>>>>>>>][-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<
+<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<<<+++++++++++++++++++++++++++++++++++++
++++++++++++.>>>>>>>>>>>>>]<<<<<<<<<<<<<[-]>[-]+>>>>>>>>>>>>[-]<<<<<<<<<<<<[->>>>
+>>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<
+<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]<
+[-]<<<<<<[-]>>>>>[-]++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.-
+-------------------------.[-]<++++++++++++++++++++++++++++++++++++++++++++++++++
++++++++++++++++.----------------------------------------------------------------
+->[-]++++++++++++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.----------
+--------------------.[-]<<<<<<<<<+++++++++++++++++++++++++++++++++++++++++++++++
+++++++++++++++++++.-------------------------------------------------------------
+---->>>>>>>>>[-]++++++++++.[-]<[-]<]<<][-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[[-]>[-]<
+<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>[
+-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>][-]+<[[-]>[-]<<<<<<<<<<<<<<<<<<<[-]>>>>
+>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<<[-]+<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>>[-]<<<<<<<[->>>>>>>>+<+<<<<<<<]>>>>>>>>[-<<<<<<<<+>>>>>>>>]<<<<<<<<[-]
++++>>>>>>>[->+<<<<<<<<->>>>>>>]>[-<+>]<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<<<<<
+<<<-<<<<<<<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<[-]+++
++++++++>>>>>[-]++++++++++>>>>[-]<<<<<<<<<[->>>>>>>>>>+<+<<<<<<<<<]>>>>>>>>>>[-<<
+<<<<<<<<+>>>>>>>>>>]>[-]<<[->+>+<<]>[-<+>]>>>[-]<<<<<<<<[->>>>>+>>>+<<<<<<<<]>>>
+>>[-<<<<<+>>>>>]>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<[-]<[-]>>>>>[-]>[-]>[-<<<<<
+<<+>>>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[->>+<+<]>>[-<<+>>]>[-]<<[->+>+<<]>[-<+>
+]>>>[-]<<<<<<<<[->>>>>+>>>+<<<<<<<<]>>>>>[-<<<<<+>>>>>]>[->+>-[>+>>]>[+[-<+>]>+>
+>]<<<<<<]<<<[-]<<[-]>>>>>>[-]>[-]>[-<<<<<<<<+>>>>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[
+-]<[->>+<+<]>>[-<<+>>]<[-]<[-]<<<[-]>>>>>[-]>>>>>>>>[-]<<<<<<<<<<<<[->>>>>>>>>>>
+>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<[-]+>>
+>>>>>>][-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<[[-]<<<
+<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>>]<<<<<<<<<
+<<<[-]>>>>[-]+>>>>>>>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>
+>>>>>>]<[[-]<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>>>>>
+>>>]<<<<<<<<<<<[-]>>>[-]<<<<<<<<<<[-]>>>>>>[-]++++++++++++++++++++++++++++++++++
+++++++++++++++++++++++++.--------------------------.[-]<<+++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++.-------------------------------------
+---------------------------->>[-]++++++++++++++++++++++++++++++++.+++++++++++++.
++++++++++++++++++.------------------------------.[-]<<<<<<<<<+++++++++++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++.---------------------------------
+-------------------------------->>>>>>>>>[-]++++++++++.[-]<<[-]<]<<][-]<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>]<[[-]>[-]<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<
+<<<<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>][-]+<[[-]>[-]<<<
+<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<<[-]+<<<<<<<<<<<<<[-]>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<[->>>>>>>>+<<+<<<<<<]>>>>>>>>[-<<<<<<<<+
+>>>>>>>>]<<<<<<<<[-]+++>>>>>>[->>+<<<<<<<<->>>>>>]>>[-<<+>>]<<<<<<<<<<<<<<<<<<[-
+>>>>>>>>>>>>>>>>>>+<<<<<<<<-<<<<<<<<<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<+>>
+>>>>>>>>>>>>>>>>]<<<<<<[-]+++++++++++>>>>>>[-]++++++++++>>[-]<<<<<<<<[->>>>>+>>>
++<<<<<<<<]>>>>>[-<<<<<+>>>>>]>>>>>>[-]<<<[-<<<+>>>>>>+<<<]<<<[->>>+<<<]>>>>>>>>[
+-]<<<<<<<[-<+>>>>>>>>+<<<<<<<]<[->+<]>>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-
+]<<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[-<<<<+>>>+>]<<
+<<[->>>>+<<<<]>>>>>>[-]<<<[-<<<+>>>>>>+<<<]<<<[->>>+<<<]>>>>>>>>[-]<<<<<<<[-<+>>
+>>>>>>+<<<<<<<]<[->+<]>>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<[-]>[-]>>[-]>[-]>
+[-<<<<+>>>>]>[-<<<<<<+>>>>>>]<<<<<<<[-]>[-<<<<+>>>+>]<<<<[->>>>+<<<<]>>>[-]>[-]<
+<<[-]<[-]>>>>>>>>>>>>>[-]<<<<<<<<[->>>>>>>>>+<+<<<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>
+>>>>>>]<[[-]<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>][-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<
+<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<+++++++++
++++++++++++++++++++++++++++++++++++++++.>>>>>>>>]<<<<<<<<[-]<<<<<[-]+>>>>>>>>>>>
+>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<
+<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<+++++++++++++++++++++++++++++++++++++++++++++++
++.>>>>>>>>>>>]<<<<<<<<<<<[-]<<[-]<<<<<[-]>>>>>>>>>>[-]++++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++.--------------------------.[-]<<<<<<+++++++++++
+++++++++++++++++++++++++++++++++++++++++++++++++++++++.-------------------------
+---------------------------------------->>>>>>[-]+++++++++++++++++++++++++++++++
++.+++++++++++++.+++++++++++++++++.------------------------------.[-]<<<<<<<<<<<<
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.--------------
+--------------------------------------------------->>>>>>>>>>>>[-]++++++++++.[-]
+<<<<<<[-]<]<<][-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<
++<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[[-]>[-]<<<<<<<<<<<<<<<<[->>>>>>>>
+>>>>>>>>>+<+<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>
+>>>][-]+<[[-]>[-]<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>]>[[-]<<<<<<<<<<<<<<<<<[-]+
+<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<[->>>>>>>>>>>+<<<<<<+<<
+<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<<<<<<<<<<<[-]+++>>>>>[->>>>>>+<<<<<<<<
+<<<->>>>>]>>>>>>[-<<<<<<+>>>>>>]<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<
+<<<<<<<<<<-<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>
+>>>>>>>>>>>>]<<<<<<<<<<[-]++++++++++++>>>>>>>>>>[-]++++++++++<[-]<<<<<<<<<[->>>>
+>>+>>>+<<<<<<<<<]>>>>>>[-<<<<<<+>>>>>>]>>>>>[-]<<[-<<<+>>>>>+<<]<<<[->>>+<<<]>>>
+>>>>[-]<<<[-<<<<+>>>>>>>+<<<]<<<<[->>>>+<<<<]>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<
+<<]<<<[-]<[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[-<<+>>
+>+<]<<[->>+<<]>>>>>[-]<<[-<<<+>>>>>+<<]<<<[->>>+<<<]>>>>>>>[-]<<<[-<<<<+>>>>>>>+
+<<<]<<<<[->>>>+<<<<]>>>>>[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<[-]<<<[-]>>>>>>>[-]
+>[-]>[-<<<<<<<<<+>>>>>>>>>]>[-<<<<<<<+>>>>>>>]<<<<<<[-]<[-<<+>>>+<]<<[->>+<<]>>>
+[-]<[-]>>[-]<<<<[-]>>>>>>>>>>>>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]
+>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<<[-]+>>>>>>>>>>>>]
+[-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>
+>>>>>>>>]<[[-]<<<<<<<<<<<<<++++++++++++++++++++++++++++++++++++++++++++++++.>>>>
+>>>>>>>>>]<<<<<<<<<<<<<[-]>[-]+>>>>>>>>>>>>[-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<
+<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<<<<<<<<<<<++++++++++++
+++++++++++++++++++++++++++++++++++++.>>>>>>>>>>>]<<<<<<<<<<<[-]<[-]<<<<<<[-]>>>>
+>[-]++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.-----------------
+---------.[-]<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.
+----------------------------------------------------------------->[-]+++++++++++
++++++++++++++++++++++.+++++++++++++.+++++++++++++++++.--------------------------
+----.[-]<<<<<<+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.
+----------------------------------------------------------------->>>>>>[-]++++++
+++++.[-]<[-]<]<<]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]<]
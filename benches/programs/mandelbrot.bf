@@ -0,0 +1,276 @@
+; Prints an ascii mandelbrot set, 24 by 10 characters with at most 8 iterations per point.
+; The numbers are fixed point with 4 fractional bits, stored as a sign and a magnitude.
+; Generated, so it is plain brainfuck apart from the pragma.
+#pragma cells 16
+
+[-]++++++++++>>[-]+>[-]++++++++++++++++++<<<[->[-]++++++++++++++++++++++++>>>[-]
++>[-]++++++++++++++++++++++++++++++++<<<<[->>>>>[-]>[-]>[-]>[-]>>>>>>>>>[-]+++++
++++>[-]+[>>>[-]>>>>[-]<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<
+<<<<<]>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]<[-<<<<<<<
+<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>+<<<<<+<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>[-<
+<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]<]<[-]++++++++++++++++>>>[-]<<<<<<[->>>
+>+>>+<<<<<<]>>>>[-<<<<+>>>>]>>>>[-]<<<<<[->+>>>>+<<<<<]>[-<+>]>>[->+>-[>+>>]>[+[
+-<+>]>+>>]<<<<<<]<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>[-]>>>>>[-]>[-]>[-<<<<<<<+>>>>
+>>>]>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<[-]>>[-]>[-]<<<<<<<
+<<<<<<<<[-]<<<<[->>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<+<<<<]>>>>>>>>>>>>>>>>>>>[-<
+<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>+
+<<<<<<<<<<<<<<<+<<<<]>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>
+>][-]++<<[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>+<<<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>[-
+<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<<<<[-]>>>[->+<<<<+>>>]>[-<+>]<<[-]+<<[>>>>[-]
++>[-]<<<<[->>>>>>>>>>>>+<<<<<<<<+<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<
+<<<<<<[[-]<[-]>]<[[-]<<[-]<<[-]+>[-]+>>>]<<<-<-]>[-]<[-]>>[-<+<+>>]<[->+<]<[[-]<
+<<<<<<<<<<<[-]>>>>>>>>>>>>]>>[-]>[-][-]>[-]<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>
++<+<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>]<
+[-<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>+<<+<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>[-
+<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>]<]<<<[-]++++++++++++++++>>>>>[-]<<<[->+>>+
+<<<]>[-<+>]>>>>[-]<<<<<<<[->>>+>>>>+<<<<<<<]>>>[-<<<+>>>]>>[->+>-[>+>>]>[+[-<+>]
+>+>>]<<<<<<]<<<<<<<<<<<<<<<[-]>>>>>>>>>[-]>>>>>>>[-]>[-]>[-<<<<<<<<<+>>>>>>>>>]>
+[-<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>]<<<<<<<[-]<<<[-]>[-]<<<<<<<<<<<[-]<<<<
+[->>>>>>>>>>>>>>>+<<<<<<<<<<<+<<<<]>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>
+>>>]<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>+<<<<<<<<<<<+<<<<]>>>>>>>>>>>>>>>[-<<<<<<<<<
+<<<<<<+>>>>>>>>>>>>>>>][-]++>[-]<<<<<<<<<<<<[->>>>>>>>>>>>>>+<<+<<<<<<<<<<<<]>>>
+>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<[-]<<[->>>+<+<<]>>>[-<<<+>>>]<<<<[-]
++>>>[>[-]+>[-]<<<[->>>>>>>>>>>+<<<<<<<<+<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>
+]<<<<<<<<[[-]<[-]>]<[[-]<<<<[-]>>>[-]+<[-]+>>]<<->-]<[-]>[-]<<<[->>+>+<<<]>>[-<<
++>>]>[[-]<<<<<<<<<<<<<[-]>>>>>>>>>>>>>]<<<[-]>[-]<<<<<<<<<<<<<[-]>>[-]>>>>>>>>>[
+-]<<<<<<<<<<[->>>>>>>>>>>>+<<+<<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>
+]<<<<<<<<<<[->>>>>>>>>>+<<+<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<<<[-]++++
++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++++>>[-]<[->>>+<<+<]>>
+>[-<<<+>>>]>[-]<<<<<[->>>>+>+<<<<<]>>>>[-<<<<+>>>>]<[-]+>>[<[-]+>>[-]<<<<[->>>>>
++<+<<<<]>>>>>[-<<<<<+>>>>>]<[[-]<<[-]>>]<<[[-]<[-]>>[-]+<<<[-]+>>]<<->>>-]<<<[-]
+<[-]<[-]>>>>>[-]<<[-<+>>>+<<]<[->+<][-]+>>>[[-]<<<[-]<<<[-]>>>>>>]<<<[[-]>>[-]>>
+>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<
+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<+
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>+<<<<<<<<<<<<<+<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<]<[-]++++++++<<<<<<
+<[-]<<<<[->>>>>>>>>>>>+<<<<<<<<+<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<
+<<<[-]>>>>>[->+<<<<<<+>>>>>]>[-<+>]<<<<<<<<[->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]<<<<
+<<<<<<<<<[-]>>>>>>>>>>>>[-]>>[-]>[-]>[-<<<<+>>>>]>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>
+>>>>>>>]<<<<<<<<[-]>>>[-]>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<[-]<<<<<<<<[->>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<+<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<
+<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<+<<<<<<]>>>>>>>>>>>>
+>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>][-]++<<
+<<<<<<<[-]<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<+<<<<<<<<<<<<]>>>>>>>>>
+>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<[-]>>>>
+>>>>>>>[->+<<<<<<<<<<<<+>>>>>>>>>>>]>[-<+>]<<<<<<<<<[-]+<<<[>>>>>>>>>>>>[-]+>[-]
+<<<<<<<<<<<[->>>>>>>>>>>>+<+<<<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]
+<[[-]<[-]>]<[[-]<<<<<<<<<[-]<<<[-]+>>[-]+>>>>>>>>>>]<<<<<<<<<<-<<-]>>[-]<<[-]>>>
+[-<+<<+>>>]<[->+<]<<[[-]<<<<<<<<<<[-]>>>>>>>>>>]>>>[-]>>>>>>>>[-][-]<<<<<<<<<<<<
+<<<<<<<<<[->>>>>>>>>>>>>+>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>[-<<<<<<<<<
+<<<<+>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>+>>>>>>>>
++<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<
+<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>][-]++<[-]>>>>>>>>>[->>+<<<<<<<<<<<+>>>>>>>>>]>
+>[-<<+>>]<[-]<<<<<<<<<[->>>>>>>>>>+<+<<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>
+]<<<<<<<<<<<<<[-]+>>>>>>>>>>>>[>[-]+>[-]<<<<<<<<<<<<[->>>>>>>>>>>>>+<+<<<<<<<<<<
+<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<[[-]<[-]>]<[[-]<<<<<<<<<<<<<[-]>>
+>>>>>>>>>>[-]+<<<<<<<<<<[-]+>>>>>>>>>>>]<<<<<<<<<<<->>>>>>>>>>-]<<<<<<<<<<[-]>>>
+>>>>>>>[-]<<<<<<<<<<<<[->>+>>>>>>>>>>+<<<<<<<<<<<<]>>[-<<+>>]>>>>>>>>>>[[-]<[-]>
+]<<<<<<<<<<<<[-]>>>[-][-]>>>>>>>>[-<<<<<<<<<<<+>>>+>>>>>>>>]<<<<<<<<<<<[->>>>>>>
+>>>>+<<<<<<<<<<<][-]+>>>[[-]<<<[-]>>[-]<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>+<<<<
+<<<<<<<<+<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>
+>>>>>>>>>>>>>]<[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<[-
+]+>[>[-]+>[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<
+<<<<<<<<<+>>>>>>>>>>>>>>]<[[-]<[-]>]<[[-]<<[-]>[-]+<<<<<<<<<<<[-]+>>>>>>>>>>>>]<
+<<<<<<<<<<<->>>>>>>>>>>-]<<<<<<<<<<<[-]>>>>>>>>>>>[-]<[-<<<<<<<<<<+>>>>>>>>>>>+<
+]<<<<<<<<<<[->>>>>>>>>>+<<<<<<<<<<][-]+>>>>>>>>>>>[[-]<<<<<<<<<<<[-]<<<<<<<<<<<<
+<<<<<[-]>>>>>>[->>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>]>>>
+>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<-<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[
+-]>>>>>>[->>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>]>>>>>>>
+>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>]<]<<<<<<<<<
+<<[[-]<<<<<<<<<<<<<<<<<[-]<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<+<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<[
+->>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<->>>>>>]>>>>>>>>>>>>>>>>>>
+>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<[-]<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<+<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<]>>>>>>>>>>[-]<<<<<<<<<]<
+<<[[-]<<<<<<<<<<<<<<<[-]>>>>>>[->>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<
+<+>>>>>>]>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<
+<<<<<<<<<<<<+<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>
+[->>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>]>>>>>>>>>>>>>>>>>>>
+>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<]>>>>>>>>>>>[-]<<
+<<<<<<<<<<<<<<<<<<<<<[-]+>>>>>>>>>>>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<[->>
+>>>>>>>>>>>>>>>+>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<
+<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>+>>>>>>>>+<<<<<<<<<<<<
+<<<<<<<<<<<]>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>][-]++>>>>>>>>>[-]<[
+-<<<<<<<<<+>>>>>>>>>>+<]<<<<<<<<<[->>>>>>>>>+<<<<<<<<<]>>>>>>>>>>>[-]<<<<<<<<<<[
+-<+>>>>>>>>>>>+<<<<<<<<<<]<[->+<]<<[-]+>>>>>>>>>>>>>[<<<<<<<<<<<[-]+>>>>>>>>>>>>
+[-]<<[->>>+<+<<]>>>[-<<<+>>>]<[[-]<<<<<<<<<<<<[-]>>>>>>>>>>>>]<<<<<<<<<<<<[[-]<<
+[-]>>>>>>>>>>>>>[-]+<[-]+<<<<<<<<<<]>>>>>>>>>>->-]<[-]>[-]<<<<<<<<<<<<<[->>>>>>>
+>>>>>+>+<<<<<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]>[[-]<<[-]>>]<<<<<
+<<<<<<<<[-]>>>[-][-]>>>>>>>>[-<<<<<<<<<<<+>>>+>>>>>>>>]<<<<<<<<<<<[->>>>>>>>>>>+
+<<<<<<<<<<<][-]+>>>[[-]<<<[-]>>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>
+>>>>>>>>>>>>>>>>>>+<<+<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<
+<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<[-]<<<<<<<<<<<<
+<[->>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>
+>[-<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>]<[-]+<<<<<<<<<<<[>>>>>>>>
+>>>>[-]+>[-]<<<[->>>>+<+<<<]>>>>[-<<<<+>>>>]<[[-]<[-]>]<[[-]<[-]<<<<<<<<<<<[-]+>
+>>>>>>>>>[-]+>>]<<-<<<<<<<<<<-]>>>>>>>>>>[-]<<<<<<<<<<[-]>>>>>>>>>>>[-<+<<<<<<<<
+<<+>>>>>>>>>>>]<[->+<][-]+<<<<<<<<<<[[-]>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<[-]<<<<<
+<[->>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<+<<<<<<]>>>>>>>>>>>>>>>>>>>>
+>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<
+<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<-<<<<]>>>>>>>>>>>>>>>
+>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<
+<<<<<<[-]<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<+<<<<<<]>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>]<<<<<<<<<<<<]>>>>>>>>>>[[-]<<<<<<<<<<<<<<<<<<<[-]<<<<[->>>>>>>>>>>>>>>>>>>>>
+>>>>+<<<<<<<<<<<<<<<<<<<<<+<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<
+<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>
+>>>>>>>+<<<<<<<<<<<<<<<<<<<<<-<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<
+<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<[-]<<<<[->>>>>>>
+>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<+<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<
+<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>]<<]>[-]<<<<<<<<<<]<<<[[-]<<<<<
+<<[-]<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<+<<<<<<]>>>>>>>>>>>>
+>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<
+<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<+<<<<]>>>>>>>>>>>>>
+>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<
+<<<<[-]<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<+<<<<<<]>>>>>>>>
+>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<
+<<<<<<<<<<]>>>>>>>>>>>[-][-]<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>+>>>>>>>>+<<<<<<<<<<
+<<<<<<<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>
+>>>>>>>>>>>>>>>+>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>
+[-<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>][-]++>>>>>>>>>>[-]<<[->+>+<<]>
+[-<+>]<<<<<<<<<<[-]>[->>>>>>>>>+<<<<<<<<<<+>]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<<<<
+<<<<<<<<[-]+>>[>>>>>>>>>>[-]+>>[-]<[->>+<+<]>>[-<<+>>]<[[-]<<[-]>>]<<[[-]<<<<<<<
+<<<<<[-]>>[-]+>>>>>>>>>>>[-]+<]>-<<<<<<<<<<<-]>>>>>>>>>>>[-]<<<<<<<<<<<[-]<<[->>
+>>>>>>>>>>>+<<<<<<<<<<<+<<]>>>>>>>>>>>>>[-<<<<<<<<<<<<<+>>>>>>>>>>>>>]<<<<<<<<<<
+<[[-]>>>>>>>>>[-]<<<<<<<<<]<<[-]>>>[-][-]>>>>>>>>[-<<<<<<<<<<<+>>>+>>>>>>>>]<<<<
+<<<<<<<[->>>>>>>>>>>+<<<<<<<<<<<][-]+>>>[[-]<<<[-]>>>>>>>>>>>>>[-]<<<<<<<<<<<<<<
+<<<<<<[->>>>>>>>>>>>>>>>>>>>>+<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>[-<<<<
+<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<<[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>>]<<<<<<<<<<<<[-]+>>>>>>>>>>[>>[-]+>[-]<<[->>>+<+<<]>>>[-<<<+>>>]<[[-]<[
+-]>]<[[-]<<<<<<<<<<<<[-]>>>>>>>>>>[-]+>[-]+>]<-<-]>[-]<[-]<<<<<<<<<<[->>>>>>>>>>
+>+<+<<<<<<<<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>>>>>>>>>>>][-]+<[[-]>[-]<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<[-]>>>>>>>>>>[->>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<+>>>>>>>>>>]>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>
+>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<-<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<[-]>>>>>>>>>>[->>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>
+>>]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]<<]>[[-
+]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<+<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<[->>>>>>>
+>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<->>>>>>>>>>]>>>>>>>>>>>>>>>>>>>>>[
+-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[-]
+<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+<<]>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>]<]<<<<<<<<<<<[-]>]<<<[[-]<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>[->>
+>>>>>>>+<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]<<<<<<<<<<
+<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<+<<]>>>>>>>>>>>>>>>>>>>>>[
+-<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>[-
+>>>>>>>>>>+<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]<<]
+>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<->>>>>>>>>>>>>>>>>[-]+<<<<<<<<[-]<<<<<<<<<[->>>>>
+>+>>>+<<<<<<<<<]>>>>>>[-<<<<<<+>>>>>>]>>>[[-]>>>>>>>>[-]<<<<<<<<]>>>>>>>>[[-]<<<
+<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<]>[-]<<<<<<<<<<<<<[-]>[-]>[-]>[-]>
+[-]>[-]>[-]>[-]>>]>>>>[-]<<<<<[->>>>>>>+<<+<<<<<]>>>>>>>[-<<<<<<<+>>>>>>>][-]+<<
+<[-]>[->>>>>>>>>>>>+<<<<<<<<<<<<<+>]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<
+<<<<<<<<<[[-]>>>[-]<<<]>>>[[-]<<<[-]+++++++++++++++++++++++++++++++++++.[-]>[-]+
++++++++++>>]<<->>[-]+<<<[-]>[->>>>>>>>>>>>+<<<<<<<<<<<<<+>]>>>>>>>>>>>>[-<<<<<<<
+<<<<<+>>>>>>>>>>>>]<<<<<<<<<<<<<[[-]>>>[-]<<<]>>>[[-]<<<[-]+++++++++++++++++++++
++++++++++++++++++++++.[-]>[-]++++++++++>>]<<->>[-]+<<<[-]>[->>>>>>>>>>>>+<<<<<<<
+<<<<<<+>]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<<<<<<<<<<[[-]>>>[-]<<<]>>>[
+[-]<<<[-]+++++++++++++++++++++++++++++++++++++++++++.[-]>[-]++++++++++>>]<<->>[-
+]+<<<[-]>[->>>>>>>>>>>>+<<<<<<<<<<<<<+>]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]
+<<<<<<<<<<<<<[[-]>>>[-]<<<]>>>[[-]<<<[-]++++++++++++++++++++++++++++++++++++++++
++++++++++++++++++++++.[-]>[-]++++++++++>>]<<->>[-]+<<<[-]>[->>>>>>>>>>>>+<<<<<<<
+<<<<<<+>]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<<<<<<<<<<[[-]>>>[-]<<<]>>>[
+[-]<<<[-]+++++++++++++++++++++++++++++++++++++++++++++++++++++++++++.[-]>[-]++++
+++++++>>]<<->>[-]+<<<[-]>[->>>>>>>>>>>>+<<<<<<<<<<<<<+>]>>>>>>>>>>>>[-<<<<<<<<<<
+<<+>>>>>>>>>>>>]<<<<<<<<<<<<<[[-]>>>[-]<<<]>>>[[-]<<<[-]++++++++++++++++++++++++
+++++++++++++++++++++++++++++++++++.[-]>[-]++++++++++>>]<<->>[-]+<<<[-]>[->>>>>>>
+>>>>>+<<<<<<<<<<<<<+>]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<<<<<<<<<<[[-]>
+>>[-]<<<]>>>[[-]<<<[-]++++++++++++++++++++++++++++++++++++++++++++.[-]>[-]++++++
+++++>>]<<->>[-]+<<<[-]>[->>>>>>>>>>>>+<<<<<<<<<<<<<+>]>>>>>>>>>>>>[-<<<<<<<<<<<<
++>>>>>>>>>>>>]<<<<<<<<<<<<<[[-]>>>[-]<<<]>>>[[-]<<<[-]++++++++++++++++++++++++++
+++++++++++++++++++++.[-]>[-]++++++++++>>]<<->>[-]+<<<[-]>[->>>>>>>>>>>>+<<<<<<<<
+<<<<<+>]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<<<<<<<<<<[[-]>>>[-]<<<]>>>[[
+-]<<<[-]++++++++++++++++++++++++++++++++.[-]>[-]++++++++++>>]<<-[-]>>[-]++>>[-]<
+<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>+>>>+<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>
+>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]<[->+>>>+<<<<]>[-<+>][-
+]++>>>>>>>>>>>>[-]<<<<<<<<<[->>>>>>>>>>>+<<+<<<<<<<<<]>>>>>>>>>>>[-<<<<<<<<<<<+>
+>>>>>>>>>>]<[-]<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<
+<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<<<<[-]+>>>>>>>>>>>[>[-]+>[-]<<<[->>>>+<+<<<]
+>>>>[-<<<<+>>>>]<[[-]<[-]>]<[[-]<<<<<<<<<<<<[-]>>>>>>>>>>>[-]+<[-]+>>]<<->-]<[-]
+>[-]<<<<<<<<<<<[->>>>>>>>>>+>+<<<<<<<<<<<]>>>>>>>>>>[-<<<<<<<<<<+>>>>>>>>>>]>[[-
+]<<<<<<<<<<[-]>>>>>>>>>>]<<<<<<<<<<<[-]<<[-][-]>>>[-<+<<+>>>]<[->+<][-]+<<[[-]>>
+[-]>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>+<<<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<[-]<<<<<<<<<<
+<<<[->>>>>>>>>>>>>>+<+<<<<<<<<<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>
+>]<<[-]+>[>[-]+>[-]<<<<[->>>>>+<+<<<<]>>>>>[-<<<<<+>>>>>]<[[-]<[-]>]<[[-]<<[-]>[
+-]+<<[-]+>>>]<<<->>-]<<[-]>>[-]<[-<+>>+<]<[->+<][-]+>>[[-]<<[-]<[-]<<<<<<<<<<<<<
+<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<+<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
++>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<[->>>>>>>>>>>>>>+<<<<-<<<<<<<<
+<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<[-]<<<<<<<<<<<
+<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<<+<<<<<<<<<<<<<<<<<
+<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<]<<[[-]<[-]<<<<<<<<<<[->>>>>>>>>>>>>>+<<<<+<<<<<
+<<<<<]>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<+>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<[-]>[->>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<
+<<+>]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>]<<<]>[-]<<<<<<<<<<<<<]>
+>[[-]>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>+<<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<[->>>>>>>>>>
+>>+<<+<<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<<<<<<<<<<<<[-]<<<<<
+<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<+<<<<<<<<<<<<<<<
+<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<]>[-]<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>
+>>>>>>>[->>>>>+<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>]>>>>>[-<<<<<+>>>>>]<<<
+<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<+<<<<<<<<<<<<<<<<<
+<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<[->>>>>>>>+<<<<<<<<]<<<<<[-]>>>>>>>
+>>>>>>[-]<<<<<<<<<<[-]<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>[-]++++++++++.[-]<<<<<<<<<<<<<[-]++++>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>
+>>>>>>>>>>>>>>>+>>>+<<<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<
+<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>]>>>>>>>>>>>[-<<<<<<<<<<<+>>>+>>>>>>>>]<<<<<<
+<<<<<[->>>>>>>>>>>+<<<<<<<<<<<][-]++>>>>>>>>>>>>>[-]<<<<<<<<<<[->>>>>>>>>+>+<<<<
+<<<<<<]>>>>>>>>>[-<<<<<<<<<+>>>>>>>>>]>>[-]<<<<<<<<<<<<<<[->>>>>>>>>>>>+>>+<<<<<
+<<<<<<<<<]>>>>>>>>>>>>[-<<<<<<<<<<<<+>>>>>>>>>>>>]<<<<<<<<<<[-]+>>>>>>>>>>>>[<<[
+-]+>>>[-]<<[->>>+<+<<]>>>[-<<<+>>>]<[[-]<<<[-]>>>]<<<[[-]<<<<<<<<<<[-]>>>>>>>>>>
+>>[-]+<[-]+<]>->-]<[-]>[-]<<<<<<<<<<<<[->>>>>>>>>>>+>+<<<<<<<<<<<<]>>>>>>>>>>>[-
+<<<<<<<<<<<+>>>>>>>>>>>]>[[-]<<<<<<<<<<<[-]>>>>>>>>>>>]<<<<<<<<<<<<[-]<<[-][-]>>
+>[-<+<<+>>>]<[->+<][-]+<<[[-]>>[-]>>>>>>>>>>>[-]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<]>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<[-]<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<<<+<<<<<
+<<<<<<<<<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<[-]+<<[>>>[-]+
+>[-]<<<[->>>>+<+<<<]>>>>[-<<<<+>>>>]<[[-]<[-]>]<[[-]<[-]<<[-]+>[-]+>>]<<-<-]>[-]
+<[-]>>[-<+<+>>]<[->+<][-]+<[[-]>[-]<<<<<<<<<<<<<<[-]<<<<<<<<<<<<<<<<<<<<[->>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>
+>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<<-<]>>>
+>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<[-]<<<<<<<<<<<
+<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<+<<<<<<<<<<<<<
+<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<
+<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<]>[[-]<<<<<<<<<<<<<<[-]<[->>>>>
+>>>>>>>>>>>>+<<<<<<<<<<<<<<<<+<]>>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<<+>>>>>>>>>>>
+>>>>>>]<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>+<<<<<<<<<<<<<<<<-<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<
+<<<<<<[-]>>>>>>>>>>[->>>>+<<<<<<<<<<<<<<+>>>>>>>>>>]>>>>[-<<<<+>>>>]<<]>[-]<<<<<
+<<<<<<<<<]>>[[-]<<<[-]<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>+
+<<<<<<<<<<<<<<<+<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>[-<<<<<<
+<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<<<<<<<<<<<<<<
+<<[->>>>>>>>>>>>>>>>+<<<<<<<<<<<<<<<+<]>>>>>>>>>>>>>>>>[-<<<<<<<<<<<<<<<<+>>>>>>
+>>>>>>>>>>]<<<<<<<<<<<<<[-]<<<<<<<<<<<<<<<<<<<<<<<[->>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>>>>>>+<<<<<<<<<<<<<+<<<<<<<<<<<<<<<<<<<<<<<]>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>
+>>>[-<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>>]<
+<<<<<<<<<<<]>[-]<<<<<<<<<<<<<<<<<<<<<<<<<[-]>>>>>>>>>>>>>>>>>>>>>>>[->>+<<<<<<<<
+<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>>>>]>>[-<<+>>]<<<<<<<<<<<<<<<<<<<<<<<<[-]>
+>>>>>>>>>>>>>>>>>>>[->>>>+<<<<<<<<<<<<<<<<<<<<<<<<+>>>>>>>>>>>>>>>>>>>>]>>>>[-<<
+<<+>>>>]<<[-]<<[-]<[-]<<<<<<<<<<<<<<<<<<<<<<]
@@ -0,0 +1,39 @@
+; A calculator for single digits. Type something like "7*8" or "4+5", and it
+; prints the answer.
+;  cell 0: the first digit
+;  cell 1: the operator, '+' or '*'
+;  cell 2: the second digit
+;  cell 3: the answer
+;  cells 4 to 9: used when printing the answer
+
+:read_digit {
+    , =[ @ - '0 ]
+}
+
+; Prints the number in the current cell in decimal and clears it, it can be up to 99.
+; Uses the six cells to the right of it, which have to be zero
+:print_number {
+    ; Divides the cell by 10, leaving a copy of it one cell to the right, the
+    ; remainder three cells to the right and the quotient four cells to the right
+    >>+A<<
+    [->+>-[>+>>]>[+[-<+>]>+>>]<<<<<<]
+    >>>> ?{ +'0 . [-] }
+    < +'0 . [-]
+    < [-]
+    < [-] <
+}
+
+; The operator is turned into 0 for '*' and 1 for '+' right away, since
+; the expression uses the two cells after it
+#/read_digit > , =[ @ - '* ] > #/read_digit <
+
+?{
+    ; Adding
+    >> =[ @<3 + @<1 ] <<
+}:{
+    ; Multiplying is adding the second digit to the answer, first digit times
+    < [ >>> =[ @ + @<1 ] <<< - ] >
+}
+
+>> #/print_number
++A .
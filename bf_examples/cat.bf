@@ -0,0 +1,14 @@
+; Prints back everything you type, until you type a '.'
+; The input comes in a line at a time, so nothing is printed until you press enter.
+;  cell 0: 1 while it should keep going
+;  cell 1: the character that was read
+;  cell 2: the character minus '.', which is zero when it's time to stop
+
+:echo {
+    > ,
+    > =[ @<1 - '. ]
+    ?{ <.> }:{ <<->> }
+    [-] < [-] <
+}
+
++[ #/echo ]
@@ -0,0 +1,6 @@
+; Hello world, with the string printing macros from the standard library.
+; The string is written to the tape, printed, and then cleaned up again.
+#std/print/start
+    "Hello, world!\n"
+#std/print/end
+#std/print/cleanup
@@ -0,0 +1,47 @@
+; Conway's game of life, on a 6 by 6 board with a glider on it. Prints the
+; board and then the next 4 generations.
+; The board is 8 by 8 with a row or column of dead cells around the edges, so
+; every cell has eight neighbours. Each cell on the board uses eight cells of
+; the tape:
+;  0: 1 if the cell is alive
+;  1: the next generation of the cell
+;  2 to 7: scratch space for working out the next generation
+; so a row of the board is 64 cells long, and the top left cell that isn't on
+; the edge is 72 cells in. Counts are in hex, so those are >40 and >48.
+
+; Works out the next generation of the current cell, starting at its cell 1
+:step {
+    ; Counts the neighbours
+    =[ @<49 + @<41 + @<39 + @<9 + @>7 + @>37 + @>3F + @>47 ]
+    ; A cell lives on with 2 neighbours if it's alive, or with 3 neighbours.
+    ; That's when two times the neighbours plus the cell, minus 5, is 0, 1 or 2
+    =[ @*2 + @<1 - 5 ]
+    ?{ - ?{ - ?{ [-] }:{ + } }:{ + } }:{ + }
+}
+
+; Moves the next generation of the current cell into it
+:update {
+    [-] > [-<+>] <
+}
+
+:print {
+    ?{ >+'# . [-]< }:{ >+'. . [-]< }
+}
+
+; Runs a macro on every cell that isn't on the edge, starting at the top left
+; one, with a newline after every row if it's printing
+:each_step { ( ( #src/step >8 )6 >10 )6 <C0 <C0 }
+:each_update { ( ( #src/update >8 )6 >10 )6 <C0 <C0 }
+:each_print { ( ( #src/print >8 )6 >+A . [-]< >10 )6 <C0 <C0 }
+
+; The glider
+>48
+>8 + >48 + >30 + >8 + >8 + <90
+
+#/each_print
+(
+    >+A . [-]<
+    > #/each_step <
+    #/each_update
+    #/each_print
+)4
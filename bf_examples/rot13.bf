@@ -0,0 +1,43 @@
+; Rot13, every letter is swapped with the one 13 steps away in the alphabet,
+; so running the output through it again gives back what you typed.
+; Type a '.' to stop.
+; The letters are found by dividing the character by 32 and then 13, this is the
+; program from the brainfuck page on wikipedia, with the reading swapped out for
+; a macro that knows when to stop.
+
+; Reads a character into the current cell, plus one, or 0 if it's a '.'
+:read {
+    ,
+    > =[ @<1 - '. ] ?{ <+> }:{ <[-]> } [-] <
+}
+
+#/read
+[
+    `
+    -[
+        >>++++[>++++++++<-]
+        <+<-[
+            >+>+>-[>>>]
+            <[[>+<-]>>+>]
+            <<<<<-
+        ]
+    ]>>>[-]+
+    >--[-[<->+++[-]]]<[
+        ++++++++++++<[
+            >-[>+>>]
+            >[+[<+>-]>+>>]
+            <<<<<-
+        ]
+        >>[<+>-]
+        >[
+            -[
+                -<<[-]>>
+            ]<<[<<->>-]>>
+        ]<<[<<+>>-]
+    ]
+    <[-]
+    <.[-]
+    <
+    `
+    #/read
+]
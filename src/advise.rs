@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use crate::Compiler;
+use crate::binary::Program;
+use crate::compiler::{ CompiledUnit, string_size };
+use crate::instructions::*;
+use crate::report::RunStats;
+
+// "advise file [modifiers]" looks at a compiled program, and a run of it, and suggests
+// changes that would make it smaller or faster, with where in the source they would go
+pub struct Advice {
+    // Where it's about, ex: "'src/foo' at (3, 4)"
+    pub place: String,
+    pub message: String,
+    // How much it matters, in bytes for size advice and in instructions run for speed advice.
+    // The advice is sorted by it, most first
+    pub weight: u64
+}
+
+// Macros that would save less than this by being noinline aren't worth the calls
+const MIN_NOINLINE_SAVING: usize = 64;
+// Strings are only mentioned if they're at least this many percent of the program
+const MIN_STRING_SHARE: usize = 5;
+// Loops are only mentioned if they're at least this many percent of the instructions that ran
+const MIN_LOOP_SHARE: u64 = 1;
+const MAX_ADVICE: usize = 10;
+
+// How the macros and strings of a program could be changed to make it smaller.
+// 'linked' is the "src" unit after linking, the standard library isn't given advice
+pub fn size_advice(compiler: &Compiler, linked: &CompiledUnit) -> Vec<Advice> {
+    let mut copies: HashMap<_, usize> = HashMap::new();
+    for (id, _, _) in &linked.copies {
+        *copies.entry(*id).or_insert(0) += 1;
+    }
+
+    let mut advice = Vec::new();
+    let program_size = linked.code.len().max(1);
+    for unit in compiler.units() {
+        if unit.name.split('/').next() == Some(crate::STD_ROOT) {
+            continue;
+        }
+
+        let (size, uses) = match (unit.compiled_size, unit.name == "src") {
+            (Some(size), true) => (size, 1),
+            (Some(size), false) => (size, copies.get(&unit.id).cloned().unwrap_or(0)),
+            (None, _) => continue
+        };
+        if uses == 0 {
+            continue;
+        }
+        let place = format!("'{}' at {}", unit.name, unit.defined_at);
+
+        // Every use becomes a 5 byte CALL, and the code itself gets a RET
+        if !unit.noinline && uses > 1 && unit.name != "src" {
+            let saving = (uses * size).saturating_sub(size + 1 + uses * 5);
+            if saving >= MIN_NOINLINE_SAVING {
+                advice.push(Advice {
+                    place: place.clone(),
+                    message: format!("Mark it noinline, it's copied {} times at {} bytes each, that would save about {} bytes. \
+                        It can't be used inside ?{{ }} or @[ ] after that, and *opt leaves programs with calls alone",
+                        uses, size, saving),
+                    weight: saving as u64
+                });
+            }
+        }
+        if unit.noinline && size < len(CALL) {
+            advice.push(Advice {
+                place: place.clone(),
+                message: format!("Don't mark it noinline, it's only {} bytes, which is less than the CALL to it", size),
+                weight: (len(CALL) - size) as u64
+            });
+        }
+
+        for (loc, string, is_safe) in &unit.strings {
+            if compiler.is_pooled(string, *is_safe) {
+                continue;
+            }
+            let size = match string_size(string, *is_safe) {
+                Ok(size) => size,
+                Err(_) => continue
+            };
+            let total = size * uses;
+            if total * 100 < program_size * MIN_STRING_SHARE {
+                continue;
+            }
+
+            let mut message = format!("The string {:?} is {} bytes in {} copies, {}% of the program",
+                string, total, uses, total * 100 / program_size);
+            if *is_safe {
+                let unsafe_size = string_size(string, false).unwrap_or(size);
+                message.push_str(&format!(". If the cells it goes in are zero already, 0\"...\" would save {} bytes",
+                    (size - unsafe_size) * uses));
+            }
+            if uses > 1 {
+                message.push_str(&format!(". *pool_strings would save about {} bytes",
+                    total.saturating_sub(size + 1 + uses * 5)));
+            }
+            advice.push(Advice { place: format!("'{}' at {}", unit.name, loc), message, weight: total as u64 });
+        }
+    }
+
+    sort(advice)
+}
+
+// The loops that took up a lot of the run, and could be a lot faster.
+// Only linear loops are mentioned, the ones that only add to the cells around them while counting
+// the current cell down or up. They take as many iterations as the value of the cell, but could be
+// done in a few instructions each, like "[->++<]" is "add twice the cell to the next one"
+pub fn speed_advice(program: &Program, stats: &RunStats) -> Vec<Advice> {
+    let mut advice = Vec::new();
+    let total = stats.instructions.max(1);
+    for (offset, entries, iterations) in stats.loops() {
+        let body = match linear_body(&program.code, offset) {
+            Some(body) => body,
+            None => continue
+        };
+
+        // The body, and the LOOP_CLOSE after it
+        let instructions = iterations * (body + 1);
+        if instructions * 100 < total * MIN_LOOP_SHARE {
+            continue;
+        }
+
+        let place = program.symbols.as_ref()
+            .and_then(|symbols| symbols.describe(offset))
+            .unwrap_or_else(|| format!("the loop at {:X}", offset));
+        advice.push(Advice {
+            place,
+            message: format!("A linear loop, entered {} times, {} iterations, {:.1}% of the run. \
+                It only adds to the cells around it, so it takes as many iterations as the cell it counts; \
+                a smaller count, or fewer entries, is what makes it faster",
+                entries, iterations, instructions as f64 * 100.0 / total as f64),
+            weight: instructions
+        });
+    }
+
+    sort(advice)
+}
+
+// How many instructions the body of the loop at 'start' has, if it's a linear loop
+fn linear_body(code: &[u8], start: usize) -> Option<u64> {
+    let mut index = start + len(code[start]);
+    let mut offset = 0isize;
+    let mut counter = 0i64;
+    let mut instructions = 0;
+    loop {
+        match *code.get(index)? {
+            SHIFT_RIGHT => offset += 1,
+            SHIFT_LEFT => offset -= 1,
+            INCREMENT if offset == 0 => counter += 1,
+            DECREMENT if offset == 0 => counter -= 1,
+            INCREMENT | DECREMENT => {},
+            LOOP_CLOSE | LOOP_CLOSE_WIDE => break,
+            _ => return None
+        }
+        instructions += 1;
+        index += len(code[index]);
+    }
+
+    if offset == 0 && counter.abs() == 1 { Some(instructions) } else { None }
+}
+
+fn sort(mut advice: Vec<Advice>) -> Vec<Advice> {
+    advice.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.place.cmp(&b.place)));
+    advice.truncate(MAX_ADVICE);
+    advice
+}
@@ -0,0 +1,81 @@
+use std::time::{ Duration, Instant };
+
+use crate::{ Modifiers, CompileOptions, compile_program, execute_bf };
+use crate::source::read_source;
+
+// Where the bundled benchmark programs live, relative to the root of the repository.
+// Every program can have a file with the same name but the extension ".out",
+// containing the output it's expected to produce.
+pub const BENCH_PROGRAM_DIR: &str = "benches/programs";
+
+pub struct BenchResult {
+    pub name: String,
+    pub compile_time: Duration,
+    pub run_times: Vec<Duration>,
+    // None if there was no expected output to compare with
+    pub output_matches: Option<bool>
+}
+
+impl BenchResult {
+    pub fn fastest_run(&self) -> Duration {
+        self.run_times.iter().min().cloned().unwrap_or_default()
+    }
+
+    pub fn mean_run(&self) -> Duration {
+        if self.run_times.is_empty() {
+            return Duration::default();
+        }
+
+        self.run_times.iter().sum::<Duration>() / self.run_times.len() as u32
+    }
+}
+
+// The paths of all the bundled benchmark programs, sorted by name
+pub fn bundled_programs() -> std::io::Result<Vec<String>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(BENCH_PROGRAM_DIR)? {
+        let path = entry?.path();
+        if path.extension() == Some(std::ffi::OsStr::new("bf")) {
+            paths.push(path.to_string_lossy().into_owned());
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
+
+// Compiles a program once and runs it 'iterations' times, timing both.
+// The output of the program is thrown away, but compared with the
+// expected output if there is one.
+pub fn bench_file(path: &str, std_lib: Option<&[char]>, iterations: usize) -> Result<BenchResult, String> {
+    let source = read_source(path)?;
+
+    let start = Instant::now();
+    let program = compile_program(source, std_lib.map(|std_lib| std_lib.to_vec()), CompileOptions::default())
+        .map_err(|failure| failure.describe())?;
+    let compile_time = start.elapsed();
+
+    let modifiers = Modifiers::new();
+    let mut run_times = Vec::with_capacity(iterations);
+    let mut output = Vec::new();
+    for _ in 0..iterations {
+        output.clear();
+        let start = Instant::now();
+        execute_bf(&program, &modifiers, &[], &mut output)?;
+        run_times.push(start.elapsed());
+    }
+
+    let expected_path = std::path::Path::new(path).with_extension("out");
+    let output_matches = std::fs::read(expected_path).ok()
+        .map(|expected| expected == output);
+
+    let name = std::path::Path::new(path).file_stem()
+        .map_or_else(|| String::from(path), |stem| stem.to_string_lossy().into_owned());
+
+    Ok(BenchResult {
+        name,
+        compile_time,
+        run_times,
+        output_matches
+    })
+}
@@ -0,0 +1,440 @@
+use std::io::prelude::*;
+use std::io::{ Error, ErrorKind };
+use std::fs::File;
+
+use crate::instructions::*;
+use crate::pragma::Pragmas;
+use crate::symbols::DebugSymbols;
+
+// The bytes every compiled file starts with
+pub const MAGIC: [u8; 4] = [0xBF, 0xFF, 0xBB, 0xFF];
+
+// The version of the binary format that this compiler writes.
+// Bump this whenever the layout of the header or the instruction set changes
+pub const FORMAT_VERSION: u8 = 10;
+
+// The oldest version that can still be read
+//  1: magic, version
+//  2: magic, version, pragmas
+//  3: magic, version, pragmas, labels
+//  4: the ASSERT instruction
+//  5: the DEBUG_OP instruction
+//  6: the CALL and RET instructions
+//  7: the max_steps and max_memory pragmas
+//  8: the LOOP_OPEN_WIDE and LOOP_CLOSE_WIDE instructions
+//  9: magic, version, pragmas, labels, exports, imports
+// 10: the INTROSPECT instruction
+pub const OLDEST_FORMAT_VERSION: u8 = 1;
+
+// Magic bytes followed by the version byte
+pub const HEADER_LEN: usize = MAGIC.len() + 1;
+
+// A name for the debug dump at 'offset' in the code, written as "!name" in the source
+#[derive(Clone, Debug)]
+pub struct Label {
+    pub offset: usize,
+    pub name: String
+}
+
+impl Label {
+    pub fn new(offset: usize, name: String) -> Label {
+        Label {
+            offset,
+            name
+        }
+    }
+}
+
+// A compiled program, with the settings it needs to run
+pub struct Program {
+    pub pragmas: Pragmas,
+    pub code: Vec<u8>,
+    pub labels: Vec<Label>,
+    pub symbols: Option<DebugSymbols>,
+    // For libraries, where the macros in them start, as (name, offset)
+    pub exports: Vec<(String, usize)>,
+    // The CALLs to macros in a library, as (offset of the CALL, name). They go nowhere
+    // until the program is loaded on top of the library with on_library
+    pub imports: Vec<(usize, String)>
+}
+
+impl Program {
+    pub fn new(pragmas: Pragmas, code: Vec<u8>) -> Program {
+        Program {
+            pragmas,
+            code,
+            labels: Vec::new(),
+            symbols: None,
+            exports: Vec::new(),
+            imports: Vec::new()
+        }
+    }
+
+    // Puts the program after the code of a library, and points its CALLs into the library
+    // at the macros they call. The code of a library is a loop that never runs, so it doesn't
+    // change what the program does
+    pub fn on_library(self, library: &Program) -> Result<Program, String> {
+        let base = library.code.len();
+        let mut code = library.code.clone();
+        code.extend_from_slice(&self.code);
+
+        // The program's own CALLs moved along with it
+        let imported: Vec<usize> = self.imports.iter().map(|(offset, _)| *offset).collect();
+        let mut index = 0;
+        while index < self.code.len() {
+            if self.code[index] == CALL && !imported.contains(&index) {
+                let target = read_u32(&self.code, index + 1) as usize + base;
+                code[base + index + 1..base + index + 5].copy_from_slice(&(target as u32).to_le_bytes());
+            }
+            index += len(self.code[index]);
+        }
+
+        for (offset, name) in &self.imports {
+            let target = library.exports.iter()
+                .find(|(export, _)| export == name)
+                .map(|(_, target)| *target)
+                .ok_or_else(|| format!("The library doesn't have '{}', which the program calls", name))?;
+            code[base + offset + 1..base + offset + 5].copy_from_slice(&(target as u32).to_le_bytes());
+        }
+
+        let mut program = Program::new(self.pragmas, code);
+        program.labels = library.labels.iter().cloned()
+            .chain(self.labels.into_iter().map(|label| Label::new(label.offset + base, label.name)))
+            .collect();
+        program.symbols = self.symbols.map(|symbols| symbols.shifted(base));
+        Ok(program)
+    }
+
+    // The label of the debug dump at 'offset', if it has one.
+    // Labels are sorted by offset, since the code only ever grows while compiling
+    pub fn label_at(&self, offset: usize) -> Option<&str> {
+        self.labels.binary_search_by_key(&offset, |label| label.offset)
+            .ok()
+            .map(|index| &self.labels[index].name[..])
+    }
+
+    // Where the debug dump with a label is, if there is one
+    pub fn find_label(&self, name: &str) -> Option<usize> {
+        self.labels.iter()
+            .find(|label| label.name == name)
+            .map(|label| label.offset)
+    }
+}
+
+pub enum FileKind {
+    Binary,
+    Source
+}
+
+fn invalid_data(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+// Figures out if a file is a compiled binary or source code.
+// A file only counts as a binary if it has the magic bytes AND a version we understand,
+// anything with the magic bytes but a bad version is treated as source code with a warning.
+// Files that have the magic bytes but are too short to contain a header are errors.
+pub fn detect_file_kind(file_name: &str) -> std::io::Result<FileKind> {
+    let file = File::open(file_name)?;
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    file.take(HEADER_LEN as u64).read_to_end(&mut header)?;
+
+    if header.len() < MAGIC.len() || header[..MAGIC.len()] != MAGIC {
+        return Ok(FileKind::Source);
+    }
+
+    if header.len() < HEADER_LEN {
+        return Err(invalid_data(format!(
+            "'{}' looks like a binary but is truncated, the header is {} bytes long but the file only has {}",
+            file_name, HEADER_LEN, header.len())));
+    }
+
+    match validate_version(header[MAGIC.len()]) {
+        Ok(()) => Ok(FileKind::Binary),
+        Err(msg) => {
+            crate::diagnostics::warn("not_a_binary", Some(file_name),
+                format!("'{}' starts like a binary, but {}, so it's parsed as source code instead", file_name, msg));
+            Ok(FileKind::Source)
+        }
+    }
+}
+
+fn validate_version(version: u8) -> Result<(), String> {
+    if (OLDEST_FORMAT_VERSION..=FORMAT_VERSION).contains(&version) {
+        Ok(())
+    }else{
+        Err(format!("it has format version {}, expected version {} to {}",
+            version, OLDEST_FORMAT_VERSION, FORMAT_VERSION))
+    }
+}
+
+// Makes sure that every instruction is valid, that no instruction
+// runs past the end of the program and that every loop jumps to the other end of itself
+pub fn validate_code(code: &[u8]) -> Result<(), String> {
+    let mut index = 0;
+    // Where every instruction starts, and every call, since a call has to jump to the start of an instruction
+    let mut starts = Vec::new();
+    let mut calls = Vec::new();
+    let mut open_loops = Vec::new();
+    while index < code.len() {
+        starts.push(index);
+        match code[index] {
+            SHIFT_RIGHT | SHIFT_LEFT | INCREMENT | DECREMENT | PRINT | READ | DEBUG | RET => index += 1,
+            instruction @ (LOOP_OPEN | LOOP_CLOSE | ASSERT | CALL | LOOP_OPEN_WIDE | LOOP_CLOSE_WIDE) => {
+                if index + len(instruction) > code.len() {
+                    return Err(format!("Instruction at {:X} is truncated", index));
+                }
+
+                if instruction == CALL {
+                    calls.push(index);
+                }else if is_loop_open(instruction) {
+                    open_loops.push(index);
+                }else if is_loop_close(instruction) {
+                    validate_loop(code, open_loops.pop(), index)?;
+                }
+                index += len(instruction);
+            },
+            DEBUG_OP => {
+                match code.get(index + 1) {
+                    None => return Err(format!("Instruction at {:X} is truncated", index)),
+                    Some(kind) if *kind > DEBUG_PAUSE => return Err(format!("Invalid debug operation {:X} at {:X}", kind, index)),
+                    Some(_) if index + len(DEBUG_OP) > code.len() => return Err(format!("Instruction at {:X} is truncated", index)),
+                    Some(_) => index += len(DEBUG_OP)
+                }
+            },
+            INTROSPECT => {
+                match code.get(index + 1) {
+                    None => return Err(format!("Instruction at {:X} is truncated", index)),
+                    Some(kind) if *kind > FACT_INPUT => return Err(format!("Invalid introspection fact {:X} at {:X}", kind, index)),
+                    Some(_) => index += len(INTROSPECT)
+                }
+            },
+            instruction => return Err(format!("Invalid instruction {:X} at {:X}", instruction, index))
+        }
+    }
+
+    if let Some(start) = open_loops.pop() {
+        return Err(format!("The loop at {:X} is never closed", start));
+    }
+
+    for call in calls {
+        let target = read_u32(code, call + 1) as usize;
+        if starts.binary_search(&target).is_err() {
+            return Err(format!("The CALL at {:X} jumps to {:X}, which isn't the start of an instruction", call, target));
+        }
+    }
+
+    Ok(())
+}
+
+// A loop that doesn't jump exactly to its other end means the offsets are broken,
+// for example from a body too big for its offsets that got cut off
+fn validate_loop(code: &[u8], start: Option<usize>, end: usize) -> Result<(), String> {
+    let start = start.ok_or_else(|| format!("The loop close at {:X} doesn't have a loop open", end))?;
+    let wide = code[start] == LOOP_OPEN_WIDE;
+    if wide != (code[end] == LOOP_CLOSE_WIDE) {
+        return Err(format!("The loop at {:X} mixes wide and narrow instructions", start));
+    }
+
+    let after_end = end + len(code[end]);
+    let after_start = start + len(code[start]);
+    if start.checked_add(loop_offset(code, start)) != Some(after_end)
+            || end.checked_sub(loop_offset(code, end)) != Some(after_start) {
+        return Err(format!("The loop at {:X} doesn't jump to its end at {:X}, the loop body is too large \
+            for its offsets or the binary is broken", start, end));
+    }
+
+    Ok(())
+}
+
+// Labels, exports and imports are stored as a u32 count, followed by the offset(u32) and
+// the length(u16) of the name and the UTF-8 name of every one of them
+fn encode_names<'a>(names: impl ExactSizeIterator<Item = (usize, &'a str)>, data: &mut Vec<u8>) {
+    data.extend_from_slice(&(names.len() as u32).to_le_bytes());
+    for (offset, name) in names {
+        data.extend_from_slice(&(offset as u32).to_le_bytes());
+        data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        data.extend_from_slice(name.as_bytes());
+    }
+}
+
+// Returns the (offset, name)s and how many bytes they took up, 'what' is what they are for errors
+fn decode_names(data: &[u8], what: &str) -> Result<(Vec<(usize, String)>, usize), String> {
+    let truncated = || format!("The {} in the header are truncated", what);
+    let mut index = 0;
+    let mut take = |n: usize| -> Result<&[u8], String> {
+        let bytes = data.get(index..index + n).ok_or_else(truncated)?;
+        index += n;
+        Ok(bytes)
+    };
+
+    let count = take(4)?;
+    let count = u32::from_le_bytes([count[0], count[1], count[2], count[3]]);
+    let mut names = Vec::new();
+    for _ in 0..count {
+        let offset = take(4)?;
+        let offset = u32::from_le_bytes([offset[0], offset[1], offset[2], offset[3]]) as usize;
+        let len = take(2)?;
+        let len = u16::from_le_bytes([len[0], len[1]]) as usize;
+        let name = String::from_utf8(take(len)?.to_vec())
+            .map_err(|_| format!("A name in the {} in the header isn't valid UTF-8", what))?;
+        names.push((offset, name));
+    }
+
+    Ok((names, index))
+}
+
+pub fn read_bin_from_file(file_name: &str) -> std::io::Result<Program> {
+    read_versioned_bin(file_name).map(|(_, program)| program)
+}
+
+// Reads a binary along with the format version it was written with
+pub fn read_versioned_bin(file_name: &str) -> std::io::Result<(u8, Program)> {
+    let mut file = File::open(file_name)?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+
+    if data.len() < HEADER_LEN || data[..MAGIC.len()] != MAGIC {
+        return Err(invalid_data(format!("'{}' is not a binary, or the header is truncated", file_name)));
+    }
+
+    let version = data[MAGIC.len()];
+    validate_version(version).map_err(invalid_data)?;
+
+    let mut code = data.split_off(HEADER_LEN);
+    let pragmas = if version >= 2 {
+        let pragmas = Pragmas::decode(&code, version).map_err(invalid_data)?;
+        code.drain(..Pragmas::encoded_len(version));
+        pragmas
+    }else{
+        Pragmas::new()
+    };
+
+    let mut take_names = |what: &str| -> std::io::Result<Vec<(usize, String)>> {
+        let (names, len) = decode_names(&code, what).map_err(invalid_data)?;
+        code.drain(..len);
+        Ok(names)
+    };
+    let labels: Vec<Label> = if version >= 3 {
+        take_names("labels")?.into_iter().map(|(offset, name)| Label::new(offset, name)).collect()
+    }else{
+        Vec::new()
+    };
+    let (exports, imports) = if version >= 9 {
+        let exports = take_names("exports")?.into_iter().map(|(offset, name)| (name, offset)).collect();
+        (exports, take_names("imports")?)
+    }else{
+        (Vec::new(), Vec::new())
+    };
+
+    validate_code(&code).map_err(invalid_data)?;
+    for label in &labels {
+        if code.get(label.offset) != Some(&DEBUG) {
+            return Err(invalid_data(format!("The label '{}' doesn't point at a debug dump", label.name)));
+        }
+    }
+
+    for (offset, name) in &imports {
+        if code.get(*offset) != Some(&CALL) {
+            return Err(invalid_data(format!("The import of '{}' doesn't point at a CALL", name)));
+        }
+    }
+    for (name, offset) in &exports {
+        if *offset >= code.len() {
+            return Err(invalid_data(format!("The export '{}' points past the end of the code", name)));
+        }
+    }
+
+    let mut program = Program::new(pragmas, code);
+    program.labels = labels;
+    program.labels.sort_by_key(|label| label.offset);
+    program.exports = exports;
+    program.imports = imports;
+    Ok((version, program))
+}
+
+pub fn write_bin_to_file(file_name: &str, program: &Program) -> std::io::Result<()> {
+    let mut header = Vec::with_capacity(HEADER_LEN + Pragmas::ENCODED_LEN);
+    header.extend_from_slice(&MAGIC);
+    header.push(FORMAT_VERSION);
+    program.pragmas.encode(&mut header);
+    encode_names(program.labels.iter().map(|label| (label.offset, &label.name[..])), &mut header);
+    encode_names(program.exports.iter().map(|(name, offset)| (*offset, &name[..])), &mut header);
+    encode_names(program.imports.iter().map(|(offset, name)| (*offset, &name[..])), &mut header);
+
+    let mut file = File::create(file_name)?;
+    file.write_all(&header)?;
+    file.write_all(&program.code)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile_test_program;
+
+    // Writes the program as a binary, and gives back the bytes of the file
+    fn binary_bytes(path: &str, text: &str) -> Vec<u8> {
+        write_bin_to_file(path, &compile_test_program(text)).unwrap();
+        std::fs::read(path).unwrap()
+    }
+
+    fn read_error(path: &str, data: &[u8]) -> String {
+        std::fs::write(path, data).unwrap();
+        read_bin_from_file(path).err().expect("The binary should have been rejected").to_string()
+    }
+
+    #[test]
+    fn broken_binaries_are_rejected() {
+        let path = std::env::temp_dir().join(format!("brainfuck_binary_{}.bfo", std::process::id()));
+        let path = path.to_str().unwrap();
+        let data = binary_bytes(path, "+5 [->+<] >.");
+        assert!(read_bin_from_file(path).is_ok());
+        assert!(matches!(detect_file_kind(path), Ok(FileKind::Binary)));
+
+        let mut bad_magic = data.clone();
+        bad_magic[1] = 0;
+        assert!(read_error(path, &bad_magic).contains("is not a binary"));
+        assert!(matches!(detect_file_kind(path), Ok(FileKind::Source)));
+
+        let mut bad_version = data.clone();
+        bad_version[MAGIC.len()] = FORMAT_VERSION + 1;
+        assert!(read_error(path, &bad_version).contains(&format!("format version {}", FORMAT_VERSION + 1)));
+        assert!(matches!(detect_file_kind(path), Ok(FileKind::Source)));
+
+        let message = read_error(path, &data[..MAGIC.len()]);
+        assert!(message.contains("truncated"), "{}", message);
+        assert!(detect_file_kind(path).is_err());
+
+        // Cut off in the middle of the pragmas, and in the middle of the code
+        assert!(!read_error(path, &data[..HEADER_LEN + 2]).is_empty());
+        let message = read_error(path, &data[..data.len() - 3]);
+        assert!(message.contains("truncated"), "{}", message);
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn labelled_debug_dumps_can_be_found_by_name() {
+        let program = compile_test_program(":show { + !inner } !start #src/show >!.");
+        let start = program.find_label("start").unwrap();
+        let inner = program.find_label("inner").unwrap();
+        assert!(start < inner);
+        assert_eq!((program.code[start], program.code[inner]), (DEBUG, DEBUG));
+        assert_eq!(program.label_at(inner), Some("inner"));
+        assert_eq!(program.labels.len(), 2);
+
+        // "!." is a dump without a label, followed by a print
+        assert_eq!(&program.code[program.code.len() - 2..], &[DEBUG, PRINT]);
+        assert_eq!(program.label_at(program.code.len() - 2), None);
+
+        let path = std::env::temp_dir().join(format!("brainfuck_labels_{}.bfo", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_bin_to_file(path, &program).unwrap();
+        let read = read_bin_from_file(path).unwrap_or_else(|error| panic!("{}", error));
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(read.find_label("start"), Some(start));
+        assert_eq!(read.label_at(inner), Some("inner"));
+    }
+}
@@ -0,0 +1,120 @@
+// A bundle is a program together with the standard library it was written for,
+// in one file, so it compiles the same way anywhere. It looks like this:
+//  ;@bundle 1
+//  ;@entry example.bf 1234
+//  ...the 1234 characters of the program...
+//  ;@std 5678 0123456789ABCDEF
+//  ...the 5678 characters of the standard library...
+// The lengths are in characters, and the number after the length of the
+// library is a hash of it, so you can tell if two bundles use the same library.
+// The library part is left out if there was no library when bundling.
+
+pub const BUNDLE_HEADER: &str = ";@bundle 1";
+
+pub struct Bundle {
+    // The name of the file the program came from
+    pub entry: String,
+    pub source: Vec<char>,
+    pub std_lib: Option<Vec<char>>
+}
+
+// FNV-1a, it just has to tell different libraries apart
+pub fn hash(text: &[char]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut buffer = [0; 4];
+    for c in text {
+        for byte in c.encode_utf8(&mut buffer).bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100_0000_01b3);
+        }
+    }
+
+    hash
+}
+
+pub fn is_bundle(text: &[char]) -> bool {
+    text.len() >= BUNDLE_HEADER.len() && text.iter().zip(BUNDLE_HEADER.chars()).all(|(a, b)| *a == b)
+}
+
+pub fn write_bundle(bundle: &Bundle) -> String {
+    let mut text = format!("{}\n;@entry {} {}\n", BUNDLE_HEADER, bundle.entry, bundle.source.len());
+    text.extend(bundle.source.iter());
+    if let Some(std_lib) = &bundle.std_lib {
+        text.push_str(&format!("\n;@std {} {:016X}\n", std_lib.len(), hash(std_lib)));
+        text.extend(std_lib.iter());
+    }
+    text.push('\n');
+    text
+}
+
+struct Reader<'a> {
+    text: &'a [char],
+    index: usize
+}
+
+impl<'a> Reader<'a> {
+    fn line(&mut self) -> Option<String> {
+        if self.index >= self.text.len() {
+            return None;
+        }
+
+        let start = self.index;
+        while self.index < self.text.len() && self.text[self.index] != '\n' {
+            self.index += 1;
+        }
+        let line = self.text[start..self.index].iter().collect();
+        self.index += 1;
+        Some(line)
+    }
+
+    fn chars(&mut self, n: usize) -> Result<Vec<char>, String> {
+        let chars = self.text.get(self.index..self.index + n)
+            .ok_or_else(|| String::from("The bundle is truncated"))?
+            .to_vec();
+        // Skip the newline after the section
+        self.index += n + 1;
+        Ok(chars)
+    }
+}
+
+fn parse_len(text: &str) -> Result<usize, String> {
+    text.parse().map_err(|_| format!("Invalid length '{}' in the bundle", text))
+}
+
+pub fn read_bundle(text: &[char]) -> Result<Bundle, String> {
+    let mut reader = Reader { text, index: 0 };
+    if reader.line().as_deref() != Some(BUNDLE_HEADER) {
+        return Err(format!("Not a bundle, it doesn't start with '{}'", BUNDLE_HEADER));
+    }
+
+    let entry_line = reader.line().unwrap_or_default();
+    let (entry, len) = match entry_line.strip_prefix(";@entry ").and_then(|rest| rest.rsplit_once(' ')) {
+        Some(value) => value,
+        None => return Err(String::from("Expected ';@entry name length' after the bundle header"))
+    };
+    let source = reader.chars(parse_len(len)?)?;
+
+    let std_lib = match reader.line() {
+        None => None,
+        Some(line) if line.is_empty() && reader.index >= text.len() => None,
+        Some(line) => {
+            let parts: Vec<&str> = line.split(' ').collect();
+            match parts[..] {
+                [";@std", len, expected_hash] => {
+                    let std_lib = reader.chars(parse_len(len)?)?;
+                    if format!("{:016X}", hash(&std_lib)) != expected_hash {
+                        return Err(String::from("The standard library in the bundle doesn't match its hash"));
+                    }
+                    Some(std_lib)
+                },
+                _ => return Err(format!("Expected ';@std length hash' in the bundle, got '{}'", line))
+            }
+        }
+    };
+
+    Ok(Bundle {
+        entry: String::from(entry),
+        source,
+        std_lib
+    })
+}
@@ -0,0 +1,37 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use ansi_term::Style;
+
+// Whether styled text gets escape codes. Main turns this off when the output
+// goes somewhere that doesn't understand them, like a file or a dumb terminal
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+// The style to actually paint with, a plain style doesn't write any escape codes
+pub fn style<S: Into<Style>>(style: S) -> Style {
+    if is_enabled() {
+        style.into()
+    }else{
+        Style::new()
+    }
+}
+
+// Colors are off if NO_COLOR is set, if TERM is "dumb", or if stdout isn't a terminal
+pub fn detect() -> bool {
+    if std::env::var_os("NO_COLOR").is_some() {
+        return false;
+    }
+
+    if std::env::var("TERM").map(|term| term == "dumb").unwrap_or(false) {
+        return false;
+    }
+
+    std::io::stdout().is_terminal()
+}
@@ -0,0 +1,42 @@
+use crate::{ Compiler, CompileOptions, STD_ROOT };
+use crate::compiler::CompiledUnit;
+use crate::binary::FORMAT_VERSION;
+use crate::emit::json_string;
+use crate::parse_bf::Loc;
+
+fn json_loc(loc: Loc) -> String {
+    format!("{{ \"line\": {}, \"column\": {} }}", loc.line(), loc.column())
+}
+
+// A description of every unit in a build, for tools that want to know what went where
+// without running the compiler themselves. 'program' is the linked root unit, the
+// ranges of every unit are where its code ended up in it, a unit that's used in many
+// places has many ranges. 'src_file' and 'std_file' are the files the units came from
+pub fn compile_database(compiler: &Compiler, program: &CompiledUnit, options: CompileOptions, src_file: &str, std_file: &str) -> String {
+    let units: Vec<String> = compiler.units().iter()
+        .map(|unit| {
+            let file = if unit.name.split('/').next() == Some(STD_ROOT) { std_file } else { src_file };
+            let dependencies: Vec<String> = unit.dependencies.iter().map(|name| json_string(name)).collect();
+            let ranges: Vec<String> = if unit.name == "src" {
+                vec![format!("[0, {}]", program.code.len())]
+            }else{
+                program.copies.iter()
+                    .filter(|(id, _, _)| *id == unit.id)
+                    .map(|(_, start, end)| format!("[{}, {}]", start, end))
+                    .collect()
+            };
+
+            format!("    {{\n      \"name\": {},\n      \"file\": {},\n      \"start\": {},\n      \"end\": {},\n      \
+                \"dependencies\": [{}],\n      \"noinline\": {},\n      \"compiled_size\": {},\n      \"ranges\": [{}]\n    }}",
+                json_string(&unit.name), json_string(file), json_loc(unit.defined_at),
+                unit.defined_until.map(json_loc).unwrap_or_else(|| String::from("null")),
+                dependencies.join(", "), unit.noinline,
+                unit.compiled_size.map(|size| size.to_string()).unwrap_or_else(|| String::from("null")),
+                ranges.join(", "))
+        })
+        .collect();
+
+    format!("{{\n  \"format_version\": {},\n  \"options\": {{ \"pool_strings\": {}, \"unicode_identifiers\": {}, \"assume_zero_tape\": {} }},\n  \"code_size\": {},\n  \
+        \"units\": [\n{}\n  ]\n}}\n",
+        FORMAT_VERSION, options.pool_strings, options.unicode_identifiers, options.assume_zero_tape, program.code.len(), units.join(",\n"))
+}
@@ -1,81 +1,403 @@
 use std::collections::{ HashMap, HashSet };
 use std::sync::{ Mutex };
-use crate::parse_bf::{ Token, TokenType, Loc };
+use crate::parse_bf::{ Token, TokenType, TableEntry, TableValue, Loc };
+use crate::interner::{ Interner, Symbol };
+use crate::binary::Label;
+use crate::testing::TestCase;
+use crate::{ Error, CompileOptions };
+use crate::color::style;
+use ansi_term::Color;
 
-pub struct Depender {
-    pub locs: Vec<Loc>,
-    pub id: u16
+// Limits so that pathological sources are errors instead of eating all the memory.
+// Repeat blocks in repeat blocks grow exponentially, so these are hit long before
+// anything reasonable would get close
+pub const MAX_EXPANDED_TOKENS: usize = 1 << 20;
+pub const MAX_UNIT_BYTES: usize = 1 << 26;
+
+struct Depender {
+    locs: Vec<Loc>,
+    id: Symbol
+}
+
+type UnitAst = (HashSet<Symbol>, Vec<Token>);
+
+// A macro that's used somewhere, but never compiled
+pub struct Unresolved {
+    pub name: String,
+    // It's defined, but something it uses couldn't compile
+    pub defined: bool,
+    // The names of the units that use it, and where they use it
+    pub users: Vec<(String, Vec<Loc>)>,
+    // The defined unit with the closest name, if it isn't defined and one is close enough to be a typo
+    pub suggestion: Option<String>
+}
+
+// How many characters a name can be off by to be suggested instead of a name that isn't defined
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+// What is known about a unit as soon as it's added, kept around after compiling
+struct UnitDefinition {
+    defined_at: Loc,
+    // Right after the end of the unit, once the lexer has gotten there
+    defined_until: Option<Loc>,
+    dependencies: Vec<Symbol>,
+    // How many tokens it has, counting the ones inside of loops and blocks
+    tokens: usize,
+    // The string literals in it, as (where, string, whether it clears the cells first)
+    strings: Vec<(Loc, String, bool)>,
+    tests: Vec<TestCase>
+}
+
+// Information about a compilation unit, for tools that want to look at a program
+// without caring about how the compiler works
+#[derive(Clone, Debug)]
+pub struct UnitInfo {
+    pub name: String,
+    pub id: Symbol,
+    // None if the unit wasn't compiled, because of unresolved dependencies
+    pub compiled_size: Option<usize>,
+    pub dependencies: Vec<String>,
+    pub defined_at: Loc,
+    pub defined_until: Option<Loc>,
+    pub noinline: bool,
+    // How many cells it says it uses, from ":name uses 10 cells { ... }"
+    pub footprint: Option<usize>,
+    // How many tokens it has after repeats are expanded, counting the ones inside of loops and blocks
+    pub tokens: usize,
+    // The string literals in it, as (where, string, whether it clears the cells first)
+    pub strings: Vec<(Loc, String, bool)>,
+    pub tests: Vec<TestCase>
+}
+
+// The code of a compiled unit, with the labels of the debug dumps in it
+#[derive(Clone, Default)]
+pub struct CompiledUnit {
+    pub code: Vec<u8>,
+    pub labels: Vec<Label>,
+    // The CALLs to noinline macros, as (offset of the CALL, macro). Their offsets
+    // are filled in by link, once it's known where the macros end up
+    pub calls: Vec<(usize, Symbol)>,
+    // Every place the code of another unit ended up in this one, as (unit, start, end).
+    // After linking, this includes where the noinline macros are
+    pub copies: Vec<(Symbol, usize, usize)>,
+    // Where the code of every token starts, as (offset, where the token is)
+    pub locs: Vec<(usize, Loc)>,
+    // After linking, the CALLs to macros in a library binary, as (offset of the CALL, macro)
+    pub imports: Vec<(usize, Symbol)>
 }
 
+// Every unit goes through these states:
+//  not_compiled, waiting for dependencies -> not_compiled and ready_to_compile -> compiled
+// 'dependencies' maps a unit to everyone waiting for it to compile, which also
+// includes units that are used but never defined.
 pub struct Compiler {
-    pub id_map: Mutex<HashMap<String, u16>>,
-    pub is_finished: Mutex<bool>,
-    pub ready_to_compile: Mutex<HashSet<u16>>,
-    pub n_values: Mutex<u16>,
-    pub compiled: Mutex<HashMap<u16, Vec<u8>>>,
-    pub not_compiled: Mutex<HashMap<u16, (HashSet<u16>, Vec<Token>)>>,
-    pub dependencies: Mutex<HashMap<u16, Vec<Depender>>>
+    names: Mutex<Interner>,
+    is_finished: Mutex<bool>,
+    ready_to_compile: Mutex<HashSet<Symbol>>,
+    compiled: Mutex<HashMap<Symbol, CompiledUnit>>,
+    not_compiled: Mutex<HashMap<Symbol, UnitAst>>,
+    dependencies: Mutex<HashMap<Symbol, Vec<Depender>>>,
+    definitions: Mutex<HashMap<Symbol, UnitDefinition>>,
+    // Macros that are called instead of being copied into every place they're used
+    noinline: Mutex<HashSet<Symbol>>,
+    // Macros that are in a library binary, see add_external
+    external: Mutex<HashSet<Symbol>>,
+    // How many cells the macros that say so use, from where they start and to the right
+    footprints: Mutex<HashMap<Symbol, usize>>,
+    // Things that aren't errors but should be said after lexing, like what the fun hooks say
+    notes: Mutex<Vec<Error>>,
+    // Things that are probably mistakes, but that don't keep the program from compiling,
+    // with the code they have in json diagnostics
+    warnings: Mutex<Vec<(&'static str, Error)>>,
+    // The strings that are compiled once and called, as (contents, is safe) -> subroutine
+    pooled_strings: Mutex<HashMap<(String, bool), Symbol>>,
+    options: CompileOptions
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Compiler {
     pub fn new() -> Compiler {
+        Compiler::with_options(CompileOptions::default())
+    }
+
+    pub fn with_options(options: CompileOptions) -> Compiler {
         Compiler {
-            id_map: Mutex::new(HashMap::new()),
+            names: Mutex::new(Interner::new()),
             is_finished: Mutex::new(false),
-            n_values: Mutex::new(0),
             ready_to_compile: Mutex::new(HashSet::new()),
             compiled: Mutex::new(HashMap::new()),
             not_compiled: Mutex::new(HashMap::new()),
-            dependencies: Mutex::new(HashMap::new())
+            dependencies: Mutex::new(HashMap::new()),
+            definitions: Mutex::new(HashMap::new()),
+            noinline: Mutex::new(HashSet::new()),
+            external: Mutex::new(HashSet::new()),
+            footprints: Mutex::new(HashMap::new()),
+            notes: Mutex::new(Vec::new()),
+            warnings: Mutex::new(Vec::new()),
+            pooled_strings: Mutex::new(HashMap::new()),
+            options
         }
     }
 
-    pub fn log_unresolved_dependencies(&self) {
-        let dependencies_lock = self.dependencies.lock().unwrap();
-
-        let mut nametable_map = HashMap::new();
-        for id_mapper in self.id_map.lock().unwrap().iter() {
-            nametable_map.insert(*id_mapper.1, String::from(id_mapper.0));
-        }
-
-        let not_compiled_lock = self.not_compiled.lock().unwrap();
-        // Get the unresolved dependencies
-        for dependency in dependencies_lock.iter() {
-            if dependency.1.len() > 0 {
-                // Print the name of the thing that is depended on
-                // but not defined
-                let unresolved_dependency_name = String::from(nametable_map.get(&dependency.0).unwrap());
-
-                // Check if it was actually not defined or just haven't compiled
-                if not_compiled_lock.contains_key(&dependency.0) {
-                    println!("'{}' {}:", unresolved_dependency_name,
-                        ansi_term::Color::Cyan.paint("is defined, but couldn't compile"));
-                }else{
-                    println!("'{}' {}:", unresolved_dependency_name,
-                        ansi_term::Color::Red.paint("is not defined, but code uses it"));
-
-                    
+    pub fn options(&self) -> &CompileOptions {
+        &self.options
+    }
+
+    pub fn add_note(&self, loc: Loc, msg: String) {
+        self.notes.lock().unwrap().push(Error::new(loc, msg));
+    }
+
+    pub fn add_warning(&self, code: &'static str, loc: Loc, msg: String) {
+        self.warnings.lock().unwrap().push((code, Error::new(loc, msg)));
+    }
+
+    // The warnings that haven't been printed yet
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.lock().unwrap().iter().map(|(_, warning)| warning.msg.clone()).collect()
+    }
+
+    // Prints the warnings and notes that were added since the last time
+    pub fn log_notes(&self) {
+        use crate::diagnostics::{ self, Diagnostic, Severity };
+        for (code, warning) in self.warnings.lock().unwrap().drain(..) {
+            diagnostics::count(Severity::Warning, None);
+            if diagnostics::is_json() {
+                println!("{}", Diagnostic::new(code, Severity::Warning, None, warning.msg).at(warning.loc).to_json());
+            }else{
+                println!("{} {}: {}", style(Color::Yellow).paint("WARNING"), warning.loc, warning.msg);
+            }
+        }
+        for note in self.notes.lock().unwrap().drain(..) {
+            if diagnostics::is_json() {
+                println!("{}", Diagnostic::new("note", Severity::Note, None, note.msg).at(note.loc).to_json());
+            }else{
+                println!("{} {}: {}", style(Color::Cyan).paint("NOTE"), note.loc, note.msg);
+            }
+        }
+    }
+
+    // The macros that are used but never compiled, sorted by name
+    pub fn unresolved_dependencies(&self) -> Vec<Unresolved> {
+        let dependencies = self.dependencies.lock().unwrap();
+        let names = self.names.lock().unwrap();
+        let not_compiled = self.not_compiled.lock().unwrap();
+
+        // Everything that could have been meant, the defined units and the ones from library binaries
+        let defined: Vec<&str> = self.definitions.lock().unwrap().keys()
+            .chain(self.external.lock().unwrap().iter())
+            .map(|id| names.resolve(*id))
+            .collect();
+
+        let mut unresolved: Vec<Unresolved> = dependencies.iter()
+            .filter(|(_, dependers)| !dependers.is_empty())
+            .map(|(id, dependers)| {
+                let name = names.resolve(*id);
+                let is_defined = not_compiled.contains_key(id);
+                let suggestion = if is_defined { None } else {
+                    defined.iter()
+                        .map(|candidate| (crate::edit_distance(name, candidate), *candidate))
+                        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+                        .min()
+                        .map(|(_, candidate)| String::from(candidate))
+                };
+
+                Unresolved {
+                    name: String::from(name),
+                    defined: is_defined,
+                    users: dependers.iter()
+                        .map(|depender| (String::from(names.resolve(depender.id)), depender.locs.clone()))
+                        .collect(),
+                    suggestion
                 }
+            })
+            .collect();
+        unresolved.sort_by(|a, b| a.name.cmp(&b.name));
+        unresolved
+    }
+
+    pub fn log_unresolved_dependencies(&self) {
+        for dependency in self.unresolved_dependencies() {
+            // Check if it was actually not defined or just haven't compiled
+            if dependency.defined {
+                println!("'{}' {}:", dependency.name,
+                    style(Color::Cyan).paint("is defined, but couldn't compile"));
+            }else{
+                println!("'{}' {}:", dependency.name,
+                    style(Color::Red).paint("is not defined, but code uses it"));
+            }
+            if let Some(suggestion) = &dependency.suggestion {
+                println!(" | did you mean '{}'?", suggestion);
+            }
 
-                // Print all the dependers, since it's not defined
-                for depender in dependency.1 {
-                    let name = String::from(nametable_map.get(&depender.id).unwrap());
-                    println!(" | '{}' uses it at: ", name);
-                    for loc in &depender.locs {
-                        println!(" |  | {}", loc);
-                    }
+            // Print all the dependers, since it's not defined
+            for (name, locs) in &dependency.users {
+                println!(" | '{}' uses it at: ", name);
+                for loc in locs {
+                    println!(" |  | {}", loc);
                 }
             }
         }
     }
 
-    pub fn get_compiled_value(&self, name: &str) -> Option<Vec<u8>> {
-        let id = *(self.id_map.lock().unwrap().get(&String::from(name))?);
+    // ":name uses 10 cells { ... }", checked once everything is compiled, see metrics::check_footprints
+    pub fn set_footprint(&self, id: Symbol, cells: usize) {
+        self.footprints.lock().unwrap().insert(id, cells);
+    }
+
+    // How many cells the macro says it uses, if it says so
+    pub fn footprint(&self, name: &str) -> Option<usize> {
+        let id = self.names.lock().unwrap().get(name)?;
+        self.footprints.lock().unwrap().get(&id).copied()
+    }
+
+    pub fn get_compiled_value(&self, name: &str) -> Option<CompiledUnit> {
+        let id = self.names.lock().unwrap().get(name)?;
         Some(self.compiled.lock().unwrap().get(&id)?.clone())
     }
 
+    // Makes the macro compile to a CALL everywhere it's used, has to be done before
+    // the macro is added
+    pub fn set_noinline(&self, id: Symbol) {
+        self.noinline.lock().unwrap().insert(id);
+    }
+
+    // A compiled unit as a whole program. Every noinline macro it calls is put once
+    // in a loop at the start of the program that never runs, since the first cell
+    // is always zero, and the CALLs are pointed at them.
+    // The CALLs to macros from a library binary are left for the loader, in 'imports'
+    pub fn link(&self, name: &str) -> Option<CompiledUnit> {
+        let root = self.get_compiled_value(name)?;
+        if root.calls.is_empty() {
+            return Some(root);
+        }
+
+        let called = root.calls.iter().map(|call| call.1).collect();
+        Some(self.link_subroutines(called, &root).0)
+    }
+
+    // Every compiled macro under 'root', like "std", as a library binary that programs can call into.
+    // The code is only the loop of subroutines, and the exports say where every macro starts
+    pub fn link_library(&self, root: &str) -> (CompiledUnit, Vec<(String, usize)>) {
+        let prefix = format!("{}/", root);
+        let mut exported: Vec<(String, Symbol)> = {
+            let names = self.names.lock().unwrap();
+            let external = self.external.lock().unwrap();
+            self.compiled.lock().unwrap().keys()
+                .filter(|id| !external.contains(id))
+                .map(|id| (String::from(names.resolve(*id)), *id))
+                .filter(|(name, _)| name.starts_with(&prefix))
+                .collect()
+        };
+        exported.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let (linked, offsets) = self.link_subroutines(exported.iter().map(|(_, id)| *id).collect(), &CompiledUnit::default());
+        let exports = exported.into_iter()
+            .map(|(name, id)| (name, offsets[&id]))
+            .collect();
+        (linked, exports)
+    }
+
+    // Puts the subroutines, and everything they call, in the loop that never runs, followed by 'root'.
+    // Gives back the linked code and where every subroutine starts
+    fn link_subroutines(&self, called: Vec<Symbol>, root: &CompiledUnit) -> (CompiledUnit, HashMap<Symbol, usize>) {
+        use crate::instructions::*;
+        let compiled = self.compiled.lock().unwrap();
+        let external = self.external.lock().unwrap();
+        let mut subroutines = Vec::new();
+        let mut seen = HashSet::new();
+        let mut pending = called;
+        while let Some(symbol) = pending.pop() {
+            if !external.contains(&symbol) && seen.insert(symbol) {
+                subroutines.push(symbol);
+                pending.extend(compiled[&symbol].calls.iter().map(|call| call.1));
+            }
+        }
+
+        let mut linked = CompiledUnit::default();
+        let mut offsets = HashMap::new();
+        let start = open_loop(&mut linked.code);
+        for symbol in subroutines {
+            let start = linked.code.len();
+            offsets.insert(symbol, start);
+            append_unit(&mut linked, &compiled[&symbol]);
+            linked.copies.push((symbol, start, linked.code.len()));
+            linked.code.push(RET);
+        }
+        if close_unit_loop(&mut linked, start) {
+            for offset in offsets.values_mut() {
+                *offset += WIDE_LOOP_EXTRA;
+            }
+        }
+        append_unit(&mut linked, root);
+
+        for (offset, target) in linked.calls.drain(..) {
+            match offsets.get(&target) {
+                Some(target) => linked.code[offset + 1..offset + 5].copy_from_slice(&(*target as u32).to_le_bytes()),
+                None => linked.imports.push((offset, target))
+            }
+        }
+
+        (linked, offsets)
+    }
+
+    // A noinline macro that's in a library binary instead of in the source. It compiles to
+    // CALLs like any noinline macro, but they're only filled in when the program is loaded
+    // on top of the library
+    pub fn add_external(&self, name: &str) {
+        let id = self.intern(name);
+        self.compiled.lock().unwrap().insert(id, CompiledUnit::default());
+        self.noinline.lock().unwrap().insert(id);
+        self.external.lock().unwrap().insert(id);
+    }
+
+    // Every unit that was added, sorted by name
+    pub fn units(&self) -> Vec<UnitInfo> {
+        let names = self.names.lock().unwrap();
+        let compiled = self.compiled.lock().unwrap();
+        let definitions = self.definitions.lock().unwrap();
+        let noinline = self.noinline.lock().unwrap();
+        let footprints = self.footprints.lock().unwrap();
+
+        let mut units: Vec<UnitInfo> = definitions.iter()
+            .map(|(id, definition)| {
+                let mut dependencies: Vec<String> = definition.dependencies.iter()
+                    .map(|dependency| String::from(names.resolve(*dependency)))
+                    .collect();
+                dependencies.sort();
+
+                UnitInfo {
+                    name: String::from(names.resolve(*id)),
+                    id: *id,
+                    compiled_size: compiled.get(id).map(|unit| unit.code.len()),
+                    dependencies,
+                    defined_at: definition.defined_at,
+                    defined_until: definition.defined_until,
+                    noinline: noinline.contains(id),
+                    footprint: footprints.get(id).copied(),
+                    tokens: definition.tokens,
+                    strings: definition.strings.clone(),
+                    tests: definition.tests.clone()
+                }
+            })
+            .collect();
+
+        units.sort_by(|a, b| a.name.cmp(&b.name));
+        units
+    }
+
+    // True if every unit that was added has compiled
     pub fn is_done(&self) -> bool {
-        self.not_compiled.lock().unwrap().len() == 0
+        self.not_compiled.lock().unwrap().is_empty()
+    }
+
+    // True once finish_compilation has run
+    pub fn is_finished(&self) -> bool {
+        *self.is_finished.lock().unwrap()
     }
 
     fn try_compile_one(&self) -> Result<bool, String> {
@@ -84,8 +406,8 @@ impl Compiler {
         {
             // Pick a ready element
             let mut ready = self.ready_to_compile.lock().unwrap();
-            if ready.len() > 0 {
-                id = Some(ready.iter().next().unwrap().clone());
+            if !ready.is_empty() {
+                id = Some(*ready.iter().next().unwrap());
                 ready.remove(&id.unwrap());
             }
         }
@@ -99,33 +421,94 @@ impl Compiler {
         }
     }
 
-    pub fn finish_compilation(&self) -> Result<(), String> {
+    // Compiles everything that can be compiled. What couldn't be, because something it
+    // uses isn't defined or can't compile, is given back
+    pub fn finish_compilation(&self) -> Result<Vec<Unresolved>, String> {
+        if self.options.pool_strings {
+            self.pool_strings();
+        }
+
         while self.try_compile_one()? {}
         *self.is_finished.lock().unwrap() = true;
 
-        Ok(())
+        Ok(self.unresolved_dependencies())
     }
 
-    pub fn get_identifier_or_create(&self, identifier: &String) -> u16 {
-        let mut id_map = self.id_map.lock().unwrap();
-        if let Some(id) = id_map.get(identifier) {
-            *id
-        }else{
-            let mut n_values = self.n_values.lock().unwrap();
-            let new_id = *n_values;
-            *n_values += 1;
-            id_map.insert(String::from(identifier), new_id);
-            new_id
+    // Attaches tests declared in the source to a unit that was already added
+    pub fn add_tests(&self, id: Symbol, tests: Vec<TestCase>) {
+        if let Some(definition) = self.definitions.lock().unwrap().get_mut(&id) {
+            definition.tests.extend(tests);
+        }
+    }
+
+    pub fn set_defined_until(&self, id: Symbol, loc: Loc) {
+        if let Some(definition) = self.definitions.lock().unwrap().get_mut(&id) {
+            definition.defined_until = Some(loc);
         }
     }
 
-    pub fn add_dependencies(&self, source: u16, dependencies: &HashMap<String, Vec<Loc>>) -> HashSet<u16> {
+    pub fn intern(&self, identifier: &str) -> Symbol {
+        self.names.lock().unwrap().intern(identifier)
+    }
+
+    // True if uses of the string are CALLs to a pooled copy of it, because of *pool_strings
+    pub fn is_pooled(&self, string: &str, is_safe: bool) -> bool {
+        self.pooled_strings.lock().unwrap().contains_key(&(String::from(string), is_safe))
+    }
+
+    pub fn name_of(&self, symbol: Symbol) -> String {
+        String::from(self.names.lock().unwrap().resolve(symbol))
+    }
+
+    // The tokens of a unit that hasn't been compiled yet, for tools that only lex the source
+    pub fn tokens_of(&self, id: Symbol) -> Option<Vec<Token>> {
+        self.not_compiled.lock().unwrap().get(&id).map(|(_, tokens)| tokens.clone())
+    }
+
+    // Every string that ends up in the program more than once is compiled a single time,
+    // as a noinline subroutine, and the places that use it call it instead.
+    // Strings that are shorter than a CALL aren't worth it, and strings that don't
+    // compile are left for the places that use them to report
+    fn pool_strings(&self) {
+        // The strings and macros that every unit uses, and how many times
+        let mut contents = HashMap::new();
+        for (id, (_, ast)) in self.not_compiled.lock().unwrap().iter() {
+            let mut strings = HashMap::new();
+            let mut macros = HashMap::new();
+            count_uses(ast, &mut strings, &mut macros);
+            contents.insert(*id, (strings, macros));
+        }
+
+        let noinline = self.noinline.lock().unwrap().clone();
+        let mut copies = HashMap::new();
+        let mut counts: HashMap<(String, bool), usize> = HashMap::new();
+        for (id, (strings, _)) in &contents {
+            let unit_copies = count_copies(*id, &contents, &noinline, &mut copies);
+            for (string, count) in strings {
+                *counts.entry(string.clone()).or_insert(0) += count * unit_copies;
+            }
+        }
+
+        for ((string, is_safe), count) in counts {
+            let mut code = Vec::new();
+            if count < 2 || compile_str(&mut code, &string, is_safe && !self.options.assume_zero_tape).is_err() || code.len() <= crate::instructions::len(crate::instructions::CALL) {
+                continue;
+            }
+
+            // Names like these can't be written in the source, so they never collide with a macro
+            let symbol = self.intern(&format!("{}\"{}\"", if is_safe { "" } else { "0" }, string));
+            self.compiled.lock().unwrap().insert(symbol, CompiledUnit { code, ..CompiledUnit::default() });
+            self.noinline.lock().unwrap().insert(symbol);
+            self.pooled_strings.lock().unwrap().insert((string, is_safe), symbol);
+        }
+    }
+
+    fn add_dependencies(&self, source: Symbol, dependencies: HashMap<Symbol, Vec<Loc>>) -> HashSet<Symbol> {
         let mut unresolved = HashSet::new();
         let mut depend = self.dependencies.lock().unwrap();
-        for dependency in dependencies {
-            let id = self.get_identifier_or_create(&dependency.0);
+        for (id, locs) in dependencies {
             let depender = Depender {
-                locs: dependency.1.clone(),
+                locs,
                 id: source
             };
 
@@ -145,20 +528,24 @@ impl Compiler {
         unresolved
     }
 
-    fn compile(&self, element: u16) -> Result<(), String> {
+    fn compile(&self, element: Symbol) -> Result<(), String> {
         let (dependencies, ast) = self.not_compiled.lock().unwrap()
                         .remove(&element)
                         .expect("compile: element was an invalid id");
         assert_eq!(dependencies.len(), 0, "Tried compiling element without resolving dependencies first");
 
-        let mut commands = Vec::new();
+        let mut unit = CompiledUnit::default();
         for token in ast {
-            commands.append(&mut compile_node(self, &token, &String::new())?);
+            compile_node(self, &token, &mut unit)?;
+            if unit.code.len() > MAX_UNIT_BYTES {
+                return Err(format!("'{}' compiles to more than {} bytes, the code at {} made it too big",
+                    self.name_of(element), MAX_UNIT_BYTES, token.src_loc));
+            }
         }
 
-        //println!("{}: {:?}", element, &commands);
+        //println!("{}: {:?}", element, &unit.code);
 
-        self.compiled.lock().unwrap().insert(element, commands);
+        self.compiled.lock().unwrap().insert(element, unit);
         if let Some(dependants) = self.dependencies.lock().unwrap().remove(&element) {
             for dependant in dependants {
                 let mut lock = self.not_compiled.lock().unwrap();
@@ -167,7 +554,7 @@ impl Compiler {
                 dependencies.remove(&element);
 
                 // If we resolved all their dependencies, hooray!! It can now compile properly
-                if dependencies.len() == 0 {
+                if dependencies.is_empty() {
                     self.ready_to_compile.lock().unwrap().insert(dependant.id);
                 }
             }
@@ -176,51 +563,125 @@ impl Compiler {
         Ok(())
     }
     
-    pub fn add_compilation_unit(&self, name: String, data: Vec<Token>, dependencies: HashMap<String, Vec<Loc>>) {
-        let id = self.get_identifier_or_create(&name);
-        
-        let unresolved_dependencies = self.add_dependencies(id, &dependencies);
-        if unresolved_dependencies.len() > 0 {
+    // Units can only be defined once, a second definition with the same name is an error
+    // instead of silently replacing the first one
+    pub fn add_compilation_unit(&self, id: Symbol, defined_at: Loc, data: Vec<Token>, dependencies: HashMap<Symbol, Vec<Loc>>)
+            -> Result<(), String> {
+        let name = self.name_of(id);
+        {
+            let mut definitions = self.definitions.lock().unwrap();
+            if let Some(existing) = definitions.get(&id) {
+                let place = if name.split('/').next() == Some(crate::STD_ROOT) { " in the standard library" } else { "" };
+                return Err(format!("'{}' is already defined at {}{}", name, existing.defined_at, place));
+            }
+
+            definitions.insert(id, UnitDefinition {
+                defined_at,
+                defined_until: None,
+                dependencies: dependencies.keys().cloned().collect(),
+                tokens: crate::parse_bf::weight(&data),
+                strings: crate::parse_bf::strings(&data),
+                tests: Vec::new()
+            });
+        }
+
+        let unresolved_dependencies = self.add_dependencies(id, dependencies);
+        if !unresolved_dependencies.is_empty() {
             self.not_compiled.lock().unwrap().insert(id, (unresolved_dependencies, data));
         }else{
             self.not_compiled.lock().unwrap().insert(id, (unresolved_dependencies, data));
             self.ready_to_compile.lock().unwrap().insert(id);
         }
+
+        Ok(())
     }
 }
 
-pub fn create_loop(contained_commands: Vec<u8>) -> Vec<u8> {
-    use crate::instructions::*;
+// Loops are emitted in place, the LOOP_OPEN gets a placeholder offset that
+// close_loop patches once the size of the body is known. Building the body
+// separately and inserting the header in front of it copies the whole body
+// again for every level of nesting, which gets really slow for big programs.
+const LOOP_HEADER_LEN: usize = 5;
+// How much further in the body ends up when a loop has to be widened
+const WIDE_LOOP_EXTRA: usize = 4;
 
-    let mut contained_commands = contained_commands;
-    let offset = contained_commands.len();
+pub fn open_loop(commands: &mut Vec<u8>) -> usize {
+    let start = commands.len();
+    commands.push(crate::instructions::LOOP_OPEN);
+    commands.extend_from_slice(&[0; LOOP_HEADER_LEN - 1]);
+    start
+}
 
-    contained_commands.reserve(10);
-    contained_commands.push(LOOP_CLOSE);
-    contained_commands.push(( offset        & 0xff) as u8);
-    contained_commands.push(((offset >> 8 ) & 0xff) as u8);
-    contained_commands.push(((offset >> 16) & 0xff) as u8);
-    contained_commands.push(((offset >> 24) & 0xff) as u8);
+// If the offsets don't fit in a u32 the loop is made wide instead, which moves the body
+// further in, so anything pointing into the body has to be moved too. Returns true if that happened
+pub fn close_loop(commands: &mut Vec<u8>, start: usize) -> bool {
+    use crate::instructions::*;
+    use std::convert::TryFrom;
+    let body = commands.len() - start - LOOP_HEADER_LEN;
+    if let Ok(offset) = u32::try_from(body + LOOP_HEADER_LEN * 2) {
+        commands.push(LOOP_CLOSE);
+        commands.extend_from_slice(&(body as u32).to_le_bytes());
+        commands[start + 1..start + LOOP_HEADER_LEN].copy_from_slice(&offset.to_le_bytes());
+        return false;
+    }
 
+    let offset = (body + (LOOP_HEADER_LEN + WIDE_LOOP_EXTRA) * 2) as u64;
+    let mut header = vec![LOOP_OPEN_WIDE];
+    header.extend_from_slice(&offset.to_le_bytes());
+    commands.splice(start..start + LOOP_HEADER_LEN, header);
+    commands.push(LOOP_CLOSE_WIDE);
+    commands.extend_from_slice(&(body as u64).to_le_bytes());
+    true
+}
 
-    let offset = offset + 10;
-    contained_commands.insert(0, LOOP_OPEN);
-    contained_commands.insert(1, (offset & 0xff) as u8);
-    contained_commands.insert(2, ((offset >> 8) & 0xff) as u8);
-    contained_commands.insert(3, ((offset >> 16) & 0xff) as u8);
-    contained_commands.insert(4, ((offset >> 24) & 0xff) as u8);
-    
+// close_loop for a loop in a unit, the labels, calls, copies and locations in the
+// body are moved along if the loop had to be widened
+fn close_unit_loop(unit: &mut CompiledUnit, start: usize) -> bool {
+    if !close_loop(&mut unit.code, start) {
+        return false;
+    }
+
+    let moved = |offset: usize| if offset > start { offset + WIDE_LOOP_EXTRA } else { offset };
+    for label in unit.labels.iter_mut() {
+        label.offset = moved(label.offset);
+    }
+    for (offset, _) in unit.calls.iter_mut() {
+        *offset = moved(*offset);
+    }
+    for (_, start, end) in unit.copies.iter_mut() {
+        *start = moved(*start);
+        *end = moved(*end);
+    }
+    for (offset, _) in unit.locs.iter_mut() {
+        *offset = moved(*offset);
+    }
+    true
+}
 
-    contained_commands
+// Scratch cells are cleared before they're used, unless *assume_zero_tape says they're zero already.
+// Everything that uses scratch cells leaves them at zero afterwards, so that holds as long as
+// the program itself doesn't leave anything in them
+fn clear_scratch(commands: &mut Vec<u8>, assume_zero: bool) {
+    if !assume_zero {
+        set_to_zero(commands);
+    }
 }
 
 fn set_to_zero(commands: &mut Vec<u8>) {
-    commands.append(&mut create_loop(vec![crate::instructions::DECREMENT]));
+    let start = open_loop(commands);
+    commands.push(crate::instructions::DECREMENT);
+    close_loop(commands, start);
 }
 
-fn compile_str(string: &str, mem_safe: bool) -> Result<Vec<u8>, String> {
-    use crate::instructions::*;
+// How many bytes of code a string literal compiles to when it isn't pooled
+pub fn string_size(string: &str, is_safe: bool) -> Result<usize, String> {
     let mut commands = Vec::new();
+    compile_str(&mut commands, string, is_safe)?;
+    Ok(commands.len())
+}
+
+fn compile_str(commands: &mut Vec<u8>, string: &str, mem_safe: bool) -> Result<(), String> {
+    use crate::instructions::*;
 
     for (i, c) in string.chars().enumerate() {
         if !c.is_ascii() {
@@ -231,87 +692,925 @@ fn compile_str(string: &str, mem_safe: bool) -> Result<Vec<u8>, String> {
 
         if i < string.len() - 1 {
             if i == 0 && mem_safe {
-                set_to_zero(&mut commands);
+                set_to_zero(commands);
             }
 
             // Shift right and set to 0
             commands.push(SHIFT_RIGHT);
             if mem_safe {
-                set_to_zero(&mut commands);
+                set_to_zero(commands);
             }
 
             let sqrt = (size as f32).sqrt().floor() as u8;
-            for _ in 0..sqrt {
-                commands.push(INCREMENT);
-            }
+            commands.resize(commands.len() + sqrt as usize, INCREMENT);
 
-            let mut loop_commands = Vec::new();
-            loop_commands.push(DECREMENT);
-            loop_commands.push(SHIFT_LEFT);
-            for _ in 0..sqrt {
-                loop_commands.push(INCREMENT);
-            }
-            loop_commands.push(SHIFT_RIGHT);
-            commands.append(&mut create_loop(loop_commands));
+            let start = open_loop(commands);
+            commands.push(DECREMENT);
+            commands.push(SHIFT_LEFT);
+            commands.resize(commands.len() + sqrt as usize, INCREMENT);
+            commands.push(SHIFT_RIGHT);
+            close_loop(commands, start);
 
             let fault = size - sqrt * sqrt;
 
             if fault != 0 {
                 commands.push(SHIFT_LEFT);
+                commands.resize(commands.len() + fault as usize, INCREMENT);
 
-                for _ in 0..fault {
-                    commands.push(INCREMENT);
-                }
-                
                 commands.push(SHIFT_RIGHT);
             }
         }else{
             if mem_safe {
-                set_to_zero(&mut commands);
+                set_to_zero(commands);
             }
 
             if size >= 0x88 {
                 // Invert the size
                 let size = 0xff ^ size;
-
-                for _ in 0..size {
-                    commands.push(DECREMENT);
-                }
+                commands.resize(commands.len() + size as usize, DECREMENT);
             }else {
-                for _ in 0..size {
-                    commands.push(INCREMENT);
-                }
+                commands.resize(commands.len() + size as usize, INCREMENT);
             }
 
             commands.push(SHIFT_RIGHT);
         }
     }
 
-    Ok(commands)
+    Ok(())
+}
+
+fn shift(commands: &mut Vec<u8>, amount: isize) {
+    use crate::instructions::*;
+    let instruction = if amount < 0 { SHIFT_LEFT } else { SHIFT_RIGHT };
+    commands.resize(commands.len() + amount.unsigned_abs(), instruction);
+}
+
+// Appends compiled code, moving its labels along with it
+fn append_unit(unit: &mut CompiledUnit, other: &CompiledUnit) {
+    let base = unit.code.len();
+    unit.labels.extend(other.labels.iter()
+        .map(|label| Label::new(base + label.offset, label.name.clone())));
+    unit.calls.extend(other.calls.iter().map(|(offset, target)| (base + offset, *target)));
+    unit.copies.extend(other.copies.iter().map(|(id, start, end)| (*id, base + start, base + end)));
+    unit.locs.extend(other.locs.iter().map(|(offset, loc)| (base + offset, *loc)));
+    unit.code.extend_from_slice(&other.code);
+}
+
+fn compile_block(macros: &Compiler, tokens: &[Token]) -> Result<CompiledUnit, String> {
+    let mut unit = CompiledUnit::default();
+    for token in tokens {
+        compile_node(macros, token, &mut unit)?;
+    }
+
+    Ok(unit)
+}
+
+type UnitUses = (HashMap<(String, bool), usize>, HashMap<Symbol, usize>);
+
+fn count_uses(tokens: &[Token], strings: &mut HashMap<(String, bool), usize>, macros: &mut HashMap<Symbol, usize>) {
+    for token in tokens {
+        match &token.data {
+            TokenType::Str(string, is_safe) => *strings.entry((string.clone(), *is_safe)).or_insert(0) += 1,
+            TokenType::Macro(name) => *macros.entry(*name).or_insert(0) += 1,
+            TokenType::Loop(contents) | TokenType::LoopAt(_, contents) => count_uses(contents, strings, macros),
+            TokenType::IfElse(then, other) => {
+                count_uses(then, strings, macros);
+                count_uses(other, strings, macros);
+            },
+            _ => {}
+        }
+    }
+}
+
+// How many times the code of a unit is copied into the program. Units nobody uses
+// count once, since they could be the program, and so do noinline macros
+fn count_copies(id: Symbol, contents: &HashMap<Symbol, UnitUses>, noinline: &HashSet<Symbol>,
+        copies: &mut HashMap<Symbol, usize>) -> usize {
+    if let Some(count) = copies.get(&id) {
+        return *count;
+    }
+
+    // Guards against cycles, those units never compile anyway
+    copies.insert(id, 1);
+    let mut count = 0;
+    if !noinline.contains(&id) {
+        for (user, (_, macros)) in contents {
+            if let Some(uses) = macros.get(&id) {
+                count += uses * count_copies(*user, contents, noinline, copies);
+            }
+        }
+    }
+
+    let count = count.max(1);
+    copies.insert(id, count);
+    count
+}
+
+fn call(unit: &mut CompiledUnit, target: Symbol) {
+    unit.calls.push((unit.code.len(), target));
+    unit.code.push(crate::instructions::CALL);
+    unit.code.extend_from_slice(&[0; 4]);
+}
+
+// The furthest to the right of where it started that some code moves the
+// memory pointer. None if the code doesn't end up where it started, or
+// if it has a loop that doesn't, or calls a noinline macro, since then
+// there's no way of knowing.
+fn highest_reach(code: &[u8]) -> Option<isize> {
+    highest_reach_with(code, &|_| None)
+}
+
+// Like highest_reach, but sees through the CALLs to pooled strings, since it's known how they move
+fn block_reach(macros: &Compiler, block: &CompiledUnit) -> Option<isize> {
+    if block.calls.is_empty() {
+        return highest_reach(&block.code);
+    }
+
+    let pooled: HashSet<Symbol> = macros.pooled_strings.lock().unwrap().values().cloned().collect();
+    let compiled = macros.compiled.lock().unwrap();
+    highest_reach_with(&block.code, &|offset| {
+        let target = block.calls.iter().find(|call| call.0 == offset)?.1;
+        if pooled.contains(&target) {
+            shift_and_reach(&compiled[&target].code, &|_| None)
+        }else{
+            None
+        }
+    })
+}
+
+fn highest_reach_with(code: &[u8], call_reach: &dyn Fn(usize) -> Option<(isize, isize)>) -> Option<isize> {
+    match shift_and_reach(code, call_reach)? {
+        (0, highest) => Some(highest),
+        _ => None
+    }
+}
+
+// Where the memory pointer ends up after some code, and the furthest to the right it goes
+// on the way. 'call_reach' gives the same thing for the CALL at an offset, if it's known
+fn shift_and_reach(code: &[u8], call_reach: &dyn Fn(usize) -> Option<(isize, isize)>) -> Option<(isize, isize)> {
+    use crate::instructions::*;
+    let mut offset = 0isize;
+    let mut highest = 0isize;
+    let mut loop_offsets = Vec::new();
+    let mut index = 0;
+    while index < code.len() {
+        match code[index] {
+            SHIFT_RIGHT => offset += 1,
+            SHIFT_LEFT => offset -= 1,
+            LOOP_OPEN | LOOP_OPEN_WIDE => loop_offsets.push(offset),
+            LOOP_CLOSE | LOOP_CLOSE_WIDE if loop_offsets.pop() != Some(offset) => return None,
+            CALL => {
+                let (shift, reach) = call_reach(index)?;
+                highest = highest.max(offset + reach);
+                offset += shift;
+            },
+            _ => {}
+        }
+
+        highest = highest.max(offset);
+        index += len(code[index]);
+    }
+
+    Some((offset, highest))
 }
 
-pub fn compile_node(macros: &Compiler, token: &Token, src_path: &String) -> Result<Vec<u8>, String> {
+// Compiles "?{ then }:{ else }" to the usual if/else with two flag cells:
+//  flag = 1, temp = 0
+//  x[ then, flag = 0, move x to temp ]
+//  move temp back to x
+//  flag[ else, flag = 0 ]
+// The flags are put right after the furthest cell the blocks use, and
+// are cleared before they are used.
+fn compile_if_else(macros: &Compiler, token: &Token, then: &[Token], other: &[Token], unit: &mut CompiledUnit)
+        -> Result<(), String> {
+    use crate::instructions::*;
+    let then = compile_block(macros, then)?;
+    let other = compile_block(macros, other)?;
+
+    let mut highest = 0;
+    for (block, name) in [(&then, "if"), (&other, "else")].iter() {
+        match block_reach(macros, block) {
+            Some(reach) => highest = highest.max(reach),
+            None => return Err(format!(
+                "{}: The {} block of '?' has to leave the memory pointer where it started, and can't use noinline macros",
+                token.src_loc, name))
+        }
+    }
+
+    let flag = highest + 1;
+    let temp = flag + 1;
+    let assume_zero = macros.options.assume_zero_tape;
+
+    shift(&mut unit.code, flag);
+    clear_scratch(&mut unit.code, assume_zero);
+    unit.code.push(INCREMENT);
+    shift(&mut unit.code, 1);
+    clear_scratch(&mut unit.code, assume_zero);
+    shift(&mut unit.code, -temp);
+
+    let then_start = open_loop(&mut unit.code);
+    append_unit(unit, &then);
+    shift(&mut unit.code, flag);
+    unit.code.push(DECREMENT);
+    shift(&mut unit.code, -flag);
+    let move_start = open_loop(&mut unit.code);
+    unit.code.push(DECREMENT);
+    shift(&mut unit.code, temp);
+    unit.code.push(INCREMENT);
+    shift(&mut unit.code, -temp);
+    close_loop(&mut unit.code, move_start);
+    close_unit_loop(unit, then_start);
+
+    shift(&mut unit.code, temp);
+    let restore_start = open_loop(&mut unit.code);
+    unit.code.push(DECREMENT);
+    shift(&mut unit.code, -temp);
+    unit.code.push(INCREMENT);
+    shift(&mut unit.code, temp);
+    close_loop(&mut unit.code, restore_start);
+    shift(&mut unit.code, -temp);
+
+    shift(&mut unit.code, flag);
+    let else_start = open_loop(&mut unit.code);
+    shift(&mut unit.code, -flag);
+    append_unit(unit, &other);
+    shift(&mut unit.code, flag);
+    unit.code.push(DECREMENT);
+    close_unit_loop(unit, else_start);
+    shift(&mut unit.code, -flag);
+
+    Ok(())
+}
+
+// Compiles "@>N[ body ]" to ">N[<N body >N]<N"
+fn compile_loop_at(macros: &Compiler, token: &Token, offset: isize, body: &[Token], unit: &mut CompiledUnit)
+        -> Result<(), String> {
+    let body = compile_block(macros, body)?;
+    if block_reach(macros, &body).is_none() {
+        return Err(format!(
+            "{}: The body of '@' has to leave the memory pointer where it started, and can't use noinline macros",
+            token.src_loc));
+    }
+
+    shift(&mut unit.code, offset);
+    let start = open_loop(&mut unit.code);
+    shift(&mut unit.code, -offset);
+    append_unit(unit, &body);
+    shift(&mut unit.code, offset);
+    close_unit_loop(unit, start);
+    shift(&mut unit.code, -offset);
+
+    Ok(())
+}
+
+fn add(commands: &mut Vec<u8>, amount: i64) {
+    use crate::instructions::*;
+    let instruction = if amount < 0 { DECREMENT } else { INCREMENT };
+    commands.resize(commands.len() + amount.unsigned_abs() as usize, instruction);
+}
+
+// Compiles "=[ ... ]". The sum is built up in an accumulator cell, copying every
+// cell into it with a temporary cell, and then moved into the current cell.
+// Both scratch cells go right after the furthest cell the expression uses.
+fn compile_assign(token: &Token, terms: &[(isize, i64)], constant: i64, assume_zero: bool, commands: &mut Vec<u8>)
+        -> Result<(), String> {
+    use crate::instructions::*;
+    // Every number becomes that many '+' or '-', so it's checked before any of them are made
+    let adds = terms.iter()
+        .map(|term| term.1.unsigned_abs())
+        .fold(constant.unsigned_abs(), |total, times| total.saturating_add(times));
+    if adds > MAX_UNIT_BYTES as u64 {
+        return Err(format!("{}: The numbers in '=[' add up to {} increments, more than the {} bytes a macro can compile to",
+            token.src_loc, adds, MAX_UNIT_BYTES));
+    }
+
+    let highest = terms.iter().map(|term| term.0).max().unwrap_or(0).max(0);
+    let acc = highest + 1;
+    let temp = acc + 1;
+
+    shift(commands, acc);
+    clear_scratch(commands, assume_zero);
+    shift(commands, 1);
+    clear_scratch(commands, assume_zero);
+    shift(commands, -temp);
+
+    for &(offset, times) in terms {
+        // cell[- acc+times temp+]
+        shift(commands, offset);
+        let copy_start = open_loop(commands);
+        commands.push(DECREMENT);
+        shift(commands, acc - offset);
+        add(commands, times);
+        shift(commands, 1);
+        commands.push(INCREMENT);
+        shift(commands, offset - temp);
+        close_loop(commands, copy_start);
+
+        // temp[- cell+]
+        shift(commands, temp - offset);
+        let restore_start = open_loop(commands);
+        commands.push(DECREMENT);
+        shift(commands, offset - temp);
+        commands.push(INCREMENT);
+        shift(commands, temp - offset);
+        close_loop(commands, restore_start);
+        shift(commands, -temp);
+    }
+
+    shift(commands, acc);
+    add(commands, constant);
+    shift(commands, -acc);
+
+    set_to_zero(commands);
+    shift(commands, acc);
+    let move_start = open_loop(commands);
+    commands.push(DECREMENT);
+    shift(commands, -acc);
+    commands.push(INCREMENT);
+    shift(commands, acc);
+    close_loop(commands, move_start);
+    shift(commands, -acc);
+
+    Ok(())
+}
+
+// Compiles "%[ ... ]". The cell is copied into a counter that counts down through the keys,
+// and when it hits zero at key k, the cell gets the value of k added to it, so the cell
+// doesn't have to be compared with every key:
+//  counter = x, found = 1
+//  for every key k up to the highest one in the table:
+//      zero = 1, counter[ zero = 0, move counter to temp ], move temp back to counter
+//      zero[ found[ x += value of k - k, found = 0 ] zero = 0 ]
+//      counter -= 1
+//  found[ x = the value of '_' ], counter = 0
+// The counter, temp, found and zero cells are the four cells right after the current cell,
+// and are cleared before they are used.
+fn compile_table(entries: &[TableEntry], default: TableValue, assume_zero: bool, commands: &mut Vec<u8>) {
+    use crate::instructions::*;
+    let (counter, temp, found, zero) = (1, 2, 3, 4);
+
+    let mut table = [default; 256];
+    for &(first, last, value) in entries {
+        for key in first..=last {
+            table[key as usize] = value;
+        }
+    }
+    let highest = (0..256).rev().find(|&key| table[key] != default).unwrap_or(0);
+
+    for cell in counter..=zero {
+        shift(commands, cell);
+        clear_scratch(commands, assume_zero);
+        shift(commands, -cell);
+    }
+    shift(commands, found);
+    commands.push(INCREMENT);
+    shift(commands, -found);
+
+    // x[- counter+ temp+] temp[- x+]
+    let copy_start = open_loop(commands);
+    commands.push(DECREMENT);
+    shift(commands, counter);
+    commands.push(INCREMENT);
+    shift(commands, temp - counter);
+    commands.push(INCREMENT);
+    shift(commands, -temp);
+    close_loop(commands, copy_start);
+    shift(commands, temp);
+    let restore_start = open_loop(commands);
+    commands.push(DECREMENT);
+    shift(commands, -temp);
+    commands.push(INCREMENT);
+    shift(commands, temp);
+    close_loop(commands, restore_start);
+    shift(commands, -temp);
+
+    for (key, value) in table.iter().enumerate().take(highest + 1) {
+        if *value != default {
+            let add_value = match *value {
+                TableValue::Set(value) => value as i64 - key as i64,
+                TableValue::Add(amount) => amount
+            };
+
+            shift(commands, zero);
+            commands.push(INCREMENT);
+            shift(commands, counter - zero);
+            let test_start = open_loop(commands);
+            shift(commands, zero - counter);
+            set_to_zero(commands);
+            shift(commands, counter - zero);
+            let move_start = open_loop(commands);
+            commands.push(DECREMENT);
+            shift(commands, temp - counter);
+            commands.push(INCREMENT);
+            shift(commands, counter - temp);
+            close_loop(commands, move_start);
+            close_loop(commands, test_start);
+            shift(commands, temp - counter);
+            let restore_start = open_loop(commands);
+            commands.push(DECREMENT);
+            shift(commands, counter - temp);
+            commands.push(INCREMENT);
+            shift(commands, temp - counter);
+            close_loop(commands, restore_start);
+
+            shift(commands, zero - temp);
+            let zero_start = open_loop(commands);
+            shift(commands, found - zero);
+            let found_start = open_loop(commands);
+            shift(commands, -found);
+            add(commands, add_value);
+            shift(commands, found);
+            commands.push(DECREMENT);
+            close_loop(commands, found_start);
+            shift(commands, zero - found);
+            commands.push(DECREMENT);
+            close_loop(commands, zero_start);
+            shift(commands, -zero);
+        }
+
+        shift(commands, counter);
+        commands.push(DECREMENT);
+        shift(commands, -counter);
+    }
+
+    shift(commands, found);
+    let default_start = open_loop(commands);
+    shift(commands, -found);
+    match default {
+        TableValue::Set(value) => {
+            set_to_zero(commands);
+            add(commands, value as i64);
+        },
+        TableValue::Add(amount) => add(commands, amount)
+    }
+    shift(commands, found);
+    commands.push(DECREMENT);
+    close_loop(commands, default_start);
+    shift(commands, counter - found);
+    set_to_zero(commands);
+    shift(commands, -counter);
+}
+
+fn compile_node(macros: &Compiler, token: &Token, unit: &mut CompiledUnit) -> Result<(), String> {
     use crate::instructions::*;
     use TokenType::*;
+    unit.locs.push((unit.code.len(), token.src_loc));
+    let commands = &mut unit.code;
     match &token.data {
-        Debug => Ok(vec![DEBUG]),
-        Str(string, is_safe) => Ok(compile_str(&string[..], *is_safe)?),
+        Debug(label) => {
+            if let Some(name) = label {
+                unit.labels.push(Label::new(commands.len(), name.clone()));
+            }
+            commands.push(DEBUG);
+        },
+        Assert(value) => {
+            commands.push(ASSERT);
+            commands.extend_from_slice(&(*value as u32).to_le_bytes());
+            commands.extend_from_slice(&(token.src_loc.line() as u32).to_le_bytes());
+            commands.extend_from_slice(&(token.src_loc.column() as u32).to_le_bytes());
+        },
+        DebugOp(kind, argument) => {
+            commands.push(DEBUG_OP);
+            commands.push(*kind);
+            commands.extend_from_slice(&(*argument as u32).to_le_bytes());
+        },
+        Introspect(kind) => {
+            commands.push(INTROSPECT);
+            commands.push(*kind);
+        },
+        Str(string, is_safe) => {
+            let pooled = macros.pooled_strings.lock().unwrap().get(&(string.clone(), *is_safe)).cloned();
+            match pooled {
+                Some(symbol) => call(unit, symbol),
+                None => compile_str(commands, &string[..], *is_safe && !macros.options.assume_zero_tape)?
+            }
+        },
+        Macro(name) if macros.noinline.lock().unwrap().contains(name) => call(unit, *name),
         Macro(name) => {
-            Ok(macros.get_compiled_value(&name[..]).expect("Dependency wasn't compiled"))
+            let compiled = macros.compiled.lock().unwrap();
+            let start = unit.code.len();
+            append_unit(unit, compiled.get(name).expect("Dependency wasn't compiled"));
+            unit.copies.push((*name, start, unit.code.len()));
         },
         Loop(sub_tokens) => {
-            let mut contents = Vec::new();
+            let start = open_loop(&mut unit.code);
             for sub_token in sub_tokens.iter() {
-                contents.append(&mut compile_node(macros, sub_token, src_path)?);
+                compile_node(macros, sub_token, unit)?;
             }
 
-            Ok(create_loop(contents))
+            close_unit_loop(unit, start);
         },
-        ShiftRight(amount) => Ok(vec![SHIFT_RIGHT; *amount as usize]),
-        ShiftLeft(amount) => Ok(vec![SHIFT_LEFT; *amount as usize]),
-        Increment(amount) => Ok(vec![INCREMENT; *amount as usize]),
-        Decrement(amount) => Ok(vec![DECREMENT; *amount as usize]),
-        Print => Ok(vec![PRINT]),
-        Read => Ok(vec![READ])
-    }
-}
\ No newline at end of file
+        IfElse(then, other) => compile_if_else(macros, token, then, other, unit)?,
+        LoopAt(offset, sub_tokens) => compile_loop_at(macros, token, *offset, sub_tokens, unit)?,
+        Assign(terms, constant) => compile_assign(token, terms, *constant, macros.options.assume_zero_tape, commands)?,
+        Table(entries, default) => compile_table(entries, *default, macros.options.assume_zero_tape, commands),
+        ShiftRight(amount) => commands.resize(commands.len() + *amount as usize, SHIFT_RIGHT),
+        ShiftLeft(amount) => commands.resize(commands.len() + *amount as usize, SHIFT_LEFT),
+        Increment(amount) => commands.resize(commands.len() + *amount as usize, INCREMENT),
+        Decrement(amount) => commands.resize(commands.len() + *amount as usize, DECREMENT),
+        Print => commands.push(PRINT),
+        Read => commands.push(READ)
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::*;
+
+    fn unit(compiler: &Compiler, name: &str, tokens: Vec<Token>, uses: &[&str]) {
+        let mut dependencies = HashMap::new();
+        for dependency in uses {
+            dependencies.insert(compiler.intern(dependency), vec![Loc::zero()]);
+        }
+
+        compiler.add_compilation_unit(compiler.intern(name), Loc::zero(), tokens, dependencies).unwrap();
+    }
+
+    fn increment(n: u8) -> Token {
+        Token::new_increment(Loc::zero(), n)
+    }
+
+    fn use_macro(compiler: &Compiler, name: &str) -> Token {
+        Token::new_macro(Loc::zero(), compiler.intern(name))
+    }
+
+    fn code(compiler: &Compiler, name: &str) -> Option<Vec<u8>> {
+        compiler.get_compiled_value(name).map(|unit| unit.code)
+    }
+
+    #[test]
+    fn unit_without_dependencies_compiles() {
+        let compiler = Compiler::new();
+        unit(&compiler, "src", vec![increment(2)], &[]);
+
+        assert!(!compiler.is_done());
+        compiler.finish_compilation().unwrap();
+
+        assert!(compiler.is_done());
+        assert!(compiler.is_finished());
+        assert_eq!(code(&compiler, "src"), Some(vec![INCREMENT, INCREMENT]));
+    }
+
+    #[test]
+    fn dependant_waits_for_dependency_added_later() {
+        let compiler = Compiler::new();
+        unit(&compiler, "src", vec![use_macro(&compiler, "src/a"), increment(1)], &["src/a"]);
+
+        compiler.finish_compilation().unwrap();
+        assert!(!compiler.is_done());
+        assert_eq!(code(&compiler, "src"), None);
+
+        unit(&compiler, "src/a", vec![Token::new_print(Loc::zero())], &[]);
+        compiler.finish_compilation().unwrap();
+
+        assert!(compiler.is_done());
+        assert_eq!(code(&compiler, "src"), Some(vec![PRINT, INCREMENT]));
+    }
+
+    #[test]
+    fn dependency_compiled_before_dependant_was_added() {
+        let compiler = Compiler::new();
+        unit(&compiler, "src/a", vec![increment(1)], &[]);
+        compiler.finish_compilation().unwrap();
+
+        unit(&compiler, "src", vec![use_macro(&compiler, "src/a"), use_macro(&compiler, "src/a")], &["src/a"]);
+        compiler.finish_compilation().unwrap();
+
+        assert!(compiler.is_done());
+        assert_eq!(code(&compiler, "src"), Some(vec![INCREMENT, INCREMENT]));
+    }
+
+    #[test]
+    fn chain_of_dependencies_resolves_in_order() {
+        let compiler = Compiler::new();
+        unit(&compiler, "src", vec![use_macro(&compiler, "src/b")], &["src/b"]);
+        unit(&compiler, "src/b", vec![use_macro(&compiler, "src/a"), increment(1)], &["src/a"]);
+        unit(&compiler, "src/a", vec![Token::new_read(Loc::zero())], &[]);
+        compiler.finish_compilation().unwrap();
+
+        assert!(compiler.is_done());
+        assert_eq!(code(&compiler, "src"), Some(vec![READ, INCREMENT]));
+    }
+
+    #[test]
+    fn undefined_dependency_is_unresolved() {
+        let compiler = Compiler::new();
+        unit(&compiler, "src", vec![use_macro(&compiler, "src/missing")], &["src/missing"]);
+        compiler.finish_compilation().unwrap();
+
+        assert!(!compiler.is_done());
+        let units = compiler.units();
+        assert_eq!(units.len(), 1);
+        assert_eq!(units[0].name, "src");
+        assert_eq!(units[0].compiled_size, None);
+        assert_eq!(units[0].dependencies, vec![String::from("src/missing")]);
+    }
+
+    #[test]
+    fn cycle_never_compiles() {
+        let compiler = Compiler::new();
+        unit(&compiler, "src/a", vec![use_macro(&compiler, "src/b")], &["src/b"]);
+        unit(&compiler, "src/b", vec![use_macro(&compiler, "src/a")], &["src/a"]);
+        compiler.finish_compilation().unwrap();
+
+        assert!(!compiler.is_done());
+        assert!(compiler.units().iter().all(|unit| unit.compiled_size.is_none()));
+    }
+
+    #[test]
+    fn labels_are_moved_with_expanded_macros() {
+        let compiler = Compiler::new();
+        unit(&compiler, "src/a", vec![Token::new_debug(Loc::zero(), Some(String::from("here")))], &[]);
+        unit(&compiler, "src", vec![increment(3), use_macro(&compiler, "src/a")], &["src/a"]);
+        compiler.finish_compilation().unwrap();
+
+        let compiled = compiler.get_compiled_value("src").unwrap();
+        assert_eq!(compiled.code, vec![INCREMENT, INCREMENT, INCREMENT, DEBUG]);
+        assert_eq!(compiled.labels.len(), 1);
+        assert_eq!(compiled.labels[0].offset, 3);
+        assert_eq!(compiled.labels[0].name, "here");
+    }
+
+    #[test]
+    fn highest_reach_needs_balanced_code() {
+        assert_eq!(highest_reach(&[SHIFT_RIGHT, SHIFT_RIGHT, SHIFT_LEFT, SHIFT_LEFT]), Some(2));
+        assert_eq!(highest_reach(&[SHIFT_LEFT, SHIFT_RIGHT]), Some(0));
+        assert_eq!(highest_reach(&[SHIFT_RIGHT]), None);
+
+        let mut unbalanced_loop = Vec::new();
+        let start = open_loop(&mut unbalanced_loop);
+        unbalanced_loop.push(SHIFT_RIGHT);
+        close_loop(&mut unbalanced_loop, start);
+        unbalanced_loop.push(SHIFT_LEFT);
+        assert_eq!(highest_reach(&unbalanced_loop), None);
+    }
+
+    #[test]
+    fn if_else_with_unbalanced_block_fails() {
+        let compiler = Compiler::new();
+        let if_else = Token::new_if_else(Loc::zero(), vec![Token::new_shift_right(Loc::zero(), 1)], Vec::new());
+        unit(&compiler, "src", vec![if_else], &[]);
+
+        assert!(compiler.finish_compilation().is_err());
+    }
+
+    #[test]
+    fn units_cant_be_defined_twice() {
+        let compiler = Compiler::new();
+        unit(&compiler, "std/marker", vec![increment(1)], &[]);
+
+        let again = compiler.add_compilation_unit(compiler.intern("std/marker"), Loc::zero(), vec![increment(2)], HashMap::new());
+        assert!(again.unwrap_err().contains("standard library"));
+
+        compiler.finish_compilation().unwrap();
+        assert_eq!(code(&compiler, "std/marker"), Some(vec![INCREMENT]));
+    }
+
+    #[test]
+    fn noinline_macros_are_linked_once() {
+        let compiler = Compiler::new();
+        compiler.set_noinline(compiler.intern("src/sub"));
+        unit(&compiler, "src/sub", vec![increment(1)], &[]);
+        let uses = vec![use_macro(&compiler, "src/sub"), use_macro(&compiler, "src/sub")];
+        unit(&compiler, "src", uses, &["src/sub"]);
+        compiler.finish_compilation().unwrap();
+
+        let linked = compiler.link("src").unwrap();
+        let call_to_sub = |offset: usize| {
+            assert_eq!(linked.code[offset], CALL);
+            assert_eq!(read_u32(&linked.code, offset + 1), 5);
+        };
+        assert_eq!(&linked.code[5..7], &[INCREMENT, RET]);
+        call_to_sub(12);
+        call_to_sub(17);
+        assert!(linked.calls.is_empty());
+    }
+
+    #[test]
+    fn strings_used_in_many_places_are_pooled() {
+        let compiler = Compiler::with_options(CompileOptions { pool_strings: true, ..CompileOptions::default() });
+        let hello = || Token::new_str(Loc::zero(), String::from("Hello"), true);
+        unit(&compiler, "src/greet", vec![hello()], &[]);
+        let uses = vec![use_macro(&compiler, "src/greet"), hello(), use_macro(&compiler, "src/greet")];
+        unit(&compiler, "src", uses, &["src/greet"]);
+        compiler.finish_compilation().unwrap();
+
+        let mut expected = Vec::new();
+        compile_str(&mut expected, "Hello", true).unwrap();
+        let linked = compiler.link("src").unwrap();
+        assert_eq!(&linked.code[5..5 + expected.len()], &expected[..]);
+        assert_eq!(linked.code[5 + expected.len()], RET);
+        // The rest of the program is just the three calls
+        let rest = &linked.code[5 + expected.len() + 1 + 5..];
+        assert_eq!(rest.len(), 15);
+        for offset in [0, 5, 10] {
+            assert_eq!(rest[offset], CALL);
+            assert_eq!(read_u32(rest, offset + 1), 5);
+        }
+    }
+
+    #[test]
+    fn loop_offsets_have_to_match() {
+        use crate::binary::validate_code;
+
+        let mut code = vec![INCREMENT];
+        let start = open_loop(&mut code);
+        code.push(DECREMENT);
+        close_loop(&mut code, start);
+        assert!(validate_code(&code).is_ok());
+
+        // The same loop with 64 bit offsets
+        let mut wide = vec![INCREMENT, LOOP_OPEN_WIDE];
+        wide.extend_from_slice(&19u64.to_le_bytes());
+        wide.push(DECREMENT);
+        wide.push(LOOP_CLOSE_WIDE);
+        wide.extend_from_slice(&1u64.to_le_bytes());
+        assert!(validate_code(&wide).is_ok());
+
+        // Like a body too big for its offset, that got cut off
+        code[2] = 0xFF;
+        assert!(validate_code(&code).is_err());
+        assert!(validate_code(&[LOOP_CLOSE, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn tables_map_every_key() {
+        use crate::interpreter::{ Interpreter, State };
+
+        let source = "%[ 'a-'z: @-20  3: 'y  _: @ ]";
+        for key in 0..=255u8 {
+            let text = format!("+{:X} {} .", key, source);
+            let program = crate::compile_test_program(&text);
+            let mut interpreter = Interpreter::new(program);
+            assert_eq!(interpreter.run(), &State::Finished);
+
+            let expected = match key {
+                b'a'..=b'z' => key - 0x20,
+                3 => b'y',
+                _ => key
+            };
+            assert_eq!(interpreter.take_output(), vec![expected]);
+        }
+    }
+
+    #[test]
+    fn identifiers_are_ascii_unless_asked_for() {
+        let compile = |text: &str, unicode_identifiers: bool| crate::compile_program(text.chars().collect(), None,
+            CompileOptions { unicode_identifiers, ..CompileOptions::default() }).is_ok();
+
+        assert!(compile(":a_b2 { + } #src/a_b2", false));
+        assert!(!compile(":café { + } #src/café", false));
+        assert!(compile(":café { + } #src/café", true));
+        assert!(!compile(":a.b { + }", false));
+        assert!(!compile(":a { + } #src/a.b", false));
+        assert!(!compile(":a { + } #src//a", false));
+        // Names that don't start with an ascii character, and dots that go above the root
+        assert!(compile(":éa { + } #/éa", true));
+        assert!(!compile("#éa", true));
+        assert!(!compile("#../x", false));
+        assert!(compile(":a { + } :b { #./a } #/b", false));
+    }
+
+    #[test]
+    fn programs_run_on_top_of_library_binaries() {
+        use crate::interpreter::{ Interpreter, State };
+
+        let std_lib = ":twice { ++ } :inner { #std/twice #std/twice } :four noinline { #std/inner }";
+        let library = crate::compile_library(std_lib.chars().collect(), CompileOptions::default())
+            .unwrap_or_else(|_| panic!("The library didn't compile"));
+        assert_eq!(library.exports.len(), 3);
+
+        let program = crate::compile_on_library("+ #std/four #std/twice .".chars().collect(), &library, CompileOptions::default())
+            .unwrap_or_else(|_| panic!("The program didn't compile"));
+        assert_eq!(program.imports.len(), 2);
+
+        let mut interpreter = Interpreter::new(program.on_library(&library).unwrap());
+        assert_eq!(interpreter.run(), &State::Finished);
+        assert_eq!(interpreter.take_output(), vec![7]);
+    }
+
+    #[test]
+    fn assume_zero_tape_skips_clearing_scratch_cells() {
+        use crate::interpreter::{ Interpreter, State };
+
+        let text = "+3 ?{ > =[ @<1*2 ] %[ 6: 'a _: 'b ] . < }:{ - } >> \"!\" < .";
+        let mut outputs = Vec::new();
+        let mut sizes = Vec::new();
+        for assume_zero_tape in [false, true] {
+            let program = crate::compile_program(text.chars().collect(), None,
+                CompileOptions { assume_zero_tape, ..CompileOptions::default() })
+                .unwrap_or_else(|_| panic!("{} didn't compile", text));
+            sizes.push(program.code.len());
+            let mut interpreter = Interpreter::new(program);
+            assert_eq!(interpreter.run(), &State::Finished);
+            outputs.push(interpreter.take_output());
+        }
+
+        assert_eq!(outputs[0], b"a!");
+        assert_eq!(outputs[0], outputs[1]);
+        assert!(sizes[1] < sizes[0]);
+    }
+
+    #[test]
+    fn unresolved_dependencies_suggest_close_names() {
+        let text = ":twice { ++ } :thrice { #src/tweice + } #src/twice #src/thrice #src/banana";
+        let compiler = match crate::compile_units(text.chars().collect(), None, CompileOptions::default()) {
+            Err(crate::CompileFailure::Unresolved(compiler)) => compiler,
+            _ => panic!("{} shouldn't compile", text)
+        };
+
+        let unresolved = compiler.unresolved_dependencies();
+        let names: Vec<_> = unresolved.iter().map(|dependency| (&dependency.name[..], dependency.suggestion.as_deref())).collect();
+        assert_eq!(names, vec![
+            ("src/banana", None),
+            ("src/thrice", None),
+            ("src/tweice", Some("src/twice")),
+        ]);
+    }
+
+    #[test]
+    fn std_reads_and_prints_numbers() {
+        use crate::interpreter::{ Interpreter, State };
+
+        let text = "(#std/read_num #std/print_num >+A.[-]<)3";
+        let program = crate::compile_program(text.chars().collect(), Some(include_str!("../std.bf").chars().collect()),
+            CompileOptions::default())
+            .unwrap_or_else(|_| panic!("{} didn't compile", text));
+        let mut interpreter = Interpreter::new(program);
+        interpreter.push_input(b"0 42 300\n");
+        assert_eq!(interpreter.run(), &State::Finished);
+        assert_eq!(interpreter.take_output(), b"0\n42\n44\n");
+    }
+
+    #[test]
+    fn the_program_is_lexed_even_if_std_has_errors() {
+        let failure = crate::compile_program("+ ]".chars().collect(), Some(":x { [ }".chars().collect()), CompileOptions::default());
+        match failure {
+            Err(crate::CompileFailure::Std(std_errors, errors)) => {
+                assert!(!std_errors.is_empty());
+                assert_eq!(errors.len(), 1);
+            },
+            _ => panic!("Expected errors in both std and the program")
+        }
+    }
+
+    #[test]
+    fn denied_features_are_errors_where_they_are_used() {
+        let mut options = CompileOptions::default();
+        options.features.add("src/io:read,print", false).unwrap();
+        options.features.add("src/io/echo:read", true).unwrap();
+        let text = ":io { :echo { , . ! } } , #io/echo";
+        let errors = match crate::compile_program(text.chars().collect(), None, options) {
+            Err(crate::CompileFailure::Source(errors)) => errors,
+            _ => panic!("{} should have been denied", text)
+        };
+        let messages: Vec<String> = errors.iter().map(|error| error.msg.clone()).collect();
+        assert_eq!(messages, vec![
+            String::from("'src/io/echo' isn't allowed to use print, it's denied in 'src/io'")
+        ]);
+    }
+
+    #[test]
+    fn raw_blocks_and_extensions_can_be_denied() {
+        let errors = |text: &str, rules: &str| {
+            let mut options = CompileOptions::default();
+            options.features.add(rules, false).unwrap();
+            match crate::compile_program(text.chars().collect(), None, options) {
+                Ok(_) => Vec::new(),
+                Err(crate::CompileFailure::Source(errors)) => errors.iter().map(|error| error.msg.clone()).collect(),
+                Err(_) => panic!("{} should have compiled or been denied", text)
+            }
+        };
+
+        let denied = |feature: &str| vec![format!("'src/lib' isn't allowed to use {}, it's denied in 'src/lib'", feature)];
+        assert_eq!(errors(":lib { `+[-]` } #/lib", "src/lib:raw"), denied("raw"));
+        for extension in ["?{ + }:{ - }", "@>1[ - ]", "=[ @>1*2 ]", "%[ 'a: 1 _: 0 ]"] {
+            let text = format!(":lib {{ {} }} #/lib", extension);
+            assert_eq!(errors(&text, "src/lib:extensions"), denied("extensions"), "{}", extension);
+            // Only the namespace is denied
+            assert!(errors(extension, "src/lib:extensions").is_empty(), "{}", extension);
+        }
+    }
+
+    #[test]
+    fn macros_have_to_stay_in_the_cells_they_say_they_use() {
+        let compile = |text: &str| crate::compile_program(text.chars().collect(), None, CompileOptions::default());
+
+        let program = ":pair uses 2 cells { >+< } :wide noinline uses 10 cells { >E #src/pair <E } #src/wide";
+        assert!(compile(program).is_ok());
+
+        let messages = |text: &str| match compile(text) {
+            Err(crate::CompileFailure::Source(errors)) => errors.iter().map(|error| error.msg.clone()).collect::<Vec<_>>(),
+            _ => panic!("{} should have failed", text)
+        };
+        assert_eq!(messages(":pair uses 2 cells { >> } #src/pair"),
+            vec![String::from("'src/pair' says it uses 2 cells, but it can touch 3 cells")]);
+        assert_eq!(messages(":back uses 2 cells { <> } #src/back"),
+            vec![String::from("'src/back' says it uses 2 cells, but it can touch the cell 1 to the left of where it starts")]);
+        assert_eq!(messages(":seek uses 2 cells { [>] } #src/seek"),
+            vec![String::from("'src/seek' says it uses 2 cells, but it has a loop that doesn't end where it started, so it could go anywhere")]);
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+use crate::parse_bf::Loc;
+use crate::emit::json_string;
+
+// "*message_format=json" prints every error, warning and note from the compiler as one
+// json object per line instead of as text, so other tools don't have to pick the text apart:
+//  {"code":"syntax","severity":"error","file":"foo.bf",
+//   "span":{"start":{"line":3,"column":5},"end":{"line":3,"column":5}},
+//   "message":"Expected ']' to end loop","related":[]}
+// Lines start at 1 and columns at 0, like in the text messages. Only where something starts
+// is known, so the start and end of the span are the same. The span is null for things
+// that aren't about a place in the file, and the file is null if it isn't known.
+// "related" has other places that have to do with it, with a file, a span and a message each.
+static JSON: AtomicBool = AtomicBool::new(false);
+
+pub fn set_json(json: bool) {
+    JSON.store(json, Ordering::Relaxed);
+}
+
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+// Every error and warning that was printed is counted, so that everything can be printed before
+// giving up, with a summary at the end, instead of stopping at the first thing that went wrong
+struct Tally {
+    errors: usize,
+    warnings: usize,
+    files: BTreeSet<String>
+}
+
+static TALLY: Mutex<Tally> = Mutex::new(Tally { errors: 0, warnings: 0, files: BTreeSet::new() });
+
+pub fn count(severity: Severity, file: Option<&str>) {
+    let mut tally = TALLY.lock().unwrap();
+    match severity {
+        Severity::Error => tally.errors += 1,
+        Severity::Warning => tally.warnings += 1,
+        Severity::Note => return
+    }
+    if let Some(file) = file.filter(|file| !file.is_empty()) {
+        tally.files.insert(String::from(file));
+    }
+}
+
+pub fn error_count() -> usize {
+    TALLY.lock().unwrap().errors
+}
+
+// Ex: "4 errors, 2 warnings in 5 files", None if nothing went wrong
+pub fn summary() -> Option<String> {
+    let tally = TALLY.lock().unwrap();
+    if tally.errors == 0 && tally.warnings == 0 {
+        return None;
+    }
+
+    let plural = |n: usize, word: &str| if n == 1 { format!("1 {}", word) } else { format!("{} {}s", n, word) };
+    let mut summary = format!("{}, {}", plural(tally.errors, "error"), plural(tally.warnings, "warning"));
+    if !tally.files.is_empty() {
+        summary.push_str(&format!(" in {}", plural(tally.files.len(), "file")));
+    }
+    Some(summary)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note
+}
+
+impl Severity {
+    pub fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note"
+        }
+    }
+}
+
+pub struct Related {
+    pub file: Option<String>,
+    pub loc: Loc,
+    pub message: String
+}
+
+pub struct Diagnostic {
+    // What kind of problem it is, these don't change between versions so they're safe to match on
+    pub code: &'static str,
+    pub severity: Severity,
+    pub file: Option<String>,
+    pub loc: Option<Loc>,
+    pub message: String,
+    pub related: Vec<Related>
+}
+
+impl Diagnostic {
+    pub fn new(code: &'static str, severity: Severity, file: Option<&str>, message: String) -> Diagnostic {
+        Diagnostic {
+            code,
+            severity,
+            file: file.map(String::from),
+            loc: None,
+            message,
+            related: Vec::new()
+        }
+    }
+
+    pub fn at(mut self, loc: Loc) -> Diagnostic {
+        self.loc = Some(loc);
+        self
+    }
+
+    pub fn with_related(mut self, file: Option<&str>, loc: Loc, message: String) -> Diagnostic {
+        self.related.push(Related { file: file.map(String::from), loc, message });
+        self
+    }
+
+    pub fn to_json(&self) -> String {
+        let related: Vec<String> = self.related.iter()
+            .map(|related| format!("{{\"file\":{},\"span\":{},\"message\":{}}}",
+                optional_string(&related.file), span(Some(related.loc)), json_string(&related.message)))
+            .collect();
+
+        format!("{{\"code\":{},\"severity\":{},\"file\":{},\"span\":{},\"message\":{},\"related\":[{}]}}",
+            json_string(self.code), json_string(self.severity.name()), optional_string(&self.file),
+            span(self.loc), json_string(&self.message), related.join(","))
+    }
+}
+
+fn optional_string(text: &Option<String>) -> String {
+    match text {
+        Some(text) => json_string(text),
+        None => String::from("null")
+    }
+}
+
+fn span(loc: Option<Loc>) -> String {
+    match loc {
+        Some(loc) => {
+            let position = format!("{{\"line\":{},\"column\":{}}}", loc.line(), loc.column());
+            format!("{{\"start\":{},\"end\":{}}}", position, position)
+        },
+        None => String::from("null")
+    }
+}
+
+// Prints a warning that isn't about a place in a file, as json with "*message_format=json"
+pub fn warn(code: &'static str, file: Option<&str>, message: String) {
+    count(Severity::Warning, file);
+    if is_json() {
+        println!("{}", Diagnostic::new(code, Severity::Warning, file, message).to_json());
+    }else{
+        println!("WARNING: {}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostics_are_valid_json() {
+        let mut loc = Loc::zero();
+        for c in "ab\ncd".chars() {
+            loc.move_with(c);
+        }
+
+        let diagnostic = Diagnostic::new("syntax", Severity::Error, Some(r#"dir\"a".bf"#), String::from("Expected ']'\n\tgot \"}\""))
+            .at(loc)
+            .with_related(None, Loc::zero(), String::from("the '[' is here"));
+        assert_eq!(diagnostic.to_json(), concat!(
+            r#"{"code":"syntax","severity":"error","file":"dir\\\"a\".bf","#,
+            r#""span":{"start":{"line":2,"column":2},"end":{"line":2,"column":2}},"#,
+            r#""message":"Expected ']'\u000a\u0009got \"}\"","#,
+            r#""related":[{"file":null,"span":{"start":{"line":1,"column":0},"end":{"line":1,"column":0}},"#,
+            r#""message":"the '[' is here"}]}"#));
+
+        let warning = Diagnostic::new("large_source", Severity::Warning, None, String::from("big"));
+        assert_eq!(warning.to_json(), r#"{"code":"large_source","severity":"warning","file":null,"span":null,"message":"big","related":[]}"#);
+    }
+}
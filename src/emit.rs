@@ -0,0 +1,360 @@
+use std::fs;
+use std::path::{ Path, PathBuf };
+
+use crate::instructions::*;
+use crate::binary::{ Program, FORMAT_VERSION, write_bin_to_file };
+use crate::pragma::Tape;
+use crate::introspect::fact_name;
+
+// The files that "*emit" can write next to the source
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EmitKind {
+    // foo.bin, the compiled binary
+    Bin,
+    // foo.plain.bf, the program as plain brainfuck
+    Plain,
+    // foo.listing.txt, every instruction with its offset
+    Listing,
+    // foo.map.json, which instruction every character of the plain brainfuck came from
+    Map
+}
+
+impl EmitKind {
+    pub const ALL: [EmitKind; 4] = [EmitKind::Bin, EmitKind::Plain, EmitKind::Listing, EmitKind::Map];
+
+    // Parses "all", or a list of kinds separated by commas, ex: "bin,listing"
+    pub fn parse_list(text: &str) -> Result<Vec<EmitKind>, String> {
+        if text == "all" {
+            return Ok(EmitKind::ALL.to_vec());
+        }
+
+        text.split(',')
+            .map(|kind| match kind {
+                "bin" => Ok(EmitKind::Bin),
+                "plain" => Ok(EmitKind::Plain),
+                "listing" => Ok(EmitKind::Listing),
+                "map" => Ok(EmitKind::Map),
+                _ => Err(format!("Unknown thing to emit '{}', expected all, bin, plain, listing or map", kind))
+            })
+            .collect()
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            EmitKind::Bin => "bin",
+            EmitKind::Plain => "plain.bf",
+            EmitKind::Listing => "listing.txt",
+            EmitKind::Map => "map.json"
+        }
+    }
+}
+
+// What happens when a foreign interpreter reads past the end of its input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eof {
+    Zero,
+    MinusOne,
+    Unchanged
+}
+
+// What another brainfuck interpreter assumes, for checking that the plain brainfuck of a program
+// works on it. None is for the things the target doesn't care about
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Target {
+    pub cell_bits: Option<u8>,
+    // How many cells the tape has, starting at the cell the program starts on
+    pub tape: Option<u32>,
+    pub eof: Option<Eof>
+}
+
+impl Target {
+    // "classic" is the original interpreter, 8 bit cells, 30000 of them, and reading past the end
+    // leaves the cell alone. The settings go after it, or on their own, ex: "classic,eof:zero"
+    // or "cells:16,tape:65536"
+    pub fn parse(text: &str) -> Result<Target, String> {
+        let mut target = Target::default();
+        for part in text.split(',') {
+            match part.split_once(':') {
+                None if part == "classic" => {
+                    target = Target { cell_bits: Some(8), tape: Some(30000), eof: Some(Eof::Unchanged) };
+                },
+                Some(("cells", bits)) => target.cell_bits = Some(match bits {
+                    "8" => 8,
+                    "16" => 16,
+                    "32" => 32,
+                    _ => return Err(format!("Unsupported cell size '{}' for the target, expected 8, 16 or 32", bits))
+                }),
+                Some(("tape", length)) => target.tape = Some(length.parse().ok().filter(|length| *length > 0)
+                    .ok_or_else(|| format!("Invalid tape length '{}' for the target", length))?),
+                Some(("eof", eof)) => target.eof = Some(match eof {
+                    "zero" => Eof::Zero,
+                    "minus_one" => Eof::MinusOne,
+                    "unchanged" => Eof::Unchanged,
+                    _ => return Err(format!("Unknown eof behaviour '{}' for the target, expected zero, minus_one or unchanged", eof))
+                }),
+                _ => return Err(format!("Unknown target setting '{}', expected classic, cells:[bits], tape:[length] or eof:[behaviour]", part))
+            }
+        }
+
+        Ok(target)
+    }
+}
+
+// Why the plain brainfuck of the program can't be made to work on the target, if it can't.
+// Cells that wrap around at other values would need a cell to work in after every '+' and '-'
+fn unmet_target(program: &Program, target: &Target) -> Option<String> {
+    let bits = target.cell_bits.filter(|bits| *bits != program.pragmas.cell_bits)?;
+    Some(format!("it's made for {} bit cells but the target has {} bit cells, \
+        and plain brainfuck can't make them wrap around at the same values", program.pragmas.cell_bits, bits))
+}
+
+// Checks the program against the target. It's an error if it can't work there at all,
+// otherwise the ways it still might not work are returned as warnings
+pub fn check_target(program: &Program, target: &Target) -> Result<Vec<String>, String> {
+    if let Some(msg) = unmet_target(program, target) {
+        return Err(msg);
+    }
+
+    let mut warnings = Vec::new();
+    if let Some(length) = target.tape {
+        match program.pragmas.tape {
+            Tape::Fixed(cells) if cells <= length => {},
+            Tape::Fixed(cells) => warnings.push(format!("The program is made for a tape of {} cells, \
+                but the target only has {}", cells, length)),
+            // Most programs never go left of where they start, so this isn't worth a warning
+            Tape::Infinite => {}
+        }
+    }
+
+    let mut index = 0;
+    while index < program.code.len() && program.code[index] != READ {
+        index += len(program.code[index]);
+    }
+
+    if target.eof == Some(Eof::MinusOne) && index < program.code.len() {
+        warnings.push(String::from("The program reads input, and on the target reading past the end sets the cell to -1, \
+            the plain brainfuck can't turn that into the 0 it reads on other targets"));
+    }
+
+    Ok(warnings)
+}
+
+// Like plain_bf, but for running on the target. Reading past the end of the input gives 0 in the
+// plain brainfuck, so on targets that leave the cell alone every ',' clears the cell first
+pub fn plain_bf_for(program: &Program, target: &Target) -> Result<(String, Vec<usize>), String> {
+    if let Some(msg) = unmet_target(program, target) {
+        return Err(msg);
+    }
+
+    let (text, origins) = plain_bf(&program.code)?;
+    if target.eof != Some(Eof::Unchanged) {
+        return Ok((text, origins));
+    }
+
+    let mut normalized = String::with_capacity(text.len());
+    let mut normalized_origins = Vec::with_capacity(origins.len());
+    for (c, origin) in text.chars().zip(origins) {
+        if c == ',' {
+            normalized.push_str("[-]");
+            normalized_origins.extend([origin; 3]);
+        }
+        normalized.push(c);
+        normalized_origins.push(origin);
+    }
+    Ok((normalized, normalized_origins))
+}
+
+// The program as plain brainfuck, along with the offset of the instruction
+// every character came from. Debug instructions and assertions are left out,
+// and programs with calls can't be written as brainfuck at all.
+pub fn plain_bf(code: &[u8]) -> Result<(String, Vec<usize>), String> {
+    let mut text = String::with_capacity(code.len());
+    let mut origins = Vec::with_capacity(code.len());
+    let mut index = 0;
+
+    while index < code.len() {
+        let c = match code[index] {
+            SHIFT_LEFT => Some('<'),
+            SHIFT_RIGHT => Some('>'),
+            INCREMENT => Some('+'),
+            DECREMENT => Some('-'),
+            PRINT => Some('.'),
+            READ => Some(','),
+            LOOP_OPEN | LOOP_OPEN_WIDE => Some('['),
+            LOOP_CLOSE | LOOP_CLOSE_WIDE => Some(']'),
+            DEBUG | ASSERT | DEBUG_OP => None,
+            CALL | RET => return Err(String::from("it uses CALL and RET")),
+            INTROSPECT => return Err(String::from("it uses introspection")),
+            instruction => return Err(format!("invalid instruction {:X} at {:X}", instruction, index))
+        };
+
+        if let Some(c) = c {
+            text.push(c);
+            origins.push(index);
+        }
+        index += len(code[index]);
+    }
+
+    Ok((text, origins))
+}
+
+fn debug_op_name(kind: u8) -> &'static str {
+    match kind {
+        DEBUG_WINDOW => "WINDOW",
+        DEBUG_DUMP => "DUMP",
+        DEBUG_MARKER => "MARKER",
+        DEBUG_PAUSE => "PAUSE",
+        _ => "?"
+    }
+}
+
+// A human readable listing of every instruction in the program
+pub fn listing(program: &Program) -> String {
+    let code = &program.code[..];
+    let tape = match program.pragmas.tape {
+        Tape::Infinite => String::from("infinite"),
+        Tape::Fixed(length) => format!("fixed, {} cells", length)
+    };
+
+    let mut text = format!("; format version {}, cells {} bits, tape {}, {} bytes of code\n",
+        FORMAT_VERSION, program.pragmas.cell_bits, tape, code.len());
+    let mut index = 0;
+    while index < code.len() {
+        let line = match code[index] {
+            SHIFT_RIGHT => String::from("SHIFT_RIGHT"),
+            SHIFT_LEFT => String::from("SHIFT_LEFT"),
+            INCREMENT => String::from("INCREMENT"),
+            DECREMENT => String::from("DECREMENT"),
+            PRINT => String::from("PRINT"),
+            READ => String::from("READ"),
+            LOOP_OPEN => format!("LOOP_OPEN   -> {:04X}", index + read_u32(code, index + 1) as usize),
+            LOOP_CLOSE => format!("LOOP_CLOSE  -> {:04X}", index - read_u32(code, index + 1) as usize),
+            LOOP_OPEN_WIDE => format!("LOOP_OPEN_WIDE  -> {:04X}", index + loop_offset(code, index)),
+            LOOP_CLOSE_WIDE => format!("LOOP_CLOSE_WIDE -> {:04X}", index - loop_offset(code, index)),
+            DEBUG => match program.label_at(index) {
+                Some(label) => format!("DEBUG       '{}'", label),
+                None => String::from("DEBUG")
+            },
+            ASSERT => format!("ASSERT      {:X} at ({}, {})",
+                read_u32(code, index + 1), read_u32(code, index + 5), read_u32(code, index + 9)),
+            DEBUG_OP => format!("DEBUG_OP    {} {:X}", debug_op_name(code[index + 1]), read_u32(code, index + 2)),
+            CALL => format!("CALL        -> {:04X}", read_u32(code, index + 1)),
+            RET => String::from("RET"),
+            INTROSPECT => format!("INTROSPECT  {}", fact_name(code[index + 1])),
+            instruction => format!("?? {:X}", instruction)
+        };
+
+        text.push_str(&format!("{:04X}  {}\n", index, line));
+        index += len(code[index]);
+    }
+
+    text
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c)
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// The labels, and the offset in the binary of every character in the plain brainfuck
+fn source_map(program: &Program, plain_origins: Option<&[usize]>) -> String {
+    let labels: Vec<String> = program.labels.iter()
+        .map(|label| format!("{{ \"name\": {}, \"offset\": {} }}", json_string(&label.name), label.offset))
+        .collect();
+    let plain = match plain_origins {
+        Some(origins) => {
+            let offsets: Vec<String> = origins.iter().map(|offset| offset.to_string()).collect();
+            format!("[{}]", offsets.join(", "))
+        },
+        None => String::from("null")
+    };
+
+    format!("{{\n  \"format_version\": {},\n  \"code_size\": {},\n  \"labels\": [{}],\n  \"plain_offsets\": {}\n}}\n",
+        FORMAT_VERSION, program.code.len(), labels.join(", "), plain)
+}
+
+// Where the file of a kind goes, the path of the program with a new extension
+pub fn emit_path(program_path: &str, kind: EmitKind) -> PathBuf {
+    Path::new(program_path).with_extension(kind.extension())
+}
+
+// Writes all the files at once from the same program, so they always match. The plain
+// brainfuck is made for 'target'. Returns the paths of the files that were written
+pub fn emit(program_path: &str, program: &Program, kinds: &[EmitKind], target: &Target) -> Result<Vec<PathBuf>, String> {
+    let plain = plain_bf_for(program, target);
+    let mut written = Vec::new();
+
+    for &kind in kinds {
+        let path = emit_path(program_path, kind);
+        let result = match kind {
+            EmitKind::Bin => write_bin_to_file(&path.to_string_lossy(), program),
+            EmitKind::Plain => match &plain {
+                Ok((text, _)) => fs::write(&path, text),
+                Err(msg) => return Err(format!("Couldn't emit plain brainfuck, {}", msg))
+            },
+            EmitKind::Listing => fs::write(&path, listing(program)),
+            EmitKind::Map => fs::write(&path, source_map(program, plain.as_ref().ok().map(|(_, origins)| &origins[..])))
+        };
+
+        result.map_err(|error| format!("Couldn't write '{}', {}", path.display(), error))?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile_test_program;
+
+    #[test]
+    fn targets_warn_about_what_they_cant_run() {
+        let target = Target::parse("classic,eof:zero").unwrap_or_else(|msg| panic!("{}", msg));
+        assert_eq!(target, Target { cell_bits: Some(8), tape: Some(30000), eof: Some(Eof::Zero) });
+        assert!(Target::parse("cells:12").is_err());
+
+        let compile = compile_test_program;
+        let check = |text: &str, target: &str| check_target(&compile(text), &Target::parse(target).unwrap());
+        assert_eq!(check("+5 , .", "classic,eof:zero"), Ok(Vec::new()));
+        assert_eq!(check("+5 , .", "classic").map(|warnings| warnings.len()), Ok(0));
+        assert_eq!(check("#pragma tape fixed 40000\n+5 .", "classic").map(|warnings| warnings.len()), Ok(1));
+        assert_eq!(check("+5 , .", "eof:minus_one").map(|warnings| warnings.len()), Ok(1));
+        assert_eq!(check("+5 .", "eof:minus_one"), Ok(Vec::new()));
+        assert!(check("#pragma cells 16\n+", "classic").is_err());
+        assert_eq!(check("#pragma cells 16\n+", "cells:16"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn plain_brainfuck_reads_zero_past_the_end_on_every_target() {
+        let program = compile_test_program("+ ,[.,]");
+        let plain = |target: &str| plain_bf_for(&program, &Target::parse(target).unwrap()).map(|(text, _)| text);
+        assert_eq!(plain("eof:zero"), Ok(String::from("+,[.,]")));
+        assert_eq!(plain("eof:minus_one"), Ok(String::from("+,[.,]")));
+        assert_eq!(plain("classic"), Ok(String::from("+[-],[.[-],]")));
+        assert!(plain("cells:16").is_err());
+
+        // Every character still knows where it came from
+        let (text, origins) = plain_bf_for(&program, &Target::parse("classic").unwrap()).unwrap();
+        assert_eq!(text.len(), origins.len());
+        assert!(origins[1..5].iter().all(|origin| program.code[*origin] == READ));
+    }
+
+    #[test]
+    fn json_strings_escape_what_json_needs() {
+        assert_eq!(json_string("plain"), "\"plain\"");
+        assert_eq!(json_string(r#"say "hi" C:\bf"#), r#""say \"hi\" C:\\bf""#);
+        assert_eq!(json_string("a\nb\t\u{0}\u{1b}"), r#""a\u000ab\u0009\u0000\u001b""#);
+        assert_eq!(json_string("é ✓"), "\"é ✓\"");
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::{ Duration, Instant };
+
+use crate::binary::Program;
+use crate::interpreter::{ Interpreter, State };
+
+// "*entries=src/a,src/b" runs several macros of a program as programs of their own. The program
+// is compiled once, and every entry gets a fresh tape and the same input.
+pub struct EntryRun {
+    pub name: String,
+    pub output: Vec<u8>,
+    pub steps: u64,
+    pub time: Duration,
+    // Running if it hit the step limit, NeedsInput if it ran out of input
+    pub state: State
+}
+
+fn run_entry(name: String, program: Program, input: &[u8], max_steps: Option<u64>) -> EntryRun {
+    let start = Instant::now();
+    let mut interpreter = Interpreter::new(program);
+    interpreter.push_input(input);
+    let state = match max_steps {
+        Some(max_steps) => interpreter.run_for_instructions(max_steps).0.clone(),
+        None => interpreter.run().clone()
+    };
+
+    EntryRun {
+        name,
+        output: interpreter.take_output(),
+        steps: interpreter.steps(),
+        time: start.elapsed(),
+        state
+    }
+}
+
+// Runs the entries one after the other, or all at once on threads of their own if 'parallel'.
+// The runs are in the same order as the entries either way
+pub fn run_entries(entries: Vec<(String, Program)>, input: &[u8], max_steps: Option<u64>, parallel: bool) -> Vec<EntryRun> {
+    if !parallel {
+        return entries.into_iter()
+            .map(|(name, program)| run_entry(name, program, input, max_steps))
+            .collect();
+    }
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = entries.into_iter()
+            .map(|(name, program)| scope.spawn(move || run_entry(name, program, input, max_steps)))
+            .collect();
+        handles.into_iter()
+            .map(|handle| handle.join().expect("An entry panicked"))
+            .collect()
+    })
+}
@@ -0,0 +1,45 @@
+// Example programs that are built into the executable, for "examples". They live in
+// bf_examples, but are included here so they can be listed, extracted and run from anywhere.
+
+pub struct Example {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub source: &'static str
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        name: "hello",
+        description: "hello world, with the string macros from std",
+        source: include_str!("../bf_examples/hello.bf")
+    },
+    Example {
+        name: "cat",
+        description: "prints back what you type, with macros and if/else",
+        source: include_str!("../bf_examples/cat.bf")
+    },
+    Example {
+        name: "rot13",
+        description: "rot13 of what you type, with a plain brainfuck snippet",
+        source: include_str!("../bf_examples/rot13.bf")
+    },
+    Example {
+        name: "calculator",
+        description: "adds or multiplies two digits, with expressions",
+        source: include_str!("../bf_examples/calculator.bf")
+    },
+    Example {
+        name: "life",
+        description: "the game of life with a glider, with repeats",
+        source: include_str!("../bf_examples/life.bf")
+    }
+];
+
+pub fn find_example(name: &str) -> Result<&'static Example, String> {
+    EXAMPLES.iter()
+        .find(|example| example.name == name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = EXAMPLES.iter().map(|example| example.name).collect();
+            format!("There is no example called '{}', the examples are {}", name, names.join(", "))
+        })
+}
@@ -0,0 +1,112 @@
+use crate::parse_bf::{ Loc, Token, TokenType };
+
+// "*deny=std:read,print" and "*allow=std/io:read", guardrails for which namespaces can use which
+// features, ex: so nothing in a shared library of macros reads input or leaves debug dumps around.
+// It's about the code written in the namespace, using a macro from somewhere else that uses the
+// feature is fine. The rule with the longest namespace that the macro is in decides, and
+// everything is allowed if no rule says anything
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feature {
+    // ','
+    Read,
+    // '.'
+    Print,
+    // '!', "!=", "!~", "!#", "!%" and "!?"
+    Debug,
+    // "$p", "$f", "$r" and "$i"
+    Introspection,
+    // Plain brainfuck in '`' blocks
+    Raw,
+    // "?{ }:{ }", "@>N[ ]", "=[ ]" and "%[ ]"
+    Extensions
+}
+
+impl Feature {
+    pub const ALL: [Feature; 6] = [Feature::Read, Feature::Print, Feature::Debug, Feature::Introspection, Feature::Raw, Feature::Extensions];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Feature::Read => "read",
+            Feature::Print => "print",
+            Feature::Debug => "debug",
+            Feature::Introspection => "introspection",
+            Feature::Raw => "raw",
+            Feature::Extensions => "extensions"
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Feature, String> {
+        Feature::ALL.iter().copied().find(|feature| feature.name() == name).ok_or_else(|| {
+            let names: Vec<&str> = Feature::ALL.iter().map(|feature| feature.name()).collect();
+            format!("Unknown feature '{}', the features are {}", name, names.join(", "))
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FeatureRules {
+    // (namespace, feature, whether it's allowed), in the order they were given
+    rules: Vec<(String, Feature, bool)>
+}
+
+impl FeatureRules {
+    // Adds the rules in "namespace:feature,feature", ex: "std:read,print"
+    pub fn add(&mut self, rules: &str, allowed: bool) -> Result<(), String> {
+        let (namespace, features) = rules.split_once(':')
+            .ok_or_else(|| format!("Expected 'namespace:features', like 'std:read,print', got '{}'", rules))?;
+        let namespace = namespace.trim_end_matches('/');
+        if namespace.is_empty() {
+            return Err(format!("Expected a namespace before ':' in '{}'", rules));
+        }
+
+        for feature in features.split(',') {
+            self.rules.push((String::from(namespace), Feature::parse(feature)?, allowed));
+        }
+        Ok(())
+    }
+
+    // The namespace of the rule that keeps 'unit' from using 'feature', if there is one
+    pub fn denied_by(&self, unit: &str, feature: Feature) -> Option<&str> {
+        let in_namespace = |namespace: &str| unit == namespace
+            || (unit.starts_with(namespace) && unit[namespace.len()..].starts_with('/'));
+
+        // Later rules win over earlier ones with the same namespace
+        let (namespace, _, allowed) = self.rules.iter()
+            .filter(|(namespace, rule_feature, _)| *rule_feature == feature && in_namespace(namespace))
+            .max_by_key(|(namespace, _, _)| namespace.len())?;
+        if *allowed { None } else { Some(namespace) }
+    }
+}
+
+// The features some tokens use and where, not counting what the macros they use do.
+// '`' blocks are plain tokens once they're lexed, so the lexer keeps track of those itself
+pub fn used(tokens: &[Token]) -> Vec<(Loc, Feature)> {
+    let mut found = Vec::new();
+    for token in tokens {
+        let feature = match &token.data {
+            TokenType::Read => Feature::Read,
+            TokenType::Print => Feature::Print,
+            TokenType::Debug(_) | TokenType::Assert(_) | TokenType::DebugOp(_, _) => Feature::Debug,
+            TokenType::Introspect(_) => Feature::Introspection,
+            TokenType::Assign(_, _) | TokenType::Table(_, _) => Feature::Extensions,
+            TokenType::Loop(contents) => {
+                found.extend(used(contents));
+                continue;
+            },
+            TokenType::LoopAt(_, contents) => {
+                found.push((token.src_loc, Feature::Extensions));
+                found.extend(used(contents));
+                continue;
+            },
+            TokenType::IfElse(then, other) => {
+                found.push((token.src_loc, Feature::Extensions));
+                found.extend(used(then));
+                found.extend(used(other));
+                continue;
+            },
+            _ => continue
+        };
+        found.push((token.src_loc, feature));
+    }
+    found
+}
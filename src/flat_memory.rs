@@ -0,0 +1,88 @@
+use memmap2::MmapMut;
+use crate::inf_memory::Cells;
+use crate::machine::Fault;
+
+// How many cells the flat tape has, 1 GiB worth of them. The OS only gives the
+// region actual memory once a page is written to, so this doesn't cost 1 GiB
+pub const FLAT_TAPE_CELLS: usize = 1 << 28;
+
+// Cell 0 is in the middle, so the tape can go the same distance in both directions
+const ORIGIN: isize = (FLAT_TAPE_CELLS / 2) as isize;
+
+// How many cells fit in a page of memory
+const PAGE_CELLS: usize = 4096 / std::mem::size_of::<u32>();
+
+// A tape that's one big anonymous memory map, so every access is just an index
+// into it instead of a hash lookup. Anonymous maps start out zeroed.
+pub struct FlatMemory {
+    map: MmapMut,
+    // The lowest and highest cells that were written to
+    written: Option<(isize, isize)>
+}
+
+impl FlatMemory {
+    pub fn new() -> Result<FlatMemory, String> {
+        let map = MmapMut::map_anon(FLAT_TAPE_CELLS * std::mem::size_of::<u32>())
+            .map_err(|error| format!("Couldn't reserve memory for the flat tape, {}", error))?;
+        Ok(FlatMemory { map, written: None })
+    }
+
+    fn cells(&self) -> &[u32] {
+        // Memory maps are page aligned, so the bytes are always aligned for u32s
+        unsafe { std::slice::from_raw_parts(self.map.as_ptr() as *const u32, FLAT_TAPE_CELLS) }
+    }
+
+    fn cells_mut(&mut self) -> &mut [u32] {
+        unsafe { std::slice::from_raw_parts_mut(self.map.as_mut_ptr() as *mut u32, FLAT_TAPE_CELLS) }
+    }
+}
+
+impl Cells for FlatMemory {
+    fn get(&self, loc: isize) -> u32 {
+        self.cells().get((loc + ORIGIN) as usize).cloned().unwrap_or(0)
+    }
+
+    fn set(&mut self, loc: isize, value: u32) -> Result<(), Fault> {
+        if !self.contains(loc) {
+            return Err(Fault::OffTape(loc));
+        }
+
+        self.cells_mut()[(loc + ORIGIN) as usize] = value;
+        self.written = Some(match self.written {
+            Some((low, high)) => (low.min(loc), high.max(loc)),
+            None => (loc, loc)
+        });
+        Ok(())
+    }
+
+    // Every page between the lowest and highest written cell, the pages in
+    // between might not really be used but it's close enough
+    fn pages(&self) -> (usize, usize) {
+        let pages = match self.written {
+            Some((low, high)) => ((high + ORIGIN) as usize / PAGE_CELLS) - ((low + ORIGIN) as usize / PAGE_CELLS) + 1,
+            None => 0
+        };
+        (pages, PAGE_CELLS)
+    }
+
+    fn contains(&self, loc: isize) -> bool {
+        (-ORIGIN..ORIGIN).contains(&loc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writing_off_the_flat_tape_is_an_error() {
+        let mut memory = FlatMemory::new().unwrap();
+        assert_eq!(memory.set(5, 7), Ok(()));
+        assert_eq!(memory.get(5), 7);
+        assert_eq!(memory.set(-ORIGIN, 1), Ok(()));
+
+        assert_eq!(memory.set(ORIGIN, 1), Err(Fault::OffTape(ORIGIN)));
+        assert_eq!(memory.modify(-ORIGIN - 1, |b| b + 1), Err(Fault::OffTape(-ORIGIN - 1)));
+        assert_eq!(memory.get(ORIGIN), 0);
+    }
+}
@@ -0,0 +1,32 @@
+use std::sync::atomic::{ AtomicBool, Ordering };
+
+// Characters that don't do anything, except say something when the lexer sees them.
+// What they say is a note from the compiler, so it's printed after lexing instead of
+// in the middle of it
+pub const HOOKS: &[(char, &str)] = &[
+    ('🧙', "Ayoyoyo Wololo!")
+];
+
+// *serious turns the notes off, the characters are still allowed though
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub fn is_hook(c: char) -> bool {
+    HOOKS.iter().any(|(hook, _)| *hook == c)
+}
+
+// What the character says, if it's a hook and hooks are on
+pub fn message(c: char) -> Option<&'static str> {
+    if !is_enabled() {
+        return None;
+    }
+
+    HOOKS.iter().find(|(hook, _)| *hook == c).map(|(_, message)| *message)
+}
@@ -0,0 +1,154 @@
+// NetPBM images, for "*image=file.ppm". The program prints the whole image, header and all,
+// and it's checked before it's saved, so a broken image shows up as an error right away
+// instead of as a file that nothing can open.
+//  P1 / P4: black and white, as text / packed bits
+//  P2 / P5: grayscale, as text / bytes
+//  P3 / P6: color, as text / bytes
+// The header is the kind, the width, the height and the max value of a sample (except for black and white),
+// separated by whitespace, with '#' comments to the end of the line.
+
+pub struct ImageInfo {
+    pub kind: &'static str,
+    pub width: usize,
+    pub height: usize
+}
+
+const KINDS: [&str; 6] = ["P1", "P2", "P3", "P4", "P5", "P6"];
+
+struct Reader<'a> {
+    data: &'a [u8],
+    index: usize
+}
+
+impl<'a> Reader<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(&byte) = self.data.get(self.index) {
+            if byte == b'#' {
+                while self.data.get(self.index).is_some_and(|&byte| byte != b'\n') {
+                    self.index += 1;
+                }
+            }else if byte.is_ascii_whitespace() {
+                self.index += 1;
+            }else{
+                break;
+            }
+        }
+    }
+
+    fn number(&mut self, what: &str) -> Result<usize, String> {
+        self.skip_whitespace();
+        let start = self.index;
+        while self.data.get(self.index).is_some_and(|byte| byte.is_ascii_digit()) {
+            self.index += 1;
+        }
+
+        std::str::from_utf8(&self.data[start..self.index]).unwrap()
+            .parse()
+            .map_err(|_| format!("expected the {} of the image at byte {}", what, start))
+    }
+}
+
+pub fn check_netpbm(data: &[u8]) -> Result<ImageInfo, String> {
+    let kind = KINDS.iter()
+        .find(|kind| data.starts_with(kind.as_bytes()))
+        .ok_or_else(|| String::from("it doesn't start with a NetPBM header, P1 to P6"))?;
+    let mut reader = Reader { data, index: kind.len() };
+
+    let width = reader.number("width")?;
+    let height = reader.number("height")?;
+    if width == 0 || height == 0 {
+        return Err(format!("it's {}x{}, which is empty", width, height));
+    }
+
+    let bitmap = *kind == "P1" || *kind == "P4";
+    let max_value = if bitmap { 1 } else { reader.number("max value")? };
+    if !(1..=65535).contains(&max_value) {
+        return Err(format!("the max value is {}, it has to be between 1 and 65535", max_value));
+    }
+
+    let channels = if *kind == "P3" || *kind == "P6" { 3 } else { 1 };
+    let samples = width.checked_mul(height).and_then(|pixels| pixels.checked_mul(channels))
+        .ok_or_else(|| format!("it's {}x{}, which is way too big", width, height))?;
+    let raster_len = match *kind {
+        "P1" | "P2" | "P3" => return check_text_raster(&mut reader, kind, samples, max_value)
+            .map(|_| ImageInfo { kind, width, height }),
+        "P4" => width.div_ceil(8) * height,
+        _ => samples * if max_value < 256 { 1 } else { 2 }
+    };
+
+    // Exactly one whitespace character between the header and the raster
+    if !data.get(reader.index).is_some_and(|byte| byte.is_ascii_whitespace()) {
+        return Err(String::from("expected whitespace after the header"));
+    }
+    let raster = &data[reader.index + 1..];
+    if raster.len() < raster_len {
+        return Err(format!("the header says {}x{}, which needs {} bytes of pixels, but there are only {}",
+            width, height, raster_len, raster.len()));
+    }
+    if raster.len() > raster_len {
+        return Err(format!("there are {} extra bytes after the pixels", raster.len() - raster_len));
+    }
+
+    Ok(ImageInfo { kind, width, height })
+}
+
+fn check_text_raster(reader: &mut Reader, kind: &str, samples: usize, max_value: usize) -> Result<(), String> {
+    for i in 0..samples {
+        reader.skip_whitespace();
+        let value = if kind == "P1" {
+            // Black and white pixels don't need anything between them
+            match reader.data.get(reader.index) {
+                Some(digit @ (b'0' | b'1')) => {
+                    reader.index += 1;
+                    (digit - b'0') as usize
+                },
+                _ => return Err(format!("expected {} pixels, but pixel {} isn't a 0 or 1", samples, i))
+            }
+        }else{
+            reader.number("pixels")
+                .map_err(|_| format!("expected {} samples, but sample {} isn't a number", samples, i))?
+        };
+
+        if value > max_value {
+            return Err(format!("sample {} is {}, more than the max value of {}", i, value, max_value));
+        }
+    }
+
+    reader.skip_whitespace();
+    if reader.index < reader.data.len() {
+        return Err(format!("there are {} extra bytes after the pixels", reader.data.len() - reader.index));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(data: &[u8]) -> String {
+        check_netpbm(data).err().expect("The image should have been rejected")
+    }
+
+    #[test]
+    fn images_are_checked_before_they_are_saved() {
+        let mut color = b"P6\n# a comment\n2 1 255\n".to_vec();
+        color.extend_from_slice(&[255, 0, 0, 0, 0, 255]);
+        let info = check_netpbm(&color).unwrap_or_else(|msg| panic!("{}", msg));
+        assert_eq!((info.kind, info.width, info.height), ("P6", 2, 1));
+        assert!(check_netpbm(b"P1 3 2 101\n010").is_ok());
+        assert!(check_netpbm(b"P2 2 2 15 0 15\n7 3\n").is_ok());
+        assert!(check_netpbm(b"P4 9 1\n\xFF\x80").is_ok());
+
+        assert_eq!(error(b"P7 1 1 255\n\0"), "it doesn't start with a NetPBM header, P1 to P6");
+        assert_eq!(error(b"P5 0 4 255\n"), "it's 0x4, which is empty");
+        assert_eq!(error(b"P5 1 1 70000\n\0"), "the max value is 70000, it has to be between 1 and 65535");
+        assert_eq!(error(b"P2 2 1 9 3 10"), "sample 1 is 10, more than the max value of 9");
+
+        // The pixels are cut off, or there's more of them than the header says
+        assert_eq!(error(&color[..color.len() - 1]), "the header says 2x1, which needs 6 bytes of pixels, but there are only 5");
+        assert_eq!(error(b"P5 2 2 255\n\0\0\0\0\0"), "there are 1 extra bytes after the pixels");
+        assert_eq!(error(b"P1 2 2 101"), "expected 4 pixels, but pixel 3 isn't a 0 or 1");
+        assert_eq!(error(b"P5 2 2 255"), "expected whitespace after the header");
+    }
+}
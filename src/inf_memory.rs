@@ -1,42 +1,133 @@
 use std::collections::HashMap;
 
+use crate::machine::Fault;
+
 const MEM_BUF_SIZE_BYTES: usize = 12;
 const MEM_FLAGGER: usize = MEM_BUF_SIZE - 1;
-const MEM_BUF_SIZE: usize = 1 << MEM_BUF_SIZE_BYTES;
+pub const MEM_BUF_SIZE: usize = 1 << MEM_BUF_SIZE_BYTES;
+// The tape is split into pages that are made when a cell in them is first written to. Cells are
+// stored in a page with as many bytes as they need, so 8 bit programs don't pay for 32 bit cells
 pub struct Memory {
-    memory: HashMap<usize, [u8; MEM_BUF_SIZE]>
+    memory: HashMap<usize, Vec<u8>>,
+    // How many bytes a cell takes up in a page
+    cell_bytes: usize
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Memory {
+    // A tape that can hold cells of any size
     pub fn new() -> Memory {
+        Memory::for_cells(32)
+    }
+
+    // A tape for cells of 'cell_bits' bits, like the cells pragma says
+    pub fn for_cells(cell_bits: u8) -> Memory {
         Memory {
-            memory: HashMap::new()
+            memory: HashMap::new(),
+            cell_bytes: (cell_bits as usize).div_ceil(8)
         }
     }
 
-    pub fn set(&mut self, loc: isize, value: u8) {
+    pub fn set(&mut self, loc: isize, value: u32) {
         let loc = (loc & 0x7F_FF_FF_FF) as usize;
-        let buf_loc = loc >> MEM_BUF_SIZE_BYTES;
-        if let Some(memory) = self.memory.get_mut(&buf_loc) {
-            memory[loc & MEM_FLAGGER] = value;
-        }else{
-            let mut new_buf = [0; MEM_BUF_SIZE];
-            new_buf[loc & MEM_FLAGGER] = value;
-            self.memory.insert(buf_loc, new_buf);
-        }
+        let cell_bytes = self.cell_bytes;
+        let page = self.memory.entry(loc >> MEM_BUF_SIZE_BYTES).or_insert_with(|| vec![0; MEM_BUF_SIZE * cell_bytes]);
+        write_cell(page, loc & MEM_FLAGGER, cell_bytes, value);
     }
 
-    pub fn get(&self, loc: isize) -> u8 {
+    pub fn get(&self, loc: isize) -> u32 {
         let loc = (loc & 0x7F_FF_FF_FF) as usize;
-        if let Some(memory) = self.memory.get(&(loc >> MEM_BUF_SIZE_BYTES)) {
-            memory[loc & MEM_FLAGGER]
+        if let Some(page) = self.memory.get(&(loc >> MEM_BUF_SIZE_BYTES)) {
+            read_cell(page, loc & MEM_FLAGGER, self.cell_bytes)
         }else{
             0x00
         }
     }
 
     pub fn modify<F>(&mut self, loc: isize, func: F)
-            where F: FnOnce(u8) -> u8 {
+            where F: FnOnce(u32) -> u32 {
         self.set(loc, func(self.get(loc)));
     }
 }
+
+// Cells are stored little endian, with the bytes above the cell size left out
+fn write_cell(page: &mut [u8], index: usize, cell_bytes: usize, value: u32) {
+    let start = index * cell_bytes;
+    page[start..start + cell_bytes].copy_from_slice(&value.to_le_bytes()[..cell_bytes]);
+}
+
+fn read_cell(page: &[u8], index: usize, cell_bytes: usize) -> u32 {
+    let start = index * cell_bytes;
+    let mut bytes = [0; 4];
+    bytes[..cell_bytes].copy_from_slice(&page[start..start + cell_bytes]);
+    u32::from_le_bytes(bytes)
+}
+
+// What the interpreter needs from a tape, so it can run on different backends
+pub trait Cells {
+    fn get(&self, loc: isize) -> u32;
+    // Fails if the tape doesn't have the cell, instead of losing what was written
+    fn set(&mut self, loc: isize, value: u32) -> Result<(), Fault>;
+
+    fn modify<F>(&mut self, loc: isize, func: F) -> Result<(), Fault>
+            where F: FnOnce(u32) -> u32 {
+        self.set(loc, func(self.get(loc)))
+    }
+
+    // How many pages of memory the tape has handed out, and how many cells go in a page
+    fn pages(&self) -> (usize, usize);
+
+    // Whether the tape has the cell at all, running off the end of the tape is an error
+    fn contains(&self, _loc: isize) -> bool {
+        true
+    }
+}
+
+impl Cells for Memory {
+    fn get(&self, loc: isize) -> u32 {
+        Memory::get(self, loc)
+    }
+
+    fn set(&mut self, loc: isize, value: u32) -> Result<(), Fault> {
+        Memory::set(self, loc, value);
+        Ok(())
+    }
+
+    fn pages(&self) -> (usize, usize) {
+        (self.memory.len(), MEM_BUF_SIZE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_are_stored_at_their_own_width() {
+        for (cell_bits, value) in [(8, 0xAB), (16, 0xABCD), (32, 0xDEAD_BEEF)] {
+            let cell_bytes = cell_bits / 8;
+            let mut page = vec![0; 4 * cell_bytes];
+            write_cell(&mut page, 1, cell_bytes, value);
+            write_cell(&mut page, 2, cell_bytes, 1);
+            assert_eq!(read_cell(&page, 0, cell_bytes), 0);
+            assert_eq!(read_cell(&page, 1, cell_bytes), value);
+            assert_eq!(read_cell(&page, 2, cell_bytes), 1);
+        }
+
+        let mut memory = Memory::for_cells(8);
+        memory.set(-1, 0xFF);
+        memory.set(MEM_BUF_SIZE as isize, 7);
+        assert_eq!((memory.get(-1), memory.get(MEM_BUF_SIZE as isize), memory.get(3)), (0xFF, 7, 0));
+        assert!(memory.memory.values().all(|page| page.len() == MEM_BUF_SIZE));
+
+        let mut memory = Memory::for_cells(16);
+        memory.set(5, 0xFFFF);
+        assert_eq!(memory.get(5), 0xFFFF);
+        assert!(memory.memory.values().all(|page| page.len() == MEM_BUF_SIZE * 2));
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+// A cheap handle for a macro path like "src/foo/bar". The lexer and the
+// compiler pass these around instead of the strings, so a path is only
+// allocated the first time it's seen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Symbol(u32);
+
+pub struct Interner {
+    ids: HashMap<String, Symbol>,
+    names: Vec<String>
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner {
+            ids: HashMap::new(),
+            names: Vec::new()
+        }
+    }
+
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.ids.get(name) {
+            return *symbol;
+        }
+
+        // Every name is a string of its own, so memory runs out long before the symbols do
+        assert!(self.names.len() <= u32::MAX as usize, "Too many macros, ran out of symbols");
+        let symbol = Symbol(self.names.len() as u32);
+        self.names.push(String::from(name));
+        self.ids.insert(String::from(name), symbol);
+        symbol
+    }
+
+    // Finds the symbol of a name without creating one
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.ids.get(name).cloned()
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.names[symbol.0 as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn there_are_more_symbols_than_fit_in_16_bits() {
+        let mut interner = Interner::new();
+        let names: Vec<String> = (0..70_000).map(|i| format!("src/m{}", i)).collect();
+        let symbols: Vec<Symbol> = names.iter().map(|name| interner.intern(name)).collect();
+        for (name, symbol) in names.iter().zip(symbols) {
+            assert_eq!(interner.resolve(symbol), name);
+            assert_eq!(interner.intern(name), symbol);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::VecDeque;
+
+use crate::Memory;
+use crate::binary::Program;
+use crate::machine::{ Machine, Limits, Flow, Io };
+use crate::introspect::Random;
+
+// What an Interpreter is doing when it hands back control
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum State {
+    // It can keep going
+    Running,
+    // The program wants to READ but there's no input left, it keeps going once it gets some with push_input
+    NeedsInput,
+    Finished,
+    // The program stopped with an error at the instruction at the offset, the message
+    // says what it did, ex: "ran more than 10 instructions"
+    Failed(usize, String)
+}
+
+// An interpreter that can stop and be picked up again later, for running programs inside of
+// other programs. Unlike execute_bf it never touches stdin or stdout, the host hands it input
+// whenever it has some and takes the output whenever it wants. Debug instructions are skipped.
+// The limits from the program's pragmas are enforced, set_limits replaces them.
+pub struct Interpreter {
+    program: Program,
+    memory: Memory,
+    machine: Machine,
+    buffers: Buffers,
+    state: State
+}
+
+struct Buffers {
+    input: VecDeque<u8>,
+    // What the program printed that hasn't been taken yet
+    output: Vec<u8>
+}
+
+impl Io for Buffers {
+    fn read(&mut self, _: &Machine) -> Result<Option<u8>, String> {
+        Ok(self.input.pop_front())
+    }
+
+    fn print(&mut self, _: &Machine, byte: u8) -> Result<(), String> {
+        self.output.push(byte);
+        Ok(())
+    }
+
+    fn has_input(&self) -> bool {
+        !self.input.is_empty()
+    }
+}
+
+impl Interpreter {
+    pub fn new(program: Program) -> Interpreter {
+        Interpreter {
+            memory: Memory::for_cells(program.pragmas.cell_bits),
+            machine: Machine::new(Limits::from_pragmas(&program.pragmas), Random::from_clock()),
+            program,
+            buffers: Buffers { input: VecDeque::new(), output: Vec::new() },
+            state: State::Running
+        }
+    }
+
+    // Stops the program with an error once it goes over one of the limits, instead of the
+    // limits from the program's pragmas. Limits::default() doesn't limit anything
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.machine.set_limits(limits);
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    // How many instructions have run so far
+    pub fn steps(&self) -> u64 {
+        self.machine.steps()
+    }
+
+    pub fn mem_ptr(&self) -> isize {
+        self.machine.mem_ptr()
+    }
+
+    pub fn cell(&self, index: isize) -> u32 {
+        self.memory.get(index)
+    }
+
+    // Swaps in another program that starts from its beginning, on the same tape, with the
+    // same input, so a program can be poked at with small snippets of code
+    pub fn load(&mut self, program: Program) {
+        self.program = program;
+        self.machine.restart();
+        self.state = State::Running;
+    }
+
+    // Adds input for the program to READ. If it was waiting for input, it can keep going
+    pub fn push_input(&mut self, bytes: &[u8]) {
+        self.buffers.input.extend(bytes);
+        if self.state == State::NeedsInput && self.buffers.has_input() {
+            self.state = State::Running;
+        }
+    }
+
+    // Everything the program printed since the last time the output was taken
+    pub fn take_output(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.buffers.output)
+    }
+
+    // Runs until the program finishes, fails, or needs more input
+    pub fn run(&mut self) -> &State {
+        while self.state == State::Running {
+            self.step();
+        }
+
+        &self.state
+    }
+
+    // Like run, but hands back control after at most 'fuel' instructions, along with how much
+    // fuel is left. The state is still Running if it ran out, so a host can take turns
+    // running many programs on one thread
+    pub fn run_for_instructions(&mut self, fuel: u64) -> (&State, u64) {
+        let mut fuel = fuel;
+        while self.state == State::Running && fuel > 0 {
+            let steps = self.steps();
+            self.step();
+            fuel -= self.steps() - steps;
+        }
+
+        (&self.state, fuel)
+    }
+
+    // Runs one instruction
+    fn step(&mut self) {
+        self.state = match self.machine.step(&self.program, &mut self.memory, &mut self.buffers) {
+            Ok(Flow::Ran) => State::Running,
+            // The READ runs again once there's input
+            Ok(Flow::NeedsInput) => State::NeedsInput,
+            Ok(Flow::Finished) => State::Finished,
+            Err(fault) => State::Failed(self.machine.instr_ptr(), fault.describe())
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::Limits;
+    use crate::compile_test_program;
+
+    #[test]
+    fn waits_for_input_and_picks_up_where_it_left_off() {
+        let mut interpreter = Interpreter::new(compile_test_program(",+.,+."));
+        assert_eq!(interpreter.run(), &State::NeedsInput);
+        assert_eq!(interpreter.steps(), 0);
+
+        interpreter.push_input(b"a");
+        assert_eq!(interpreter.run(), &State::NeedsInput);
+        assert_eq!(interpreter.take_output(), b"b");
+
+        interpreter.push_input(b"x");
+        assert_eq!(interpreter.run(), &State::Finished);
+        assert_eq!(interpreter.take_output(), b"y");
+        assert_eq!(interpreter.steps(), 6);
+    }
+
+    #[test]
+    fn running_on_fuel_hands_back_control() {
+        let mut interpreter = Interpreter::new(compile_test_program("+5[->+<]>."));
+        let mut turns = 0;
+        loop {
+            let (state, fuel) = interpreter.run_for_instructions(4);
+            match state {
+                State::Running => assert_eq!(fuel, 0),
+                State::Finished => break,
+                other => panic!("Didn't expect {:?}", other)
+            }
+            turns += 1;
+        }
+
+        assert!(turns > 1);
+        assert_eq!(interpreter.take_output(), vec![5]);
+    }
+
+    #[test]
+    fn limits_stop_the_program() {
+        let mut interpreter = Interpreter::new(compile_test_program("+[]"));
+        interpreter.set_limits(Limits { max_steps: Some(100), ..Limits::default() });
+        match interpreter.run() {
+            State::Failed(_, msg) => assert_eq!(msg, "ran more than 100 instructions"),
+            other => panic!("Didn't expect {:?}", other)
+        }
+        assert_eq!(interpreter.steps(), 100);
+
+        let mut interpreter = Interpreter::new(compile_test_program("+[>+]"));
+        interpreter.set_limits(Limits { max_memory: Some(8), ..Limits::default() });
+        match interpreter.run() {
+            State::Failed(_, msg) => assert_eq!(msg, "used more than 8 cells"),
+            other => panic!("Didn't expect {:?}", other)
+        }
+        assert_eq!(interpreter.mem_ptr(), 8);
+    }
+
+    #[test]
+    fn trap_drift_stops_the_interpreter_too() {
+        let mut interpreter = Interpreter::new(compile_test_program("<+[<+]"));
+        interpreter.set_limits(Limits { trap_drift: Some(10), ..Limits::default() });
+        match interpreter.run() {
+            State::Failed(_, msg) => assert!(msg.starts_with("drifted to cell -11"), "{}", msg),
+            other => panic!("Didn't expect {:?}", other)
+        }
+        assert_eq!(interpreter.mem_ptr(), -11);
+    }
+
+    #[test]
+    fn limits_from_pragmas_stop_the_interpreter() {
+        let text = "#pragma max_steps 20\n+50[-].";
+        let mut interpreter = Interpreter::new(compile_test_program(text));
+        match interpreter.run() {
+            State::Failed(_, msg) => assert_eq!(msg, "ran more than 20 instructions"),
+            other => panic!("Didn't expect {:?}", other)
+        }
+
+        let mut interpreter = Interpreter::new(compile_test_program("#pragma max_memory 5\n+[>+]"));
+        match interpreter.run() {
+            State::Failed(_, msg) => assert_eq!(msg, "used more than 5 cells"),
+            other => panic!("Didn't expect {:?}", other)
+        }
+
+        let mut interpreter = Interpreter::new(compile_test_program(text));
+        interpreter.set_limits(Limits::default());
+        assert_eq!(interpreter.run(), &State::Finished);
+        assert_eq!(interpreter.take_output(), vec![0]);
+    }
+}
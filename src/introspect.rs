@@ -0,0 +1,80 @@
+// The facts about the interpreter that "$p", "$f", "$r" and "$i" write into the current cell,
+// for programs that want to know how they're being run. They only compile with "#pragma introspection"
+use std::time::{ SystemTime, UNIX_EPOCH };
+use crate::instructions::*;
+
+// The random bytes of "$r", a xorshift generator, nothing fancy
+pub struct Random(u64);
+
+impl Random {
+    // Always the same bytes for the same seed, so runs that are compared get the same ones
+    pub fn seeded(seed: u64) -> Random {
+        // xorshift gets stuck on zero
+        Random(seed | 1)
+    }
+
+    // Different bytes every run
+    pub fn from_clock() -> Random {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |time| time.as_nanos() as u64);
+        Random::seeded(nanos)
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 56) as u8
+    }
+}
+
+// The value of the FACT_* 'kind' as a cell. 'fuel' is how many instructions the program can still
+// run, None if there's no limit, which is as much as fits in a cell. 'input_available' is whether
+// a READ would get something right away, without asking for it
+pub fn fact(kind: u8, mem_ptr: isize, fuel: Option<u64>, input_available: bool, random: &mut Random, cell_mask: u32) -> u32 {
+    match kind {
+        FACT_POINTER => mem_ptr as u8 as u32,
+        FACT_FUEL => fuel.map_or(cell_mask, |fuel| fuel.min(cell_mask as u64) as u32),
+        FACT_RANDOM => random.next_byte() as u32,
+        FACT_INPUT => input_available as u32,
+        _ => panic!("Invalid introspection fact!")
+    }
+}
+
+// The character that goes after '$' in the source for the fact
+pub fn fact_symbol(kind: u8) -> char {
+    match kind {
+        FACT_POINTER => 'p',
+        FACT_FUEL => 'f',
+        FACT_RANDOM => 'r',
+        _ => 'i'
+    }
+}
+
+pub fn fact_name(kind: u8) -> &'static str {
+    match kind {
+        FACT_POINTER => "pointer",
+        FACT_FUEL => "fuel",
+        FACT_RANDOM => "random",
+        FACT_INPUT => "input",
+        _ => "??"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ CompileOptions, compile_program, compile_test_program };
+    use crate::interpreter::{ Interpreter, State };
+
+    #[test]
+    fn introspection_needs_its_pragma() {
+        let program = compile_test_program("#pragma introspection\n>3 $p . $i . <3 , $i . $f .");
+        assert!(crate::binary::validate_code(&program.code).is_ok());
+
+        let mut interpreter = Interpreter::new(program);
+        interpreter.push_input(b"a");
+        assert_eq!(interpreter.run(), &State::Finished);
+        assert_eq!(interpreter.take_output(), vec![3, 1, 0, 255]);
+
+        assert!(compile_program(">3 $p".chars().collect(), None, CompileOptions::default()).is_err());
+    }
+}
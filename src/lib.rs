@@ -0,0 +1,661 @@
+extern crate ansi_term;
+
+pub mod run;
+pub use run::{ execute_bf, execute_raw, execute_to_bytes };
+
+pub mod inf_memory;
+pub use inf_memory::Memory;
+
+#[cfg(feature = "flat_tape")]
+pub mod flat_memory;
+
+pub mod parse_bf;
+pub use parse_bf::{ Lexer };
+
+pub mod compiler;
+pub use compiler::{ Compiler };
+
+pub mod interner;
+
+pub mod source;
+
+pub mod binary;
+use binary::Program;
+
+pub mod pragma;
+use pragma::Pragmas;
+
+pub mod bench;
+
+pub mod optimize;
+
+pub mod trace;
+
+pub mod trace_diff;
+
+pub mod color;
+
+pub mod fun;
+
+pub mod emit;
+
+pub mod bundle;
+
+pub mod compile_db;
+
+pub mod symbols;
+
+pub mod verify;
+
+pub mod report;
+
+pub mod testing;
+
+pub mod image;
+
+pub mod snapshot;
+
+pub mod machine;
+use machine::Limits;
+
+pub mod interpreter;
+pub use interpreter::Interpreter;
+
+pub mod examples;
+
+pub mod diagnostics;
+
+pub mod metrics;
+
+pub mod entries;
+
+pub mod advise;
+
+pub mod rename;
+
+pub mod obfuscate;
+
+pub mod pretty;
+
+pub mod specialize;
+
+pub mod introspect;
+
+pub mod script;
+
+pub mod self_profile;
+
+pub mod features;
+
+pub mod instructions {
+    pub const SHIFT_RIGHT: u8 = 0x00;
+    pub const SHIFT_LEFT: u8 = 0x01;
+    pub const INCREMENT: u8 = 0x02;
+    pub const DECREMENT: u8 = 0x03;
+    pub const LOOP_OPEN: u8 = 0x04;
+    pub const LOOP_CLOSE: u8 = 0x05;
+    pub const PRINT: u8 = 0x06;
+    pub const READ: u8 = 0x07;
+    pub const DEBUG: u8 = 0x08;
+    // Followed by the expected value, and the line and column of the assertion in the source,
+    // all as u32s
+    pub const ASSERT: u8 = 0x09;
+    // Followed by what to do(one of the DEBUG_* kinds below) as a u8 and an argument as a u32
+    pub const DEBUG_OP: u8 = 0x0A;
+    // Followed by the offset to jump to as a u32, the offset after the CALL is pushed
+    // on the call stack
+    pub const CALL: u8 = 0x0B;
+    // Jumps back to the offset on the top of the call stack
+    pub const RET: u8 = 0x0C;
+    // LOOP_OPEN and LOOP_CLOSE with the offset as a u64, for loops with bodies
+    // too big for a u32 offset
+    pub const LOOP_OPEN_WIDE: u8 = 0x0D;
+    pub const LOOP_CLOSE_WIDE: u8 = 0x0E;
+    // Followed by which fact to write into the current cell(one of the FACT_* kinds below) as a u8,
+    // see introspect
+    pub const INTROSPECT: u8 = 0x0F;
+
+    // "!~N", prints the N cells on both sides of the current cell
+    pub const DEBUG_WINDOW: u8 = 0x00;
+    // "!#N", dumps N cells starting at the current cell to the dump file
+    pub const DEBUG_DUMP: u8 = 0x01;
+    // "!%N", a profiling marker, the run ends with a summary of the markers
+    pub const DEBUG_MARKER: u8 = 0x02;
+    // "!?", waits until enter is pressed
+    pub const DEBUG_PAUSE: u8 = 0x03;
+
+    // "$p", the lowest byte of the index of the current cell
+    pub const FACT_POINTER: u8 = 0x00;
+    // "$f", how many more instructions the program can run before it's stopped
+    pub const FACT_FUEL: u8 = 0x01;
+    // "$r", a random byte
+    pub const FACT_RANDOM: u8 = 0x02;
+    // "$i", 1 if there's input that can be read without asking for it, 0 otherwise
+    pub const FACT_INPUT: u8 = 0x03;
+
+    // How many bytes an instruction takes up, including its operands
+    pub fn len(instruction: u8) -> usize {
+        match instruction {
+            LOOP_OPEN | LOOP_CLOSE | CALL => 5,
+            LOOP_OPEN_WIDE | LOOP_CLOSE_WIDE => 9,
+            ASSERT => 13,
+            DEBUG_OP => 6,
+            INTROSPECT => 2,
+            _ => 1
+        }
+    }
+
+    pub fn instruction_name(instruction: u8) -> &'static str {
+        match instruction {
+            SHIFT_RIGHT => "SHIFT_RIGHT",
+            SHIFT_LEFT => "SHIFT_LEFT",
+            INCREMENT => "INCREMENT",
+            DECREMENT => "DECREMENT",
+            LOOP_OPEN => "LOOP_OPEN",
+            LOOP_CLOSE => "LOOP_CLOSE",
+            PRINT => "PRINT",
+            READ => "READ",
+            DEBUG => "DEBUG",
+            ASSERT => "ASSERT",
+            DEBUG_OP => "DEBUG_OP",
+            CALL => "CALL",
+            RET => "RET",
+            LOOP_OPEN_WIDE => "LOOP_OPEN_WIDE",
+            LOOP_CLOSE_WIDE => "LOOP_CLOSE_WIDE",
+            INTROSPECT => "INTROSPECT",
+            _ => "??"
+        }
+    }
+
+    // Reads the u32 operand that starts at 'index'
+    pub fn read_u32(code: &[u8], index: usize) -> u32 {
+        u32::from_le_bytes([code[index], code[index + 1], code[index + 2], code[index + 3]])
+    }
+
+    // Reads the u64 operand that starts at 'index'
+    pub fn read_u64(code: &[u8], index: usize) -> u64 {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&code[index..index + 8]);
+        u64::from_le_bytes(bytes)
+    }
+
+    pub fn is_loop_open(instruction: u8) -> bool {
+        instruction == LOOP_OPEN || instruction == LOOP_OPEN_WIDE
+    }
+
+    pub fn is_loop_close(instruction: u8) -> bool {
+        instruction == LOOP_CLOSE || instruction == LOOP_CLOSE_WIDE
+    }
+
+    // How far the loop instruction at 'index' jumps, the same for narrow and wide loops
+    pub fn loop_offset(code: &[u8], index: usize) -> usize {
+        match code[index] {
+            LOOP_OPEN_WIDE | LOOP_CLOSE_WIDE => read_u64(code, index + 1) as usize,
+            _ => read_u32(code, index + 1) as usize
+        }
+    }
+}
+
+pub type CompileResult<T> = Result<T, Vec<Error>>;
+
+pub struct Error {
+    pub loc: parse_bf::Loc,
+    pub msg: String
+}
+
+impl Error {
+    pub fn new(loc: parse_bf::Loc, msg: String) -> Error {
+        Error {
+            loc,
+            msg
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ERROR {}: '{}'", self.loc, self.msg)
+    }
+}
+
+// Where the input of a program comes from. READ drains the sources in the order
+// they were given, and asks on stdin once all of them are empty
+pub enum InputSource {
+    Text(String),
+    File(String)
+}
+
+// How line endings are translated between the program and the outside, "*newline=lf|crlf|raw"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Newline {
+    // Lines typed in the terminal lose their line ending, nothing else changes
+    Trim,
+    // Input lines end with "\n", "\r\n" from windows consoles and files becomes "\n"
+    Lf,
+    // Input lines end with "\r\n", and every "\n" the program prints is printed as "\r\n"
+    Crlf,
+    // Everything goes through exactly as it is, line endings included
+    Raw
+}
+
+impl Newline {
+    pub fn parse(name: &str) -> Result<Newline, String> {
+        match name {
+            "lf" => Ok(Newline::Lf),
+            "crlf" => Ok(Newline::Crlf),
+            "raw" => Ok(Newline::Raw),
+            _ => Err(format!("'{}' isn't a kind of newline, expected lf, crlf or raw", name))
+        }
+    }
+
+    // Input on its way to the program, a line from the terminal or the contents of *in and *in_file
+    pub fn translate_input(self, data: &[u8]) -> Vec<u8> {
+        let mut translated = Vec::with_capacity(data.len());
+        for (i, &byte) in data.iter().enumerate() {
+            let after_cr = i > 0 && data[i - 1] == b'\r';
+            match (self, byte) {
+                (Newline::Lf, b'\n') if after_cr => {
+                    translated.pop();
+                    translated.push(b'\n');
+                },
+                (Newline::Crlf, b'\n') if !after_cr => translated.extend_from_slice(b"\r\n"),
+                _ => translated.push(byte)
+            }
+        }
+
+        translated
+    }
+
+    // A line typed in the terminal, on its way to the program
+    pub fn translate_line(self, line: &str) -> Vec<u8> {
+        match self {
+            Newline::Trim => line.trim_end().bytes().collect(),
+            _ => self.translate_input(line.as_bytes())
+        }
+    }
+}
+
+pub struct Modifiers {
+    pub is_debug: bool,
+    pub inputs: Vec<InputSource>,
+    pub print_bin: bool,
+    pub save_bin: Option<String>,
+    // 0 doesn't optimize, *opt is 1, and *opt=2 and up unroll loops too
+    pub opt_level: u8,
+    // The optimizer passes to run, all of them if it's empty
+    pub passes: Vec<String>,
+    pub verify_opt: bool,
+    // Runs the program with its input when it's built, and only keeps what it printed, see specialize
+    pub specialize: bool,
+    // Where to save a trace of the run, for trace-diff
+    pub save_trace: Option<String>,
+    // Where "!#N" dumps go, they're printed if there's no file
+    pub dump_file: Option<String>,
+    // Where to write a snapshot of the run at every '!'
+    pub snapshot_dir: Option<String>,
+    // Runs on one big memory mapped tape instead of the hash map, needs the flat_tape feature
+    pub flat_tape: bool,
+    // The files to write next to the program
+    pub emit: Vec<emit::EmitKind>,
+    // Another interpreter the plain brainfuck is meant for, it's made for it and checked against what the program needs
+    pub target: Option<emit::Target>,
+    // What to print when the program wants more input, None to not print anything
+    pub prompt: Option<String>,
+    // Prints every character the program reads, dimmed
+    pub echo_input: bool,
+    // Where to log everything the program prints and everything typed in
+    pub transcript: Option<String>,
+    // Writes what the program prints to this file instead of the terminal, byte for byte
+    pub out: Option<String>,
+    // Escapes control characters in what the program prints to the terminal, *out files still get the raw bytes
+    pub sanitize_output: bool,
+    // Checks that what the program prints is a NetPBM image, and saves it to this file
+    pub image: Option<String>,
+    // Where to write a json report of the run
+    pub report: Option<String>,
+    // Prints how long the phases of the compiler took and how much memory they used, see self_profile
+    pub self_profile: bool,
+    // Prints how many times the loops ran when the program is done
+    pub profile: bool,
+    // Limits that replace the ones from the pragmas
+    pub max_steps: Option<u64>,
+    pub max_memory: Option<u64>,
+    // Stops the program if the memory pointer gets more than this many cells away from where it started,
+    // which is usually a missing '<' or '>' rather than a program that needs that much tape
+    pub trap_drift: Option<u64>,
+    // Ignores all the limits, even the ones from the pragmas
+    pub no_limits: bool,
+    // For running binaries from people you don't know. Limits the run even if the binary doesn't,
+    // never asks for input in the terminal, escapes control characters in the output, and skips
+    // the debug instructions, so the program can't pause or write files
+    pub sandbox: bool,
+    // Turns off the easter eggs
+    pub serious: bool,
+    // Prints the errors and warnings as json, see diagnostics
+    pub json_messages: bool,
+    // The macros to run as programs of their own instead of the whole program, see entries
+    pub entries: Vec<String>,
+    // Runs the entries on threads of their own
+    pub parallel: bool,
+    // A library binary to run the program on top of, see Program::on_library
+    pub library: Option<String>,
+    pub newline: Newline,
+    pub compile_options: CompileOptions
+}
+
+// The limits of *sandbox, a binary's pragmas can make them lower but not higher
+pub const SANDBOX_MAX_STEPS: u64 = 100_000_000;
+pub const SANDBOX_MAX_MEMORY: u64 = 1 << 20;
+
+pub const DEFAULT_PROMPT: &str = "The program requests some more characters to process: ";
+
+impl Default for Modifiers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Modifiers {
+    pub fn new() -> Modifiers {
+        Modifiers {
+            is_debug: false,
+            save_bin: None,
+            print_bin: false,
+            inputs: Vec::new(),
+            opt_level: 0,
+            passes: Vec::new(),
+            verify_opt: false,
+            specialize: false,
+            save_trace: None,
+            dump_file: None,
+            snapshot_dir: None,
+            flat_tape: false,
+            emit: Vec::new(),
+            target: None,
+            prompt: Some(String::from(DEFAULT_PROMPT)),
+            echo_input: false,
+            transcript: None,
+            out: None,
+            image: None,
+            sanitize_output: false,
+            report: None,
+            self_profile: false,
+            profile: false,
+            max_steps: None,
+            max_memory: None,
+            trap_drift: None,
+            no_limits: false,
+            sandbox: false,
+            serious: false,
+            json_messages: false,
+            entries: Vec::new(),
+            parallel: false,
+            library: None,
+            newline: Newline::Trim,
+            compile_options: CompileOptions::default()
+        }
+    }
+
+    // The limits to run a program with, from the modifiers or the pragmas
+    pub fn limits(&self, pragmas: &Pragmas) -> Limits {
+        let trap_drift = self.trap_drift;
+        if self.no_limits {
+            return Limits { trap_drift, ..Limits::default() };
+        }
+        if self.sandbox {
+            return Limits {
+                max_steps: Some(self.max_steps.unwrap_or_else(|| pragmas.max_steps.map_or(SANDBOX_MAX_STEPS, |steps| steps.min(SANDBOX_MAX_STEPS)))),
+                max_memory: Some(self.max_memory.unwrap_or_else(|| pragmas.max_memory.map_or(SANDBOX_MAX_MEMORY, |cells| cells.min(SANDBOX_MAX_MEMORY)))),
+                trap_drift
+            };
+        }
+
+        Limits { max_steps: self.max_steps.or(pragmas.max_steps), max_memory: self.max_memory.or(pragmas.max_memory), trap_drift }
+    }
+
+    // The input of a traced run, it asks for more in the terminal unless it's sandboxed
+    pub fn trace_input(&self, std_in: &[u8]) -> trace::Input {
+        trace::Input { data: self.newline.translate_input(std_in), can_ask: !self.sandbox, newline: self.newline }
+    }
+
+    // The optimizer passes that *passes picked
+    pub fn pipeline(&self) -> Result<optimize::Pipeline, String> {
+        let mut pipeline = optimize::Pipeline::new();
+        if !self.passes.is_empty() {
+            let names: Vec<&str> = self.passes.iter().map(|name| &name[..]).collect();
+            pipeline.select(&names)?;
+        }
+        Ok(pipeline)
+    }
+
+    // The input given up front, every source one after the other
+    pub fn read_inputs(&self) -> Result<Vec<u8>, String> {
+        let mut data = Vec::new();
+        for source in &self.inputs {
+            match source {
+                InputSource::Text(text) => data.extend_from_slice(text.as_bytes()),
+                InputSource::File(path) => {
+                    let contents = std::fs::read(path)
+                        .map_err(|error| format!("Couldn't read the input file '{}', {}", path, error))?;
+                    data.extend(contents);
+                }
+            }
+        }
+
+        Ok(data)
+    }
+}
+
+
+// Why compiling a program failed, the caller decides how to report it
+pub enum CompileFailure {
+    Source(Vec<Error>),
+    // The errors in the standard library, and the ones in the program, which is lexed anyway
+    // so that everything that's wrong is found at once
+    Std(Vec<Error>, Vec<Error>),
+    // Some macros are used but never defined, or depend on each other in a cycle
+    Unresolved(Box<Compiler>),
+    Invalid(String)
+}
+
+impl CompileFailure {
+    // A short description of the failure, for when it's not worth printing it nicely
+    pub fn describe(&self) -> String {
+        match self {
+            CompileFailure::Source(errors) => {
+                let messages: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                messages.join("\n")
+            },
+            CompileFailure::Std(std_errors, errors) => {
+                let messages: Vec<String> = std_errors.iter().chain(errors).map(|error| error.to_string()).collect();
+                messages.join("\n")
+            },
+            CompileFailure::Unresolved(_) => String::from("The program has unresolved dependencies"),
+            CompileFailure::Invalid(msg) => msg.clone()
+        }
+    }
+
+    // The failure as diagnostics for "*message_format=json". 'file' is the program, and 'std_file'
+    // is where the standard library came from, the macros in std are in there
+    pub fn diagnostics(&self, file: &str, std_file: &str) -> Vec<diagnostics::Diagnostic> {
+        use diagnostics::{ Diagnostic, Severity };
+        let file_of = |name: &str| if name == STD_ROOT || name.starts_with("std/") { std_file } else { file };
+        match self {
+            CompileFailure::Source(errors) => errors.iter()
+                .map(|error| Diagnostic::new("syntax", Severity::Error, Some(file), error.msg.clone()).at(error.loc))
+                .collect(),
+            CompileFailure::Std(std_errors, errors) => std_errors.iter().map(|error| (std_file, error))
+                .chain(errors.iter().map(|error| (file, error)))
+                .map(|(file, error)| Diagnostic::new("syntax", Severity::Error, Some(file), error.msg.clone()).at(error.loc))
+                .collect(),
+            CompileFailure::Unresolved(compiler) => {
+                let mut found = Vec::new();
+                for dependency in compiler.unresolved_dependencies() {
+                    let (code, mut message) = if dependency.defined {
+                        ("macro_not_compiled", format!("'{}' is defined, but couldn't compile", dependency.name))
+                    }else{
+                        ("undefined_macro", format!("'{}' is not defined, but code uses it", dependency.name))
+                    };
+                    if let Some(suggestion) = &dependency.suggestion {
+                        message.push_str(&format!(" (did you mean '{}'?)", suggestion));
+                    }
+
+                    // Every use is a diagnostic of its own, with the other uses as related locations
+                    let uses: Vec<(&str, parse_bf::Loc)> = dependency.users.iter()
+                        .flat_map(|(name, locs)| locs.iter().map(move |loc| (&name[..], *loc)))
+                        .collect();
+                    for (i, (user, loc)) in uses.iter().enumerate() {
+                        let mut diagnostic = Diagnostic::new(code, Severity::Error, Some(file_of(user)),
+                            format!("{}, '{}' uses it here", message, user)).at(*loc);
+                        for (j, (other, other_loc)) in uses.iter().enumerate() {
+                            if i != j {
+                                diagnostic = diagnostic.with_related(Some(file_of(other)), *other_loc,
+                                    format!("'{}' uses it here too", other));
+                            }
+                        }
+                        found.push(diagnostic);
+                    }
+                }
+                found
+            },
+            CompileFailure::Invalid(msg) => vec![Diagnostic::new("invalid_program", Severity::Error, Some(file), msg.clone())]
+        }
+    }
+}
+
+// Settings for how a program is compiled, that don't change what it does
+#[derive(Clone, Debug, Default)]
+pub struct CompileOptions {
+    // Compiles strings that are used more than once a single time, and calls them
+    pub pool_strings: bool,
+    // Lets names have letters that aren't ascii, like 'é'
+    pub unicode_identifiers: bool,
+    // Trusts that the scratch cells of '?', '=[' and '%[', and the cells strings go in, are zero
+    // already, so they aren't cleared first
+    pub assume_zero_tape: bool,
+    // Which namespaces can use which features, see features
+    pub features: features::FeatureRules
+}
+
+// How many characters have to be added, removed or swapped out to turn one word into the other
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b) in b.iter().enumerate() {
+            let swap = previous[j] + if a == *b { 0 } else { 1 };
+            current[j + 1] = swap.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+// The root of the standard library's macros, user code can't define anything in it
+pub const STD_ROOT: &str = "std";
+
+// Compiles a program, the standard library is optional since it may not exist.
+// The program is compiled under the name "src" and the library under "std".
+pub fn compile_program(src: Vec<char>, std_lib: Option<Vec<char>>, options: CompileOptions) -> Result<Program, CompileFailure> {
+    let (compiler, pragmas) = compile_units(src, std_lib, options)?;
+
+    link_program(&compiler, pragmas, "src")
+        .ok_or_else(|| CompileFailure::Invalid(String::from("Didn't compile! :(")))
+}
+
+// Compiles a program without the standard library, for the tests of every module
+#[cfg(test)]
+pub fn compile_test_program(text: &str) -> Program {
+    compile_program(text.chars().collect(), None, CompileOptions::default())
+        .unwrap_or_else(|failure| panic!("{} didn't compile, {}", text, failure.describe()))
+}
+
+// Like compile_program, but every entry is linked as a program of its own, so a file full of
+// macros only has to be compiled once to run several of them. The entries are names like "src/a"
+pub fn compile_entries(src: Vec<char>, std_lib: Option<Vec<char>>, options: CompileOptions, entries: &[String])
+        -> Result<Vec<Program>, CompileFailure> {
+    let (compiler, pragmas) = compile_units(src, std_lib, options)?;
+
+    entries.iter()
+        .map(|entry| link_program(&compiler, pragmas, entry)
+            .ok_or_else(|| CompileFailure::Invalid(format!("There is no macro called '{}' to run", entry))))
+        .collect()
+}
+
+// Links the unit 'name' into a program of its own, with debug symbols
+pub fn link_program(compiler: &Compiler, pragmas: Pragmas, name: &str) -> Option<Program> {
+    let unit = self_profile::phase("link", || compiler.link(name))?;
+    let symbols = symbols::DebugSymbols::new(compiler, name, &unit);
+    let mut program = Program::new(pragmas, unit.code);
+    program.labels = unit.labels;
+    program.symbols = Some(symbols);
+    program.imports = unit.imports.iter().map(|(offset, id)| (*offset, compiler.name_of(*id))).collect();
+    Some(program)
+}
+
+// Like compile_program, but the standard library comes from a library binary instead of being
+// compiled again. The program is left with imports for the macros it uses from it, see Program::on_library
+pub fn compile_on_library(src: Vec<char>, library: &Program, options: CompileOptions) -> Result<Program, CompileFailure> {
+    let compiler = Compiler::with_options(options);
+    for (name, _) in &library.exports {
+        compiler.add_external(name);
+    }
+
+    let (compiler, pragmas) = compile_source(compiler, src)?;
+    link_program(&compiler, pragmas, "src")
+        .ok_or_else(|| CompileFailure::Invalid(String::from("Didn't compile! :(")))
+}
+
+// Compiles the standard library into a library binary, that programs can be run on top of
+// with *lib=file instead of compiling the library every time
+pub fn compile_library(std_lib: Vec<char>, options: CompileOptions) -> Result<Program, CompileFailure> {
+    let (compiler, pragmas) = compile_units(Vec::new(), Some(std_lib), options)?;
+    let (unit, exports) = compiler.link_library(STD_ROOT);
+    let mut program = Program::new(pragmas, unit.code);
+    program.labels = unit.labels;
+    program.exports = exports;
+    Ok(program)
+}
+
+// Like compile_program, but gives back the compiler with every unit in it
+pub fn compile_units(src: Vec<char>, std_lib: Option<Vec<char>>, options: CompileOptions) -> Result<(Compiler, Pragmas), CompileFailure> {
+    let compiler = Compiler::with_options(options);
+
+    // The library goes first, so anything in the program that collides with it
+    // is reported as an error in the program
+    if let Some(std_lib) = std_lib {
+        let mut std_lexer = Lexer::new(std_lib);
+        if let Err(std_errors) = self_profile::phase("lex std", || std_lexer.tokenize(&[String::from(STD_ROOT)], &compiler, false)) {
+            let mut lexer = Lexer::new(src);
+            let errors = self_profile::phase("lex", || lexer.tokenize(&[String::from("src")], &compiler, false)).err();
+            compiler.log_notes();
+            return Err(CompileFailure::Std(std_errors, errors.unwrap_or_default()));
+        }
+    }
+
+    compile_source(compiler, src)
+}
+
+// Compiles the program under "src", with whatever is in the compiler already
+fn compile_source(compiler: Compiler, src: Vec<char>) -> Result<(Compiler, Pragmas), CompileFailure> {
+    let mut lexer = Lexer::new(src);
+    let result = self_profile::phase("lex", || lexer.tokenize(&[String::from("src")], &compiler, false));
+    compiler.log_notes();
+    result.map_err(CompileFailure::Source)?;
+
+    // Resolves the dependencies between the units, and generates the code of every one of them
+    let unresolved = self_profile::phase("compile", || compiler.finish_compilation()).map_err(CompileFailure::Invalid)?;
+
+    if !unresolved.is_empty() || !compiler.is_done() {
+        return Err(CompileFailure::Unresolved(Box::new(compiler)));
+    }
+
+    let errors = metrics::check_footprints(&compiler);
+    if !errors.is_empty() {
+        return Err(CompileFailure::Source(errors));
+    }
+
+    Ok((compiler, lexer.pragmas()))
+}
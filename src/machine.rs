@@ -0,0 +1,369 @@
+use crate::instructions::*;
+use crate::inf_memory::Cells;
+use crate::binary::Program;
+use crate::pragma::{ Pragmas, Tape };
+use crate::introspect::{ Random, fact };
+
+// How many calls deep a program can go before it's stopped, so runaway
+// recursion is an error instead of eating all the memory
+pub const MAX_CALL_DEPTH: usize = 1 << 16;
+
+// The message for an ASSERT at 'instr_ptr' that failed
+pub fn assertion_failure(code: &[u8], instr_ptr: usize, value: u32, mem_ptr: isize) -> String {
+    let expected = read_u32(code, instr_ptr + 1);
+    let line = read_u32(code, instr_ptr + 5);
+    let column = read_u32(code, instr_ptr + 9);
+    format!("failed the assertion at ({}, {}), expected {:X} but cell {} is {:X}",
+        line, column, expected, mem_ptr, value)
+}
+
+// What a run is stopped at, nothing is limited by default
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    // How many instructions can run
+    pub max_steps: Option<u64>,
+    // How many cells can be used, counted from the lowest to the highest cell that was visited
+    pub max_memory: Option<u64>,
+    // How far the memory pointer can get from cell 0
+    pub trap_drift: Option<u64>
+}
+
+impl Limits {
+    // The limits from the pragmas of a program
+    pub fn from_pragmas(pragmas: &Pragmas) -> Limits {
+        Limits { max_steps: pragmas.max_steps, max_memory: pragmas.max_memory, trap_drift: None }
+    }
+}
+
+// Why a run stopped before the end of the program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fault {
+    MaxSteps(u64),
+    MaxMemory(u64),
+    // Where the memory pointer went, and how far it was allowed to go
+    Drift(isize, u64),
+    // Where the memory pointer went, and how long the fixed tape is
+    OffFixedTape(isize, u32),
+    // The tape itself ran out, like the flat tape does
+    OffTape(isize),
+    CallDepth,
+    NothingToReturn,
+    // The message of assertion_failure
+    Assertion(String),
+    // Reading, printing or a debug instruction went wrong, in the Io's own words
+    Io(String)
+}
+
+impl Fault {
+    // What the program did, written to go after "the program" or "it", ex: "ran more than 10 instructions"
+    pub fn describe(&self) -> String {
+        match self {
+            Fault::MaxSteps(steps) => format!("ran more than {} instructions", steps),
+            Fault::MaxMemory(cells) => format!("used more than {} cells", cells),
+            Fault::Drift(mem_ptr, allowed) => format!("drifted to cell {}, more than the {} cells from the start it's allowed to go", mem_ptr, allowed),
+            Fault::OffFixedTape(mem_ptr, length) => format!("moved to cell {}, outside of the fixed tape of {} cells", mem_ptr, length),
+            Fault::OffTape(mem_ptr) => format!("moved to cell {}, off the end of the tape", mem_ptr),
+            Fault::CallDepth => format!("went more than {} calls deep", MAX_CALL_DEPTH),
+            Fault::NothingToReturn => String::from("returned with nothing to return to"),
+            Fault::Assertion(msg) | Fault::Io(msg) => msg.clone()
+        }
+    }
+}
+
+// What a step did
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flow {
+    Ran,
+    // The program wants to READ, but the Io didn't have anything. The READ runs again on the next step
+    NeedsInput,
+    Finished
+}
+
+// Where a Machine gets its input from, and where what it prints goes
+pub trait Io {
+    // The next byte of input, or None if there isn't any yet
+    fn read(&mut self, machine: &Machine) -> Result<Option<u8>, String>;
+
+    fn print(&mut self, machine: &Machine, byte: u8) -> Result<(), String>;
+
+    // Whether a READ would get something right away, without asking anyone for it
+    fn has_input(&self) -> bool;
+
+    // Runs the DEBUG or DEBUG_OP at the instruction pointer. Most ways of running
+    // a program don't have anywhere to show them, so they're skipped by default
+    fn debug<C: Cells>(&mut self, _program: &Program, _machine: &Machine, _memory: &C) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// What every way of running a program shares, one instruction at a time. execute_bf, the
+// Interpreter and traced runs all step through programs with this, so they run them the same way
+// and stop at the same limits, and only differ in where the input and output go
+pub struct Machine {
+    instr_ptr: usize,
+    mem_ptr: isize,
+    call_stack: Vec<usize>,
+    steps: u64,
+    // The range of cells the program visited
+    lowest_ptr: isize,
+    highest_ptr: isize,
+    limits: Limits,
+    random: Random
+}
+
+impl Machine {
+    pub fn new(limits: Limits, random: Random) -> Machine {
+        Machine {
+            instr_ptr: 0,
+            mem_ptr: 0,
+            call_stack: Vec::new(),
+            steps: 0,
+            lowest_ptr: 0,
+            highest_ptr: 0,
+            limits,
+            random
+        }
+    }
+
+    pub fn instr_ptr(&self) -> usize {
+        self.instr_ptr
+    }
+
+    pub fn mem_ptr(&self) -> isize {
+        self.mem_ptr
+    }
+
+    // How many instructions have run so far
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    // The lowest and highest cell the program visited
+    pub fn visited(&self) -> (isize, isize) {
+        (self.lowest_ptr, self.highest_ptr)
+    }
+
+    pub fn limits(&self) -> Limits {
+        self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    // How many more instructions can run, None if there's no limit
+    pub fn steps_left(&self) -> Option<u64> {
+        self.limits.max_steps.map(|max_steps| max_steps.saturating_sub(self.steps))
+    }
+
+    // Goes back to the start of the program, the memory pointer stays where it is
+    pub fn restart(&mut self) {
+        self.instr_ptr = 0;
+        self.call_stack.clear();
+    }
+
+    // Runs the instruction at the instruction pointer. If it fails, the instruction pointer
+    // stays on the instruction that failed
+    pub fn step<C: Cells, I: Io>(&mut self, program: &Program, memory: &mut C, io: &mut I) -> Result<Flow, Fault> {
+        let code = &program.code[..];
+        if self.instr_ptr >= code.len() {
+            return Ok(Flow::Finished);
+        }
+        if self.steps_left() == Some(0) {
+            return Err(Fault::MaxSteps(self.steps));
+        }
+
+        let cell_mask = program.pragmas.cell_mask();
+        let instruction = code[self.instr_ptr];
+        let mut next = self.instr_ptr + len(instruction);
+        let mem_ptr = self.mem_ptr;
+        match instruction {
+            SHIFT_LEFT => self.mem_ptr -= 1,
+            SHIFT_RIGHT => self.mem_ptr += 1,
+            INCREMENT => memory.modify(mem_ptr, |b| b.wrapping_add(0x01) & cell_mask)?,
+            DECREMENT => memory.modify(mem_ptr, |b| b.wrapping_sub(0x01) & cell_mask)?,
+            READ => match io.read(self).map_err(Fault::Io)? {
+                Some(byte) => memory.set(mem_ptr, byte as u32 & cell_mask)?,
+                None => return Ok(Flow::NeedsInput)
+            },
+            // Cells wider than a byte only print their lowest byte
+            PRINT => io.print(self, memory.get(mem_ptr) as u8).map_err(Fault::Io)?,
+            LOOP_OPEN | LOOP_OPEN_WIDE if memory.get(mem_ptr) == 0 => next = self.instr_ptr + loop_offset(code, self.instr_ptr),
+            LOOP_CLOSE | LOOP_CLOSE_WIDE if memory.get(mem_ptr) != 0 => next = self.instr_ptr - loop_offset(code, self.instr_ptr),
+            CALL => {
+                if self.call_stack.len() >= MAX_CALL_DEPTH {
+                    return Err(Fault::CallDepth);
+                }
+
+                self.call_stack.push(next);
+                next = read_u32(code, self.instr_ptr + 1) as usize;
+            },
+            RET => match self.call_stack.pop() {
+                Some(offset) => next = offset,
+                None => return Err(Fault::NothingToReturn)
+            },
+            ASSERT => {
+                let value = memory.get(mem_ptr);
+                if value != read_u32(code, self.instr_ptr + 1) & cell_mask {
+                    return Err(Fault::Assertion(assertion_failure(code, self.instr_ptr, value, mem_ptr)));
+                }
+            },
+            INTROSPECT => {
+                // The fuel left after this instruction
+                let fuel = self.steps_left().map(|steps_left| steps_left - 1);
+                let value = fact(code[self.instr_ptr + 1], mem_ptr, fuel, io.has_input(), &mut self.random, cell_mask);
+                memory.set(mem_ptr, value)?;
+            },
+            DEBUG | DEBUG_OP => io.debug(program, self, memory).map_err(Fault::Io)?,
+            _ => {}
+        }
+
+        self.steps += 1;
+        self.lowest_ptr = self.lowest_ptr.min(self.mem_ptr);
+        self.highest_ptr = self.highest_ptr.max(self.mem_ptr);
+
+        if let Tape::Fixed(length) = program.pragmas.tape {
+            if self.mem_ptr < 0 || self.mem_ptr >= length as isize {
+                return Err(Fault::OffFixedTape(self.mem_ptr, length));
+            }
+        }
+
+        // The program starts at cell 0
+        if let Some(trap_drift) = self.limits.trap_drift {
+            if self.mem_ptr.unsigned_abs() as u64 > trap_drift {
+                return Err(Fault::Drift(self.mem_ptr, trap_drift));
+            }
+        }
+
+        if let Some(max_memory) = self.limits.max_memory {
+            if (self.highest_ptr - self.lowest_ptr) as u64 >= max_memory {
+                return Err(Fault::MaxMemory(max_memory));
+            }
+        }
+
+        if !memory.contains(self.mem_ptr) {
+            return Err(Fault::OffTape(self.mem_ptr));
+        }
+
+        self.instr_ptr = next;
+        Ok(Flow::Ran)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ Modifiers, Newline, compile_test_program };
+    use crate::interpreter::{ Interpreter, State };
+    use crate::trace::{ Input, trace_run };
+
+    // The error of running the program with execute_bf, the Interpreter and trace_run,
+    // which should all stop at the same instruction
+    fn errors(text: &str, limits: Limits) -> (String, String, String) {
+        let program = compile_test_program(text);
+
+        let mut modifiers = Modifiers::new();
+        modifiers.max_steps = limits.max_steps;
+        modifiers.max_memory = limits.max_memory;
+        modifiers.trap_drift = limits.trap_drift;
+        let run = crate::execute_to_bytes(&program, &modifiers, &[]).unwrap_err();
+
+        let mut interpreter = Interpreter::new(compile_test_program(text));
+        interpreter.set_limits(limits);
+        let interpreted = match interpreter.run() {
+            State::Failed(offset, msg) => format!("{} at instruction {:X}", msg, offset),
+            other => panic!("Didn't expect {:?}", other)
+        };
+
+        let mut input = Input { data: Vec::new(), can_ask: false, newline: Newline::Raw };
+        let trace = trace_run(&program, &mut input, limits);
+        let traced = trace.error.map(|(offset, msg)| format!("{} at instruction {:X}", msg, offset)).unwrap();
+
+        (run, interpreted, traced)
+    }
+
+    #[test]
+    fn every_way_of_running_stops_at_the_same_step() {
+        let (run, interpreted, traced) = errors("+[>+<+]", Limits { max_steps: Some(50), ..Limits::default() });
+        assert!(run.starts_with(&format!("The program {}", interpreted)), "{}", run);
+        assert_eq!(interpreted, traced);
+        assert!(interpreted.starts_with("ran more than 50 instructions"), "{}", interpreted);
+    }
+
+    #[test]
+    fn every_way_of_running_asserts_the_same() {
+        let (run, interpreted, traced) = errors("+++ !=2", Limits::default());
+        assert!(run.starts_with(&format!("The program {}", interpreted)), "{}", run);
+        assert_eq!(interpreted, traced);
+        assert!(interpreted.starts_with("failed the assertion at (1, 4), expected 2 but cell 0 is 3"), "{}", interpreted);
+    }
+
+    #[test]
+    fn assertions_only_stop_programs_with_the_wrong_value() {
+        let mut interpreter = Interpreter::new(compile_test_program("+41 !=41 > !=0 <."));
+        assert_eq!(interpreter.run(), &State::Finished);
+        assert_eq!(interpreter.take_output(), b"A");
+
+        let mut interpreter = Interpreter::new(compile_test_program("+41\n>+ !=41"));
+        match interpreter.run() {
+            State::Failed(_, msg) => assert_eq!(msg, "failed the assertion at (2, 3), expected 41 but cell 1 is 1"),
+            other => panic!("Didn't expect {:?}", other)
+        }
+
+        let text = "!= +";
+        assert!(crate::compile_program(text.chars().collect(), None, crate::CompileOptions::default()).is_err(), "{}", text);
+
+        // Assertions are kept by the optimizer, so the "-" and "+" around one can't cancel out
+        let optimized = crate::optimize::optimize(&compile_test_program("+41 - !=40 + ."), 3);
+        assert!(optimized.program.code.contains(&ASSERT));
+        let mut interpreter = Interpreter::new(optimized.program);
+        assert_eq!(interpreter.run(), &State::Finished);
+    }
+
+    #[test]
+    fn calls_return_to_where_they_were_made() {
+        let program = compile_test_program(":count noinline { +. } #src/count >+2< #src/count");
+        let calls: Vec<usize> = (0..program.code.len()).filter(|i| program.code[*i] == CALL).collect();
+        assert_eq!(calls.len(), 2);
+        assert!(crate::binary::validate_code(&program.code).is_ok());
+
+        let mut interpreter = Interpreter::new(program);
+        assert_eq!(interpreter.run(), &State::Finished);
+        assert_eq!(interpreter.take_output(), vec![1, 2]);
+        assert_eq!(interpreter.cell(1), 2);
+
+        // A CALL has to go to the start of an instruction
+        let mut code = vec![INCREMENT, CALL];
+        code.extend_from_slice(&2u32.to_le_bytes());
+        assert!(crate::binary::validate_code(&code).is_err());
+    }
+
+    // For programs that don't read or print
+    struct NoIo;
+    impl Io for NoIo {
+        fn read(&mut self, _machine: &Machine) -> Result<Option<u8>, String> { Ok(None) }
+        fn print(&mut self, _machine: &Machine, _byte: u8) -> Result<(), String> { Ok(()) }
+        fn has_input(&self) -> bool { false }
+    }
+
+    #[test]
+    fn calls_can_only_go_so_deep() {
+        let run = |code: Vec<u8>| {
+            let program = Program::new(Pragmas::new(), code);
+            let mut machine = Machine::new(Limits::default(), Random::seeded(0));
+            let mut memory = crate::Memory::new();
+            loop {
+                match machine.step(&program, &mut memory, &mut NoIo) {
+                    Ok(Flow::Ran) => {},
+                    other => return (other, machine.steps())
+                }
+            }
+        };
+
+        // Calls itself forever
+        let mut code = vec![CALL];
+        code.extend_from_slice(&0u32.to_le_bytes());
+        assert_eq!(run(code), (Err(Fault::CallDepth), MAX_CALL_DEPTH as u64));
+        assert_eq!(run(vec![INCREMENT, RET]), (Err(Fault::NothingToReturn), 1));
+    }
+}
@@ -1,268 +1,1665 @@
 extern crate ansi_term;
 use std::env;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::IsTerminal;
 
-pub mod run;
-use run::execute_bf;
+use brainfuck::{ Modifiers, InputSource, Newline, edit_distance, CompileFailure, compile_program, compile_entries, compile_units, compile_on_library, compile_library, link_program, execute_bf, execute_raw, execute_to_bytes, instructions };
+use brainfuck::image::check_netpbm;
+use brainfuck::run::TerminalOutput;
+use brainfuck::compile_db::compile_database;
+use brainfuck::metrics::unit_metrics;
+use brainfuck::advise::{ Advice, size_advice, speed_advice };
+use brainfuck::run::execute_with_stats;
+use brainfuck::entries::run_entries;
+use brainfuck::interpreter::State;
+use brainfuck::source::read_source;
+use brainfuck::binary::{ FileKind, Program, detect_file_kind, read_bin_from_file, read_versioned_bin, write_bin_to_file };
+use brainfuck::pragma::Tape;
+use brainfuck::bench;
+use brainfuck::bundle::{ self, Bundle };
+use brainfuck::emit::{ EmitKind, Target, emit, plain_bf_for, check_target };
+use brainfuck::color::{ self, style };
+use brainfuck::fun;
+use brainfuck::diagnostics::{ self, Diagnostic, Severity };
+use brainfuck::examples::{ EXAMPLES, find_example };
+use ansi_term::Color;
+use brainfuck::testing;
+use brainfuck::optimize::{ Pass, optimize_with_pipeline };
+use brainfuck::specialize::specialize;
+use brainfuck::script::{ self, Session, run_script };
+use brainfuck::self_profile::{ self, CountingAlloc };
+use brainfuck::verify::{ verify_optimizations, bisect_optimizations };
+use brainfuck::trace::record_run;
+use brainfuck::trace_diff::{ read_trace, diff_traces, TraceWriter };
 
-pub mod inf_memory;
-pub use inf_memory::Memory;
+// Counts the memory the compiler uses for *self_profile, it's the system allocator otherwise
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
 
-pub mod parse_bf;
-pub use parse_bf::{ Lexer };
+// Every modifier, the ones ending with '=' take a value
+const MODIFIERS: &[&str] = &[
+    "debug", "in=", "in_file=", "bin=", "print_bin", "opt", "passes=", "verify_opt", "specialize", "emit=", "target=", "prompt=", "no_prompt",
+    "echo_input", "max_steps=", "max_memory=", "trap_drift=", "deny=", "allow=", "no_limits", "sandbox", "pool_strings", "unicode_identifiers", "assume_zero_tape", "serious", "profile", "self_profile", "report=", "transcript=",
+    "out=", "image=", "sanitize_output", "flat_tape", "dump=", "snapshot_dir=", "trace=", "message_format=",
+    "entries=", "parallel", "lib=", "newline="
+];
 
-pub mod compiler;
-pub use compiler::{ Compiler };
+// The modifiers of the commands that have their own
+const BENCH_MODIFIERS: &[&str] = &["iterations="];
+const TEST_MODIFIERS: &[&str] = &["bench_threshold=", "save_bench"];
 
-pub mod instructions {
-    pub const SHIFT_RIGHT: u8 = 0x00;
-    pub const SHIFT_LEFT: u8 = 0x01;
-    pub const INCREMENT: u8 = 0x02;
-    pub const DECREMENT: u8 = 0x03;
-    pub const LOOP_OPEN: u8 = 0x04;
-    pub const LOOP_CLOSE: u8 = 0x05;
-    pub const PRINT: u8 = 0x06;
-    pub const READ: u8 = 0x07;
-    pub const DEBUG: u8 = 0x08;
-}
-
-pub type CompileResult<T> = Result<T, Vec<Error>>;
-
-pub struct Error {
-    pub loc: parse_bf::Loc,
-    pub msg: String
-}
-
-impl Error {
-    pub fn new(loc: parse_bf::Loc, msg: String) -> Error {
-        Error {
-            loc: loc,
-            msg: msg
+// 'modifiers' are the ones that could have been meant
+fn unknown_modifier(name: &str, modifiers: &[&str]) -> String {
+    let mut msg = format!("Invalid modifier name, '*{}'", name);
+    let closest = modifiers.iter()
+        .map(|modifier| (edit_distance(name, modifier.trim_end_matches('=')), modifier))
+        .min_by_key(|(distance, _)| *distance);
+    if let Some((distance, modifier)) = closest {
+        if distance <= 2 {
+            msg.push_str(&format!(", did you mean '*{}'?", modifier));
         }
     }
-}
-
-impl std::fmt::Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "ERROR {}: '{}'", self.loc, self.msg)
-    }
-}
-
-pub struct Modifiers {
-    is_debug: bool,
-    std_in: String,
-    print_bin: bool,
-    save_bin: Option<String>
-}
 
-impl Modifiers {
-    pub fn new() -> Modifiers {
-        Modifiers {
-            is_debug: false,
-            save_bin: None,
-            print_bin: false,
-            std_in: String::new()
-        }
-    }
+    let all: Vec<String> = modifiers.iter().map(|modifier| format!("*{}", modifier)).collect();
+    msg.push_str(&format!("\nThe modifiers are {}", all.join(" ")));
+    msg
 }
 
-fn read_command_line_args<'a>(args: &'a Vec<String>) -> (&'a str, Modifiers) {
-    if args.len() < 1 {
-        panic!("Expected at least 1 command line argument; \"File name\"");
+// The file and the modifiers, None if anything was wrong with them. A modifier that's wrong
+// is skipped, so the rest are still read and every mistake is printed at once
+fn read_command_line_args(args: &[String]) -> Option<(&str, Modifiers)> {
+    if args.is_empty() {
+        log_error("usage", "", String::from("Expected at least 1 command line argument; \"File name\""));
+        return None;
     }
 
     let path = &args[0][..];
     
     // Read modifiers
+    let mut errors = Vec::new();
     let mut modifiers = Modifiers::new();
     for modifier in &args[1..] {
         // Modifiers start with '*'
-        if modifier.get(0..1).unwrap() != "*" {
-            panic!("Expected '*' to start modifier '{}'", modifier);
+        if modifier.get(0..1) != Some("*") {
+            errors.push(format!("Expected '*' to start modifier '{}'", modifier));
+            continue;
         }
 
         // Split at '=', because data passed to modifiers are split with =, 
         // ex: *bin=example.bin
-        let mut contents = modifier.get(1..).unwrap().split("=");
+        let mut contents = modifier.get(1..).unwrap().split('=');
         let name = contents.next();
 
         // Do things individually depending on which modifier it is
-        if let Some(name) = name {
-            match name {
-                "debug" => {
-                    modifiers.is_debug = true;
-                },
-                "in" => {
-                    let data = contents.next().expect("'in' modifier expected some data, add data after '='");
-                    modifiers.std_in.push_str(data);
-                },
-                "bin" => {
-                    let data = contents.next().expect("'bin' modifier expected data");
-                    modifiers.save_bin = Some(String::from(data));
-                },
-                "print_bin" => {
-                    modifiers.print_bin = true;
-                },
-                _ => {
-                    panic!("Invalid modifier name, '{}'", name);
-                }
+        if let Some(name) = name.filter(|name| !name.is_empty()) {
+            if let Err(error) = read_modifier(&mut modifiers, name, &mut contents) {
+                errors.push(error);
+                continue;
             }
 
             // Too much data passed to the modifier, not all was used!
-            if let Some(_) = contents.next() {
-                panic!("Unexpected number of parameters to modifier, '{}'", modifier);
+            if contents.next().is_some() {
+                errors.push(format!("Unexpected number of parameters to modifier, '{}'", modifier));
             }
         }else {
             // There was no name in the modifier
-            panic!("Expected modifier name!, '{}'", modifier);
+            errors.push(format!("Expected modifier name!, '{}'", modifier));
+        }
+    }
+
+    if modifiers.sandbox {
+        if modifiers.no_limits {
+            errors.push(String::from("*sandbox can't be used with *no_limits, the limits are the point of it"));
+        }
+        if modifiers.dump_file.is_some() || modifiers.snapshot_dir.is_some() {
+            errors.push(String::from("*sandbox skips the debug instructions, so *dump and *snapshot_dir don't do anything with it"));
         }
+        modifiers.sanitize_output = true;
+        modifiers.prompt = None;
+    }
+
+    diagnostics::set_json(modifiers.json_messages);
+    for error in &errors {
+        log_error("invalid_modifier", path, error.clone());
     }
 
-    (path, modifiers)
+    if errors.is_empty() { Some((path, modifiers)) } else { None }
 }
 
-fn main() {
-    if ansi_term::enable_ansi_support().is_err() {
-        println!("Couldn't enable console color, so you'll be stuck with monocrome..");
+// Reads one modifier, 'contents' is what comes after the name, split at '='
+fn read_modifier(modifiers: &mut Modifiers, name: &str, contents: &mut std::str::Split<'_, char>) -> Result<(), String> {
+    match name {
+        "debug" => {
+            modifiers.is_debug = true;
+        },
+        "in" => {
+            let data = contents.next().ok_or("'in' modifier expected some data, add data after '='")?;
+            modifiers.inputs.push(InputSource::Text(String::from(data)));
+        },
+        "in_file" => {
+            let data = contents.next().ok_or("'in_file' modifier expected the path of the file")?;
+            modifiers.inputs.push(InputSource::File(String::from(data)));
+        },
+        "bin" => {
+            let data = contents.next().ok_or("'bin' modifier expected data")?;
+            modifiers.save_bin = Some(String::from(data));
+        },
+        "print_bin" => {
+            modifiers.print_bin = true;
+        },
+        "opt" => {
+            modifiers.opt_level = match contents.next() {
+                Some(data) => data.parse().map_err(|_| "'opt' modifier expected a level, like *opt=2")?,
+                None => 1
+            };
+        },
+        "passes" => {
+            let data = contents.next().ok_or("'passes' modifier expected the passes to run, ex: *passes=cancel,unroll")?;
+            modifiers.passes = data.split(',').map(String::from).collect();
+            modifiers.pipeline()?;
+            modifiers.opt_level = modifiers.opt_level.max(1);
+        },
+        "verify_opt" => {
+            modifiers.verify_opt = true;
+        },
+        "specialize" => {
+            modifiers.specialize = true;
+        },
+        "emit" => {
+            let data = contents.next().ok_or("'emit' modifier expected what to emit, ex: *emit=all")?;
+            modifiers.emit = EmitKind::parse_list(data)?;
+        },
+        "target" => {
+            let data = contents.next().ok_or("'target' modifier expected the target, ex: *target=classic")?;
+            modifiers.target = Some(Target::parse(data)?);
+        },
+        "prompt" => {
+            let data = contents.next().ok_or("'prompt' modifier expected the text to prompt with")?;
+            modifiers.prompt = Some(String::from(data));
+        },
+        "no_prompt" => {
+            modifiers.prompt = None;
+        },
+        "echo_input" => {
+            modifiers.echo_input = true;
+        },
+        "max_steps" => {
+            let data = contents.next().ok_or("'max_steps' modifier expected the number of instructions")?;
+            modifiers.max_steps = Some(data.parse().map_err(|_| "'max_steps' modifier expected a number")?);
+        },
+        "max_memory" => {
+            let data = contents.next().ok_or("'max_memory' modifier expected the number of cells")?;
+            modifiers.max_memory = Some(data.parse().map_err(|_| "'max_memory' modifier expected a number")?);
+        },
+        "trap_drift" => {
+            let data = contents.next().ok_or("'trap_drift' modifier expected the number of cells, ex: *trap_drift=1000")?;
+            modifiers.trap_drift = Some(data.parse().map_err(|_| "'trap_drift' modifier expected a number")?);
+        },
+        "pool_strings" => {
+            modifiers.compile_options.pool_strings = true;
+        },
+        "assume_zero_tape" => {
+            modifiers.compile_options.assume_zero_tape = true;
+        },
+        "unicode_identifiers" => {
+            modifiers.compile_options.unicode_identifiers = true;
+        },
+        "deny" | "allow" => {
+            let data = contents.next().ok_or_else(|| format!("'{}' modifier expected a namespace and features, ex: *{}=std:read,print", name, name))?;
+            modifiers.compile_options.features.add(data, name == "allow")?;
+        },
+        "serious" => {
+            modifiers.serious = true;
+        },
+        "no_limits" => {
+            modifiers.no_limits = true;
+        },
+        "sandbox" => {
+            modifiers.sandbox = true;
+        },
+        "profile" => {
+            modifiers.profile = true;
+        },
+        "self_profile" => {
+            modifiers.self_profile = true;
+        },
+        "report" => {
+            let data = contents.next().ok_or("'report' modifier expected the path of the file")?;
+            modifiers.report = Some(String::from(data));
+        },
+        "transcript" => {
+            let data = contents.next().ok_or("'transcript' modifier expected the path of the file")?;
+            modifiers.transcript = Some(String::from(data));
+        },
+        "out" => {
+            let data = contents.next().ok_or("'out' modifier expected the path of the file")?;
+            modifiers.out = Some(String::from(data));
+        },
+        "sanitize_output" => {
+            modifiers.sanitize_output = true;
+        },
+        "image" => {
+            let data = contents.next().ok_or("'image' modifier expected the path of the file")?;
+            modifiers.image = Some(String::from(data));
+        },
+        "flat_tape" => {
+            modifiers.flat_tape = true;
+        },
+        "dump" => {
+            let data = contents.next().ok_or("'dump' modifier expected the path of the file")?;
+            modifiers.dump_file = Some(String::from(data));
+        },
+        "snapshot_dir" => {
+            let data = contents.next().ok_or("'snapshot_dir' modifier expected the path of a directory")?;
+            modifiers.snapshot_dir = Some(String::from(data));
+        },
+        "trace" => {
+            let data = contents.next().ok_or("'trace' modifier expected the path to save the trace to")?;
+            modifiers.save_trace = Some(String::from(data));
+        },
+        "entries" => {
+            let data = contents.next().ok_or("'entries' modifier expected the macros to run, ex: *entries=src/a,src/b")?;
+            modifiers.entries = data.split(',').map(String::from).collect();
+        },
+        "parallel" => {
+            modifiers.parallel = true;
+        },
+        "newline" => {
+            let data = contents.next().ok_or("'newline' modifier expected lf, crlf or raw")?;
+            modifiers.newline = Newline::parse(data)?;
+        },
+        "lib" => {
+            let data = contents.next().ok_or("'lib' modifier expected the path of a library binary, ex: *lib=std.bin")?;
+            modifiers.library = Some(String::from(data));
+        },
+        "message_format" => {
+            let data = contents.next().ok_or("'message_format' modifier expected 'human' or 'json'")?;
+            modifiers.json_messages = match data {
+                "human" => false,
+                "json" => true,
+                _ => return Err(format!("'message_format' modifier expected 'human' or 'json', not '{}'", data))
+            };
+        },
+        _ => {
+            return Err(unknown_modifier(name, MODIFIERS));
+        }
     }
+
+    Ok(())
+}
+
+fn main() {
+    let colors = color::detect();
+    #[cfg(windows)]
+    let colors = colors && match ansi_term::enable_ansi_support() {
+        Ok(()) => true,
+        Err(_) => {
+            println!("Couldn't enable console color, so you'll be stuck with monocrome..");
+            false
+        }
+    };
+    color::set_enabled(colors);
     
     // Command line arguments
     let mut args: Vec<String> = env::args().collect();
     args.remove(0);
-    let (path, modifiers) = read_command_line_args(&args);
+    let succeeded = run_command(&args);
 
-    // Parse/read the data, different depending on if the file is a
-    //      binary or not.
-    let data: Vec<u8> = match is_binary(path).unwrap() {
-        true => {
-            let result = read_bin_from_file(path);
-            if let Err(error) = result {
-                println!("There was an error, {}", error);
+    // Everything that went wrong was printed as it happened, and is summed up once at the end
+    if let Some(summary) = diagnostics::summary().filter(|_| !diagnostics::is_json()) {
+        let color = if diagnostics::error_count() > 0 { Color::Red } else { Color::Yellow };
+        println!("\n{}", style(color).paint(summary));
+    }
+
+    if !succeeded || diagnostics::error_count() > 0 {
+        std::process::exit(1);
+    }
+}
+
+// Runs a command, or the program with its modifiers. False if it failed in a way that
+// isn't an error, like a test that doesn't pass
+fn run_command(args: &[String]) -> bool {
+    if args.first().map(|arg| &arg[..]) == Some("bench") {
+        return run_bench_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("test") {
+        return run_test_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("bundle") {
+        return run_bundle_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("obfuscate") {
+        return run_obfuscate_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("compdb") {
+        return run_compdb_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("rename") {
+        return run_rename_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("bisect-opt") {
+        run_bisect_opt_command(&args[1..]);
+        return true;
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("std-lib") {
+        run_std_lib_command(&args[1..]);
+        return true;
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("metrics") {
+        run_metrics_command(&args[1..]);
+        return true;
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("advise") {
+        run_advise_command(&args[1..]);
+        return true;
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("info") {
+        return run_info_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("examples") {
+        run_examples_command(&args[1..]);
+        return true;
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("exec") {
+        return run_exec_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("repl") {
+        return run_repl_command(&args[1..]);
+    }
+
+    if args.first().map(|arg| &arg[..]) == Some("trace-diff") {
+        return run_trace_diff_command(&args[1..]);
+    }
+
+    let (path, modifiers) = match read_command_line_args(args) {
+        Some(value) => value,
+        None => return true
+    };
+    fun::set_enabled(!modifiers.serious);
+    self_profile::set_enabled(modifiers.self_profile);
+
+    if !modifiers.entries.is_empty() {
+        run_entries_of(path, &modifiers);
+    }else{
+        compile_and_run(path, &modifiers);
+    }
+    true
+}
+
+// Reads the program, does what the modifiers say with it and runs it
+fn compile_and_run(path: &str, modifiers: &Modifiers) {
+    // The library, the program and the input don't depend on each other, so all of them are read
+    // before giving up, and everything that's wrong with them is printed at once
+    let library = match &modifiers.library {
+        Some(library_path) => read_bin_from_file(library_path)
+            .map(Some)
+            .map_err(|error| log_error("invalid_binary", library_path, error.to_string())),
+        None => Ok(None)
+    };
+
+    // Without the library everything the program uses from it would be an error, so it isn't compiled
+    let program = match &library {
+        Ok(library) => read_program(path, modifiers, library.as_ref()),
+        Err(()) => None
+    };
+
+    // The input files are read before running anything, so a missing one is noticed right away
+    let std_in = modifiers.read_inputs().map_err(|error| log_error("io", path, error));
+
+    let (library, mut program, std_in) = match (library, program, std_in) {
+        (Ok(library), Some(program), Ok(std_in)) => (library, program, std_in),
+        _ => return
+    };
+
+    // The program is saved without the library, so it stays small, and only put on top of it to run.
+    // A specialized program doesn't call into the library anymore, so it's saved once it's specialized
+    if let Some(library) = &library {
+        if let Some(bin_path) = modifiers.save_bin.as_ref().filter(|_| !modifiers.specialize) {
+            save_bin(bin_path, &program);
+        }
+
+        program = match program.on_library(library) {
+            Ok(value) => value,
+            Err(error) => {
+                log_error("invalid_binary", path, error);
                 return;
             }
+        };
+    }else if let Some((_, name)) = program.imports.first() {
+        log_error("invalid_binary", path, format!("The program calls '{}' from a library binary, run it with *lib=[file]", name));
+        return;
+    }
 
-            result.unwrap()
-        },
-        false => {
-            let compiler = Compiler::new();
+    if modifiers.verify_opt {
+        match verify_optimizations(&program, &std_in, modifiers.limits(&program.pragmas), modifiers.opt_level.max(1), &modifiers.pipeline().unwrap()) {
+            Ok(verified) => {
+                println!("{}", String::from_utf8_lossy(&verified.output));
+                println!("{}", style(Color::Green).paint(
+                    format!("The optimized program behaves the same, {}", verified.summary)));
+            },
+            Err(divergence) => {
+                println!("{}", style(Color::Red).paint(
+                    format!("The optimized program behaves differently! {}", divergence)));
+            }
+        }
+        return;
+    }
 
-            let data: Vec<char> = std::fs::read_to_string(path)
-                                    .expect("Invalid file")
-                                    .chars().collect();
-        
-            let mut lexer = Lexer::new(data);
-            if let Err(errors) = lexer.tokenize(&vec![String::from("src")], &compiler, false) {
-                for error in &errors {
-                    println!("{}", error);
+    if modifiers.opt_level > 0 {
+        let pipeline = modifiers.pipeline().unwrap();
+        program = self_profile::phase("optimize", || optimize_with_pipeline(&program, modifiers.opt_level, &pipeline)).program;
+    }
+
+    if modifiers.specialize {
+        let limits = modifiers.limits(&program.pragmas);
+        let specialized = self_profile::phase("specialize", || specialize(&program, &modifiers.newline.translate_input(&std_in), limits));
+        program = match specialized {
+            Ok(value) => value,
+            Err(error) => {
+                log_error("specialize", path, error);
+                return;
+            }
+        };
+    }
+
+    if let Some(bin_path) = modifiers.save_bin.as_ref().filter(|_| library.is_none() || modifiers.specialize) {
+        save_bin(bin_path, &program);
+    }
+
+    if let Some(target) = &modifiers.target {
+        match check_target(&program, target) {
+            Ok(warnings) => for warning in warnings {
+                diagnostics::warn("target", Some(path), warning);
+            },
+            Err(error) => log_error("target", path, format!("The program can't run on the target, {}", error))
+        }
+    }
+
+    // Without a target the plain brainfuck is left as it is
+    let target = modifiers.target.unwrap_or_default();
+    if !modifiers.emit.is_empty() {
+        match self_profile::phase("emit", || emit(path, &program, &modifiers.emit, &target)) {
+            Ok(written) => {
+                for file in written {
+                    println!("Wrote '{}'", file.display());
                 }
+            },
+            Err(error) => log_error("emit", path, error)
+        }
+    }
+
+    if modifiers.print_bin {
+        print_bf_bin(&program, &target);
+    }
 
-                println!("\n{} errors, yay!", errors.len());
+    if modifiers.self_profile {
+        print_self_profile();
+    }
+
+    if let Some(path) = &modifiers.save_trace {
+        // The steps are written as the program runs, a long run would take up a lot of memory otherwise
+        let mut writer = match TraceWriter::create(path) {
+            Ok(value) => value,
+            Err(error) => {
+                log_error("io", path, format!("Couldn't save the trace to '{}', {}", path, error));
                 return;
             }
+        };
+        let mut input = modifiers.trace_input(&std_in);
+        let trace = match record_run(&program, &mut input, modifiers.limits(&program.pragmas), &mut |step| writer.step(step)) {
+            Ok(value) => value,
+            Err(error) => {
+                log_error("io", path, format!("Couldn't save the trace to '{}', {}", path, error));
+                return;
+            }
+        };
+        println!("{}", String::from_utf8_lossy(&trace.output_bytes()));
+        if let Some((offset, msg)) = &trace.error {
+            println!("{}", style(Color::Red).paint(format!("The program stopped at instruction {:X}, it {}", offset, msg)));
+        }
+
+        let steps = writer.steps();
+        match writer.finish(&trace.error) {
+            Ok(()) => println!("Saved a trace of {} steps to '{}'", steps, path),
+            Err(error) => log_error("io", path, format!("Couldn't save the trace to '{}', {}", path, error))
+        }
+        return;
+    }
+
+    run_program(path, &program, modifiers, &std_in);
+}
+
+// Reads the program at 'path', a binary or source that's compiled, on top of 'library' if there is one
+fn read_program(path: &str, modifiers: &Modifiers, library: Option<&Program>) -> Option<Program> {
+    // Parse/read the data, different depending on if the file is a
+    //      binary or not.
+    let file_kind = match detect_file_kind(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error.to_string());
+            return None;
+        }
+    };
+
+    match file_kind {
+        FileKind::Binary => match read_bin_from_file(path) {
+            Ok(program) => Some(program),
+            Err(error) => {
+                log_error("invalid_binary", path, error.to_string());
+                None
+            }
+        },
+        FileKind::Source => {
+            let data = match read_source(path) {
+                Ok(value) => value,
+                Err(error) => {
+                    log_error("io", path, error);
+                    return None;
+                }
+            };
 
-            if let Ok(std_file) = std::fs::read_to_string("std.bf") {
-                let std_data: Vec<char> = std_file.chars().collect();
-                let mut std_lexer = Lexer::new(std_data);
-                if let Err(errors) = std_lexer.tokenize(&vec![String::from("std")], &compiler, false) {
-                        for error in &errors {
-                        println!("{}", error);
+            // Bundles bring their own standard library, and a library binary stands in for it
+            let (compiled, std_path) = if bundle::is_bundle(&data) {
+                match bundle::read_bundle(&data) {
+                    Ok(bundle) => (compile_program(bundle.source, bundle.std_lib, modifiers.compile_options.clone()), path),
+                    Err(error) => {
+                        log_error("invalid_bundle", path, error);
+                        return None;
                     }
+                }
+            }else if let Some(library) = library {
+                (compile_on_library(data, library, modifiers.compile_options.clone()), "std.bf")
+            }else{
+                (compile_program(data, read_std_lib(), modifiers.compile_options.clone()), "std.bf")
+            };
+
+            match compiled {
+                Ok(value) => Some(value),
+                Err(failure) => {
+                    log_compile_failure(path, std_path, &failure);
+                    None
+                }
+            }
+        }
+    }
+}
+
+// Runs a program with the output going where the modifiers say, 'path' is where it came from.
+// 'std_in' is the input that was read from the modifiers
+fn run_program(path: &str, program: &Program, modifiers: &Modifiers, std_in: &[u8]) {
+    if let Some(image_path) = &modifiers.image {
+        match execute_to_bytes(program, modifiers, std_in) {
+            Ok(data) => save_image(image_path, &data),
+            Err(error) => log_runtime_error(path, error)
+        }
+        return;
+    }
+
+    // Terminals get the output as text, anything else gets the exact bytes unless it should be sanitized
+    let result = match &modifiers.out {
+        Some(path) => File::create(path)
+            .map_err(|error| format!("Couldn't create '{}', {}", path, error))
+            .and_then(|mut file| execute_raw(program, modifiers, std_in, &mut file)),
+        None if modifiers.sanitize_output || std::io::stdout().is_terminal() => {
+            let mut output = TerminalOutput::new(std::io::stdout()).sanitize(modifiers.sanitize_output);
+            execute_bf(program, modifiers, std_in, &mut output)
+        },
+        None => execute_bf(program, modifiers, std_in, &mut std::io::stdout())
+    };
+
+    if let Err(error) = result {
+        log_runtime_error(path, error);
+    }
+}
+
+// "*entries=src/a,src/b", compiles the program once and runs every entry on a fresh tape
+fn run_entries_of(path: &str, modifiers: &Modifiers) {
+    let source = match read_source(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return;
+        }
+    };
+
+    let input = match modifiers.read_inputs() {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return;
+        }
+    };
+
+    let programs = match compile_entries(source, read_std_lib(), modifiers.compile_options.clone(), &modifiers.entries) {
+        Ok(value) => value,
+        Err(failure) => {
+            log_compile_failure(path, "std.bf", &failure);
+            return;
+        }
+    };
+
+    // Every entry comes from the same file, so they have the same pragmas
+    let max_steps = modifiers.limits(&programs[0].pragmas).max_steps;
+    let entries = modifiers.entries.iter().cloned().zip(programs).collect();
+    let runs = run_entries(entries, &input, max_steps, modifiers.parallel);
+
+    let mut finished = 0;
+    for run in &runs {
+        let status = match &run.state {
+            State::Finished => {
+                finished += 1;
+                style(Color::Green).paint(String::from("finished"))
+            },
+            State::NeedsInput => style(Color::Red).paint(String::from("ran out of input")),
+            State::Running => style(Color::Red).paint(String::from("hit the step limit")),
+            State::Failed(offset, msg) => style(Color::Red).paint(format!("stopped at instruction {:X}, it {}", offset, msg))
+        };
+
+        println!("{} {} after {} steps in {:.2?}", style(Color::Cyan).paint(format!("== {} ==", run.name)), status, run.steps, run.time);
+        println!("{}", String::from_utf8_lossy(&run.output));
+    }
+    println!("{} of {} entries finished", finished, runs.len());
+}
+
+fn log_runtime_error(path: &str, error: String) {
+    diagnostics::count(Severity::Error, Some(path));
+    if diagnostics::is_json() {
+        println!("{}", Diagnostic::new("runtime_error", Severity::Error, Some(path), error).to_json());
+    }else{
+        println!("{}", style(Color::Red).paint(format!("RUNTIME ERROR: {}", error)));
+    }
+}
+
+// "There was an error, ..." or a json diagnostic with "*message_format=json"
+fn log_error(code: &'static str, path: &str, error: String) {
+    diagnostics::count(Severity::Error, Some(path));
+    if diagnostics::is_json() {
+        println!("{}", Diagnostic::new(code, Severity::Error, Some(path), error).to_json());
+    }else{
+        println!("There was an error, {}", error);
+    }
+}
+
+fn save_bin(path: &str, program: &Program) {
+    if let Err(error) = write_bin_to_file(path, program) {
+        log_error("io", path, format!("Couldn't save the binary to '{}', {}", path, error));
+    }
+}
+
+// The standard library next to the executable, if it's there
+fn read_std_lib() -> Option<Vec<char>> {
+    match read_source("std.bf") {
+        Ok(value) => Some(value),
+        Err(error) => {
+            diagnostics::warn("std_not_found", Some("std.bf"), format!("Standard library could not be loaded, {}", error));
+            None
+        }
+    }
+}
+
+// "*image=file", checks that the program printed a NetPBM image and saves it
+fn save_image(path: &str, data: &[u8]) {
+    match check_netpbm(data) {
+        Ok(info) => match std::fs::write(path, data) {
+            Ok(()) => println!("Saved a {}x{} {} image to '{}'", info.width, info.height, info.kind, path),
+            Err(error) => println!("There was an error, couldn't save the image to '{}', {}", path, error)
+        },
+        Err(msg) => println!("{}", style(Color::Red).paint(
+            format!("The program didn't print a valid image, {}. Nothing was saved", msg)))
+    }
+}
+
+// "bench [files] [*iterations=N]", benchmarks the given programs,
+// or all the bundled benchmark programs if no files are given. Returns false if they couldn't be run
+fn run_bench_command(args: &[String]) -> bool {
+    let mut iterations = 5;
+    let mut paths = Vec::new();
+    for arg in args {
+        if let Some(value) = arg.strip_prefix("*iterations=") {
+            iterations = match value.parse() {
+                Ok(value) => value,
+                Err(_) => {
+                    log_error("invalid_modifier", "", format!("'iterations' modifier expected a number, got '{}'", value));
+                    return false;
+                }
+            };
+        }else if let Some(modifier) = arg.strip_prefix('*') {
+            log_error("invalid_modifier", "", unknown_modifier(modifier.split('=').next().unwrap(), BENCH_MODIFIERS));
+            return false;
+        }else{
+            paths.push(arg.clone());
+        }
+    }
+
+    if paths.is_empty() {
+        paths = match bench::bundled_programs() {
+            Ok(value) => value,
+            Err(error) => {
+                log_error("io", bench::BENCH_PROGRAM_DIR, format!("Couldn't list '{}', {}", bench::BENCH_PROGRAM_DIR, error));
+                return false;
+            }
+        };
+    }
+
+    let std_data = read_source("std.bf").ok();
+
+    println!("{:<16} {:>12} {:>12} {:>12}  output", "program", "compile", "fastest run", "mean run");
+    for path in &paths {
+        match bench::bench_file(path, std_data.as_deref(), iterations) {
+            Ok(result) => {
+                let status = match result.output_matches {
+                    Some(true) => style(Color::Green).paint("ok"),
+                    Some(false) => style(Color::Red).paint("MISMATCH"),
+                    None => style(Color::Cyan).paint("unchecked")
+                };
+
+                println!("{:<16} {:>12.2?} {:>12.2?} {:>12.2?}  {}",
+                    result.name, result.compile_time, result.fastest_run(), result.mean_run(), status);
+            },
+            Err(error) => println!("{:<16} {}", path, style(Color::Red).paint(error))
+        }
+    }
+
+    true
+}
+
+// Returns true if every test passed
+fn run_test_command(args: &[String]) -> bool {
+    // How many percent slower a benchmark can get before it fails
+    let mut threshold = 10.0;
+    let mut save_bench = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        if let Some(value) = arg.strip_prefix("*bench_threshold=") {
+            threshold = match value.parse() {
+                Ok(value) => value,
+                Err(_) => {
+                    log_error("invalid_modifier", "", format!("'bench_threshold' modifier expected a number of percent, got '{}'", value));
+                    return false;
+                }
+            };
+        }else if arg == "*save_bench" {
+            save_bench = true;
+        }else if let Some(modifier) = arg.strip_prefix('*') {
+            log_error("invalid_modifier", "", unknown_modifier(modifier.split('=').next().unwrap(), TEST_MODIFIERS));
+            return false;
+        }else{
+            paths.push(arg.clone());
+        }
+    }
+
+    if paths.is_empty() {
+        println!("Expected the paths of the files to test");
+        return false;
+    }
+
+    let std_data = read_source("std.bf").ok();
+
+    let mut passed = 0;
+    let mut failed = 0;
+    for path in &paths {
+        let results = match testing::test_file(path, std_data.as_deref()) {
+            Ok(value) => value,
+            Err(error) => {
+                println!("{}: {}", path, style(Color::Red).paint(error));
+                failed += 1;
+                continue;
+            }
+        };
+
+        for result in results.tests {
+            match result.failure {
+                None => {
+                    println!("{} '{}' at {}", style(Color::Green).paint("ok    "), result.unit, result.case.loc);
+                    passed += 1;
+                },
+                Some(failure) => {
+                    println!("{} '{}' at {}", style(Color::Red).paint("FAILED"), result.unit, result.case.loc);
+                    println!(" | {}", failure);
+                    failed += 1;
+                }
+            }
+        }
+
+        if results.benches.is_empty() {
+            continue;
+        }
+
+        let baseline = match testing::read_baseline(path) {
+            Ok(value) => value,
+            Err(error) => {
+                println!("{}: {}", path, style(Color::Red).paint(error));
+                HashMap::new()
+            }
+        };
+        for bench in &results.benches {
+            if let Some(failure) = &bench.failure {
+                println!("{} '{}'", style(Color::Red).paint("FAILED"), bench.unit);
+                println!(" | {}", failure);
+                failed += 1;
+                continue;
+            }
+
+            let numbers = format!("{} instructions, {:.2?}", bench.instructions, bench.time);
+            match baseline.get(&bench.unit) {
+                Some(&old) => {
+                    let (instructions, time, regressed) = testing::compare_to_baseline(bench, old, threshold);
+                    let label = if regressed { style(Color::Red).paint("SLOWER") } else { style(Color::Green).paint("bench ") };
+                    println!("{} '{}', {}, {:+.1}% instructions and {:+.1}% time from the baseline",
+                        label, bench.unit, numbers, instructions, time);
+                    if regressed { failed += 1 } else { passed += 1 }
+                },
+                None => {
+                    println!("{} '{}', {}, no baseline", style(Color::Cyan).paint("bench "), bench.unit, numbers);
+                    passed += 1;
+                }
+            }
+        }
 
-                    println!("\n{} errors in std, \nwhat are you playing around with the standard library for, fool!?", errors.len());
+        if save_bench {
+            match testing::write_baseline(path, &results.benches) {
+                Ok(()) => println!("Saved the baseline to '{}'", testing::baseline_path(path)),
+                Err(error) => println!("{}", style(Color::Red).paint(error))
+            }
+        }
+    }
+
+    println!("\n{} passed, {} failed", passed, failed);
+    failed == 0
+}
+
+// "obfuscate file [out_file]", writes the program again without comments and with meaningless macro names
+fn run_obfuscate_command(args: &[String]) -> bool {
+    let path = match args.first() {
+        Some(value) => value,
+        None => {
+            println!("Expected the path of the program to obfuscate");
+            return false;
+        }
+    };
+
+    let out_path = match args.get(1) {
+        Some(value) => value.clone(),
+        None => std::path::Path::new(path).with_extension("obfuscated.bf").to_string_lossy().into_owned()
+    };
+
+    let source = match read_source(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return false;
+        }
+    };
+
+    let text = match brainfuck::obfuscate::obfuscate(source, Default::default()) {
+        Ok(value) => value,
+        Err(failure) => {
+            log_compile_failure(path, "std.bf", &failure);
+            return false;
+        }
+    };
+    match std::fs::write(&out_path, text) {
+        Ok(()) => {
+            println!("Wrote '{}'", out_path);
+            true
+        },
+        Err(error) => {
+            log_error("io", &out_path, format!("Couldn't write '{}', {}", out_path, error));
+            false
+        }
+    }
+}
+
+// "bundle file [out_file]", packs a program and the standard library into one file
+fn run_bundle_command(args: &[String]) -> bool {
+    let path = match args.first() {
+        Some(value) => value,
+        None => {
+            println!("Expected the path of the program to bundle");
+            return false;
+        }
+    };
+
+    let out_path = match args.get(1) {
+        Some(value) => value.clone(),
+        None => std::path::Path::new(path).with_extension("bundle").to_string_lossy().into_owned()
+    };
+
+    let source = match read_source(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return false;
+        }
+    };
+
+    let std_lib = match read_source("std.bf") {
+        Ok(value) => Some(value),
+        Err(error) => {
+            diagnostics::warn("std_not_found", Some("std.bf"), format!("Standard library could not be loaded, the bundle won't have one, {}", error));
+            None
+        }
+    };
+
+    let entry = std::path::Path::new(path).file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.clone());
+    let std_hash = std_lib.as_ref().map(|std_lib| bundle::hash(std_lib));
+    let text = bundle::write_bundle(&Bundle { entry, source, std_lib });
+    if let Err(error) = std::fs::write(&out_path, text) {
+        log_error("io", &out_path, format!("Couldn't write '{}', {}", out_path, error));
+        return false;
+    }
+
+    match std_hash {
+        Some(hash) => println!("Bundled '{}' with the standard library {:016X} into '{}'", path, hash, out_path),
+        None => println!("Bundled '{}' into '{}'", path, out_path)
+    }
+    true
+}
+
+// "examples", lists the example programs that come with the executable.
+// "examples extract name [file]" writes one of them to name.bf, or to the file,
+// and "examples run name [modifiers]" runs one of them.
+fn run_examples_command(args: &[String]) {
+    match args.first().map(|arg| &arg[..]) {
+        None | Some("list") => {
+            for example in EXAMPLES {
+                println!("{} {}", style(Color::Cyan).paint(format!("{:<12}", example.name)), example.description);
+            }
+            println!("\nRun one with 'examples run name', or get the code with 'examples extract name'");
+        },
+        Some("extract") => {
+            let example = match args.get(1).ok_or_else(|| String::from("Expected the name of the example to extract"))
+                    .and_then(|name| find_example(name)) {
+                Ok(value) => value,
+                Err(error) => {
+                    println!("{}", error);
                     return;
                 }
-            }else{
-                println!("WARNING: Standard library could not be loaded");
+            };
+
+            let out_path = match args.get(2) {
+                Some(value) => value.clone(),
+                None => format!("{}.bf", example.name)
+            };
+
+            if std::path::Path::new(&out_path).exists() {
+                println!("There was an error, '{}' already exists, so it wasn't overwritten", out_path);
+                return;
             }
 
-            compiler.finish_compilation().expect("Invalid compilation");
-            
-            if!compiler.is_done() {
-                compiler.log_unresolved_dependencies();
+            match std::fs::write(&out_path, example.source) {
+                Ok(()) => println!("Wrote the '{}' example to '{}'", example.name, out_path),
+                Err(error) => println!("There was an error, couldn't write '{}', {}", out_path, error)
+            }
+        },
+        Some("run") => {
+            if args.len() < 2 {
+                println!("Expected the name of the example to run");
                 return;
             }
 
-            compiler.get_compiled_value("src").expect("Didn't compile! :(")
+            let (name, modifiers) = match read_command_line_args(&args[1..]) {
+                Some(value) => value,
+                None => return
+            };
+            let example = match find_example(name) {
+                Ok(value) => value,
+                Err(error) => {
+                    println!("{}", error);
+                    return;
+                }
+            };
+            fun::set_enabled(!modifiers.serious);
+
+            let path = format!("{}.bf", example.name);
+            let std_in = match modifiers.read_inputs() {
+                Ok(value) => value,
+                Err(error) => {
+                    log_error("io", &path, error);
+                    return;
+                }
+            };
+            match compile_program(example.source.chars().collect(), read_std_lib(), modifiers.compile_options.clone()) {
+                Ok(program) => run_program(&path, &program, &modifiers, &std_in),
+                Err(failure) => log_compile_failure(&path, "std.bf", &failure)
+            }
+        },
+        Some(other) => println!("Unknown examples command '{}', expected list, extract or run", other)
+    }
+}
+
+// "compdb file [modifiers]", writes a json description of every unit in the program to file.compdb.json
+fn run_compdb_command(args: &[String]) -> bool {
+    if args.is_empty() {
+        println!("Expected the path of the program");
+        return false;
+    }
+
+    let (path, modifiers) = match read_command_line_args(args) {
+        Some(value) => value,
+        None => return false
+    };
+    let source = match read_source(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return false;
+        }
+    };
+
+    let compiler = match compile_units(source, read_std_lib(), modifiers.compile_options.clone()) {
+        Ok((compiler, _)) => compiler,
+        Err(failure) => {
+            log_compile_failure(path, "std.bf", &failure);
+            return false;
         }
     };
 
-    if let Some(path) = &modifiers.save_bin {
-        write_bin_to_file(&path[..], &data)
-            .expect("Invalid write bin to file");
+    let program = compiler.link("src").expect("The program compiled but couldn't be linked");
+    let out_path = std::path::Path::new(path).with_extension("compdb.json");
+    let text = compile_database(&compiler, &program, modifiers.compile_options.clone(), path, "std.bf");
+    match std::fs::write(&out_path, text) {
+        Ok(()) => {
+            println!("Wrote '{}'", out_path.display());
+            true
+        },
+        Err(error) => {
+            log_error("io", path, format!("Couldn't write '{}', {}", out_path.display(), error));
+            false
+        }
+    }
+}
+
+// "rename file src/old src/new [modifiers]", renames a macro and everything that uses it, in place
+fn run_rename_command(args: &[String]) -> bool {
+    if args.len() < 3 {
+        println!("Expected the path of the program, the macro to rename and its new name, like 'rename foo.bf src/old src/new'");
+        return false;
     }
 
-    if modifiers.print_bin {
-        print_bf_bin(&data);
+    let mut rest = vec![args[0].clone()];
+    rest.extend_from_slice(&args[3..]);
+    let (path, modifiers) = match read_command_line_args(&rest) {
+        Some(value) => value,
+        None => return false
+    };
+    let source = match read_source(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return false;
+        }
+    };
+
+    let (text, changed) = match brainfuck::rename::rename(source, &args[1], &args[2], modifiers.compile_options.clone()) {
+        Ok(value) => value,
+        Err(failure) => {
+            log_compile_failure(path, "std.bf", &failure);
+            return false;
+        }
+    };
+
+    match std::fs::write(path, text) {
+        Ok(()) => {
+            println!("Renamed '{}' to '{}', {} {} changed", args[1], args[2], changed, if changed == 1 { "place" } else { "places" });
+            true
+        },
+        Err(error) => {
+            println!("There was an error, couldn't write '{}', {}", path, error);
+            false
+        }
+    }
+}
+
+// "metrics file [modifiers]", prints how big and complicated every macro in the program is
+fn run_metrics_command(args: &[String]) {
+    if args.is_empty() {
+        println!("Expected the path of the program");
+        return;
+    }
+
+    let (path, modifiers) = match read_command_line_args(args) {
+        Some(value) => value,
+        None => return
+    };
+    let source = match read_source(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return;
+        }
+    };
+
+    let (compiler, pragmas) = match compile_units(source, read_std_lib(), modifiers.compile_options.clone()) {
+        Ok(value) => value,
+        Err(failure) => {
+            log_compile_failure(path, "std.bf", &failure);
+            return;
+        }
+    };
+
+    let metrics = unit_metrics(&compiler);
+    let width = metrics.iter().map(|unit| unit.name.len()).max().unwrap_or(0).max(5);
+    println!("{:<width$} {:>8} {:>8} {:>32} {:>14}", "macro", "tokens", "nesting", "pointer span", "compiled size", width = width);
+    for unit in &metrics {
+        let mut span = match unit.pointer_span {
+            Some((lowest, highest)) => format!("{}..{} ({} cells)", lowest, highest, highest - lowest + 1),
+            None => String::from("unbounded")
+        };
+        if let Some(cells) = unit.footprint {
+            span.push_str(&format!(", says {} cells", cells));
+        }
+        println!("{:<width$} {:>8} {:>8} {:>32} {:>14}",
+            unit.name, unit.tokens, unit.loop_nesting, span, unit.compiled_size, width = width);
+    }
+
+    // How much *opt changes the whole program, since it works on the linked code and not on macros
+    if modifiers.opt_level > 0 {
+        if let Some(program) = link_program(&compiler, pragmas, "src") {
+            let optimized = optimize_with_pipeline(&program, modifiers.opt_level, &modifiers.pipeline().unwrap());
+            println!();
+            println!("The program is {} bytes, {} bytes with *opt={}, {} loops unrolled",
+                program.code.len(), optimized.program.code.len(), modifiers.opt_level, optimized.count(Pass::Unroll));
+        }
+    }
+}
+
+// "advise file [modifiers]", compiles and runs the program, and suggests what would make it smaller or faster
+fn run_advise_command(args: &[String]) {
+    if args.is_empty() {
+        println!("Expected the path of the program");
+        return;
+    }
+
+    let (path, modifiers) = match read_command_line_args(args) {
+        Some(value) => value,
+        None => return
+    };
+    let source = match read_source(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return;
+        }
+    };
+
+    let (compiler, pragmas) = match compile_units(source, read_std_lib(), modifiers.compile_options.clone()) {
+        Ok(value) => value,
+        Err(failure) => {
+            log_compile_failure(path, "std.bf", &failure);
+            return;
+        }
+    };
+    let (linked, program) = match (compiler.link("src"), link_program(&compiler, pragmas, "src")) {
+        (Some(linked), Some(program)) => (linked, program),
+        _ => {
+            log_error("invalid_program", path, String::from("Didn't compile! :("));
+            return;
+        }
+    };
+
+    print_advice("Size", &size_advice(&compiler, &linked));
+
+    let std_in = match modifiers.read_inputs() {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return;
+        }
+    };
+    let (result, stats) = execute_with_stats(&program, &modifiers, &std_in);
+    if let Err(error) = result {
+        println!("{}", style(Color::Red).paint(format!("The run stopped early, so the speed advice is only about the part that ran: {}", error)));
     }
-    execute_bf(&data, &modifiers);
+    print_advice("Speed", &speed_advice(&program, &stats));
 }
 
-fn is_binary(file_name: &str) -> std::io::Result<bool> {
-    use std::io::prelude::*;
-    use std::fs::File;
+// "bisect-opt file [modifiers]", finds the fewest optimizer changes that make the program behave differently
+fn run_bisect_opt_command(args: &[String]) {
+    if args.is_empty() {
+        println!("Expected the path of the program");
+        return;
+    }
+
+    let (path, modifiers) = match read_command_line_args(args) {
+        Some(value) => value,
+        None => return
+    };
+    let source = match read_source(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return;
+        }
+    };
+    let std_in = match modifiers.read_inputs() {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, error);
+            return;
+        }
+    };
+
+    let program = match compile_program(source, read_std_lib(), modifiers.compile_options.clone()) {
+        Ok(value) => value,
+        Err(failure) => {
+            log_compile_failure(path, "std.bf", &failure);
+            return;
+        }
+    };
 
-    let mut file = File::open(file_name)?;
-    let mut data = [0u8; 4];
-    if file.read_exact(&mut data).is_err() {
-        return Ok(false);
+    let level = modifiers.opt_level.max(1);
+    let bisection = match bisect_optimizations(&program, &modifiers.newline.translate_input(&std_in), modifiers.limits(&program.pragmas), level, &modifiers.pipeline().unwrap()) {
+        Some(value) => value,
+        None => {
+            println!("{}", style(Color::Green).paint(format!("The program behaves the same with *opt={}, there's nothing to bisect", level)));
+            return;
+        }
+    };
+
+    println!("{}", style(Color::Red).paint(format!("The program behaves differently with *opt={}! {}", level, bisection.divergence)));
+    println!("It takes these changes:");
+    for pass in &bisection.passes {
+        let last = pass.last_site
+            .map(|offset| program.symbols.as_ref()
+                .and_then(|symbols| symbols.describe(offset))
+                .unwrap_or_else(|| format!("at instruction {:X}", offset)))
+            .unwrap_or_else(|| String::from("nowhere"));
+        println!(" | {}: the first {} of {} changes, the last one is {}", pass.pass.name(), pass.needed, pass.total, last);
     }
+}
 
-    Ok( data[0] == 0xBF &&
-        data[1] == 0xFF &&
-        data[2] == 0xBB &&
-        data[3] == 0xFF)
+fn print_advice(title: &str, advice: &[Advice]) {
+    println!("{}", style(Color::Cyan.bold()).paint(format!("{}:", title)));
+    if advice.is_empty() {
+        println!(" | Nothing to suggest");
+    }
+    for advice in advice {
+        println!(" | {}: {}", advice.place, advice.message);
+    }
 }
 
-fn read_bin_from_file(file_name: &str) -> std::io::Result<Vec<u8>> {
-    use std::io::prelude::*;
-    use std::fs::File;
+// "std-lib file", compiles the standard library into a library binary for *lib
+fn run_std_lib_command(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("Expected the path to write the library to, like 'std.bin'");
+            return;
+        }
+    };
 
-    let mut file = File::open(file_name)?;
-    let mut data = Vec::new();
-    file.read_to_end(&mut data)?;
-    file.flush()?;
+    let std_lib = match read_source("std.bf") {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", "std.bf", error);
+            return;
+        }
+    };
 
-    let real_data = Vec::from(&data[4..]);
-    Ok(real_data)
+    let (_, modifiers) = match read_command_line_args(args) {
+        Some(value) => value,
+        None => return
+    };
+    let library = match compile_library(std_lib, modifiers.compile_options.clone()) {
+        Ok(value) => value,
+        Err(failure) => {
+            log_compile_failure(path, "std.bf", &failure);
+            return;
+        }
+    };
+
+    match write_bin_to_file(path, &library) {
+        Ok(()) => println!("Wrote '{}', {} macros in {} bytes", path, library.exports.len(), library.code.len()),
+        Err(error) => log_error("io", path, error.to_string())
+    }
 }
 
-fn write_bin_to_file(file_name: &str, data: &Vec<u8>) -> std::io::Result<()> {
-    use std::io::prelude::*;
-    use std::fs::File;
+// "info [files]", prints what's in the header of binaries without running them
+fn run_info_command(paths: &[String]) -> bool {
+    if paths.is_empty() {
+        println!("Expected the paths of the binaries to look at");
+        return false;
+    }
 
-    let mut file = File::create(file_name)?;
-    file.write(&[0xBF, 0xFF, 0xBB, 0xFF])?;
-    file.write(&data[..])?;
+    // Every file is looked at, even if some of them can't be read
+    let mut succeeded = true;
+    for path in paths {
+        let (version, program) = match detect_file_kind(path) {
+            Ok(FileKind::Binary) => match read_versioned_bin(path) {
+                Ok(value) => value,
+                Err(error) => {
+                    log_error("invalid_binary", path, format!("{}: {}", path, error));
+                    succeeded = false;
+                    continue;
+                }
+            },
+            Ok(FileKind::Source) => {
+                log_error("invalid_binary", path, format!("{}: not a binary, it looks like source code", path));
+                succeeded = false;
+                continue;
+            },
+            Err(error) => {
+                log_error("io", path, format!("{}: {}", path, error));
+                succeeded = false;
+                continue;
+            }
+        };
 
-    Ok(())
+        // The instructions that aren't plain brainfuck
+        let mut debug_dumps = 0;
+        let mut assertions = 0;
+        let mut introspections = 0;
+        let mut index = 0;
+        while index < program.code.len() {
+            use instructions::*;
+            match program.code[index] {
+                DEBUG | DEBUG_OP => debug_dumps += 1,
+                ASSERT => assertions += 1,
+                INTROSPECT => introspections += 1,
+                _ => {}
+            }
+            index += len(program.code[index]);
+        }
+
+        let tape = match program.pragmas.tape {
+            Tape::Infinite => String::from("infinite"),
+            Tape::Fixed(length) => format!("fixed, {} cells", length)
+        };
+        let labels: Vec<&str> = program.labels.iter().map(|label| &label.name[..]).collect();
+
+        println!("{}", path);
+        println!(" | format version: {}", version);
+        println!(" | cells:          {} bits", program.pragmas.cell_bits);
+        println!(" | tape:           {}", tape);
+        if let Some(steps) = program.pragmas.max_steps {
+            println!(" | max steps:      {}", steps);
+        }
+        if let Some(cells) = program.pragmas.max_memory {
+            println!(" | max memory:     {} cells", cells);
+        }
+        println!(" | code:           {} bytes", program.code.len());
+        println!(" | debug dumps:    {}", debug_dumps);
+        println!(" | assertions:     {}", assertions);
+        if introspections > 0 {
+            println!(" | introspections: {}", introspections);
+        }
+        println!(" | labels:         {} {}", labels.len(), labels.join(", "));
+        if !program.exports.is_empty() {
+            println!(" | exports:        {}", program.exports.len());
+        }
+        if !program.imports.is_empty() {
+            let mut imports: Vec<&str> = program.imports.iter().map(|(_, name)| &name[..]).collect();
+            imports.sort();
+            imports.dedup();
+            println!(" | imports:        {} {}", imports.len(), imports.join(", "));
+        }
+    }
+
+    succeeded
 }
 
-fn print_bf_bin(bf: &Vec<u8>) {
-    let mut text = String::new();
-    let mut index = 0;
-
-    while index < bf.len() {
-        use instructions::*;
-        match bf[index] {
-            DEBUG => {},
-            SHIFT_LEFT => text.push('<'),
-            SHIFT_RIGHT => text.push('>'),
-            INCREMENT => text.push('+'),
-            DECREMENT => text.push('-'),
-            PRINT => text.push('.'),
-            READ => text.push(','),
-            LOOP_OPEN => {
-                text.push('[');
-                index += 4;
+// "exec script.tfkrepl", runs the commands in a script, returns true if none of them failed
+fn run_exec_command(args: &[String]) -> bool {
+    let path = match args.first() {
+        Some(value) => value,
+        None => {
+            println!("Expected the path of the script to run");
+            return false;
+        }
+    };
+
+    let text = match std::fs::read_to_string(path) {
+        Ok(value) => value,
+        Err(error) => {
+            log_error("io", path, format!("Couldn't read '{}', {}", path, error));
+            return false;
+        }
+    };
+
+    let std_lib = read_std_lib();
+    let result = run_script(&text, std_lib.as_deref());
+    for line in &result.log {
+        println!("{}", line);
+    }
+
+    match result.failure {
+        Some((line, error)) => {
+            println!("{} at line {} of '{}'", style(Color::Red).paint("FAILED"), line, path);
+            println!(" | {}", error);
+            false
+        },
+        None => {
+            println!("{}", style(Color::Green).paint(format!("Ran {} commands", result.commands)));
+            true
+        }
+    }
+}
+
+// "repl [*record] [*replay=file]", runs the commands of a script as they're typed in, until the
+// input ends. *record writes the session to a file that 'exec' can run again, with what the
+// program printed turned into 'assert_output's, so it can go in a bug report or be a test.
+// *replay runs the commands of a file like that first, and goes on from where it left off
+fn run_repl_command(args: &[String]) -> bool {
+    let mut record = false;
+    let mut replay = None;
+    for arg in args {
+        if arg == "*record" {
+            record = true;
+        }else if let Some(path) = arg.strip_prefix("*replay=") {
+            replay = Some(path);
+        }else{
+            log_error("invalid_modifier", "", format!("Invalid modifier for repl, '{}'", arg));
+            return false;
+        }
+    }
+
+    let replayed = match replay {
+        Some(path) => match std::fs::read_to_string(path) {
+            Ok(value) => value,
+            Err(error) => {
+                log_error("io", path, format!("Couldn't read '{}', {}", path, error));
+                return false;
+            }
+        },
+        None => String::new()
+    };
+
+    let mut transcript = None;
+    if record {
+        let seconds = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |time| time.as_secs());
+        let path = format!("session-{}.tfkrepl", seconds);
+        match File::create(&path) {
+            Ok(mut file) => {
+                let header = format!("; Recorded with 'repl *record' {} seconds after 1970, run it again with 'exec {}'\n", seconds, path);
+                if let Err(error) = std::io::Write::write_all(&mut file, header.as_bytes()) {
+                    log_error("io", &path, format!("Couldn't write to '{}', {}", path, error));
+                    return false;
+                }
+                println!("Recording the session to '{}'", path);
+                transcript = Some((file, path));
             },
-            LOOP_CLOSE => {
-                text.push(']');
-                index += 4;
+            Err(error) => {
+                log_error("io", &path, format!("Couldn't create '{}', {}", path, error));
+                return false;
+            }
+        }
+    }
+
+    let std_lib = read_std_lib();
+    let mut session = Session::new(std_lib.as_deref());
+    for line in replayed.lines() {
+        println!("> {}", line);
+        if !repl_line(&mut session, line, &mut transcript, true) {
+            return false;
+        }
+    }
+
+    let mut line = String::new();
+    loop {
+        if std::io::stdin().is_terminal() {
+            print!("> ");
+        }
+        line.clear();
+        match std::io::Write::flush(&mut std::io::stdout()).and_then(|_| std::io::stdin().read_line(&mut line)) {
+            Ok(0) => return true,
+            Ok(_) => {},
+            Err(error) => {
+                log_error("io", "", format!("Couldn't read the next command, {}", error));
+                return false;
+            }
+        }
+
+        if !repl_line(&mut session, line.trim_end_matches(['\r', '\n']), &mut transcript, false) {
+            return false;
+        }
+    }
+}
+
+// Runs a line of the repl, and writes it to the transcript if there is one, with an 'assert_output'
+// for what the program printed and what the command printed as comments. Lines that are 'replayed'
+// from a transcript have those after them already.
+// Returns false if the transcript couldn't be written to
+fn repl_line(session: &mut Session, line: &str, transcript: &mut Option<(File, String)>, replayed: bool) -> bool {
+    let mut recorded = vec![String::from(line)];
+    if let Some((command, argument)) = script::parse_line(line) {
+        let result = session.run_command(command, argument);
+        let printed = String::from_utf8_lossy(&session.take_printed()).into_owned();
+        if !printed.is_empty() {
+            println!("{}", printed);
+        }
+
+        match result {
+            Ok(()) => {
+                if !replayed && !printed.is_empty() {
+                    // Checked right away, so what the program prints next is checked on its own,
+                    // the same as when the transcript is run again
+                    let check = format!("assert_output {}", script::escape(&printed));
+                    if let Some((command, argument)) = script::parse_line(&check) {
+                        let _ = session.run_command(command, argument);
+                    }
+                    recorded.push(check);
+                }
             },
-            _ => panic!("print_bf_bin got invalid bf binary")
+            Err(error) => {
+                // A command that failed would stop the transcript, so it's only kept as a comment
+                println!("{}", style(Color::Red).paint(format!("ERROR: {}", error)));
+                recorded = vec![format!("; {}", line), format!(";  ERROR: {}", error)];
+            }
+        }
+
+        for logged in session.take_log() {
+            println!("{}", logged);
+            if !replayed {
+                recorded.push(format!("; {}", logged));
+            }
+        }
+    }
+
+    if let Some((file, path)) = transcript {
+        let text: String = recorded.iter().map(|line| format!("{}\n", line)).collect();
+        if let Err(error) = std::io::Write::write_all(file, text.as_bytes()) {
+            log_error("io", path, format!("Couldn't write to '{}', {}", path, error));
+            return false;
+        }
+    }
+    true
+}
+
+// "trace-diff a.trace b.trace", returns true if the traces are the same
+fn run_trace_diff_command(paths: &[String]) -> bool {
+    if paths.len() != 2 {
+        println!("Expected the paths of two traces to compare");
+        return false;
+    }
+
+    let traces = read_trace(&paths[0]).and_then(|a| read_trace(&paths[1]).map(|b| (a, b)));
+    let (a, b) = match traces {
+        Ok(value) => value,
+        Err(error) => {
+            println!("There was an error, {}", error);
+            return false;
+        }
+    };
+
+    match diff_traces(&a, &b) {
+        None => {
+            println!("{}", style(Color::Green).paint(format!("The traces are the same, {} steps", a.steps.len())));
+            true
+        },
+        Some(divergence) => {
+            println!("{}", style(Color::Red).paint(divergence));
+            false
+        }
+    }
+}
+
+fn log_compile_failure(path: &str, std_path: &str, failure: &CompileFailure) {
+    let diagnostics = failure.diagnostics(path, std_path);
+    for diagnostic in &diagnostics {
+        diagnostics::count(diagnostic.severity, diagnostic.file.as_deref());
+    }
+
+    if diagnostics::is_json() {
+        for diagnostic in diagnostics {
+            println!("{}", diagnostic.to_json());
         }
+        return;
+    }
+
+    match failure {
+        CompileFailure::Source(errors) => {
+            for error in errors {
+                println!("{}", error);
+            }
+        },
+        CompileFailure::Std(std_errors, errors) => {
+            for error in std_errors {
+                println!("{}", error);
+            }
+            println!("What are you playing around with the standard library for, fool!?");
 
-        index += 1;
+            for error in errors {
+                println!("{}", error);
+            }
+        },
+        CompileFailure::Unresolved(compiler) => compiler.log_unresolved_dependencies(),
+        CompileFailure::Invalid(msg) => println!("Invalid compilation, {}", msg)
     }
+}
 
-    println!("Bin: {}", text);
+// What *self_profile found out about the phases of the compiler that ran
+fn print_self_profile() {
+    println!("{}", style(Color::Cyan).paint("Compiler phases:"));
+    let phases = self_profile::take_phases();
+    let total: std::time::Duration = phases.iter().map(|phase| phase.time).sum();
+    for phase in &phases {
+        println!(" | {:<10} {:>10.2?} {:>5.1}%, {} allocated, {} at most at once", phase.name, phase.time,
+            phase.time.as_secs_f64() * 100.0 / total.as_secs_f64().max(f64::MIN_POSITIVE),
+            self_profile::format_bytes(phase.allocated), self_profile::format_bytes(phase.peak));
+    }
+    println!(" | {:<10} {:>10.2?}", "total", total);
+}
+
+fn print_bf_bin(program: &Program, target: &Target) {
+    match plain_bf_for(program, target) {
+        Ok((text, _)) => println!("Bin: {}", text),
+        Err(msg) => println!("Bin: can't be written as brainfuck, {}", msg)
+    }
 }
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use crate::{ Compiler, Error };
+use crate::interner::Symbol;
+use crate::instructions::*;
+
+// How big and how complicated every macro of a program is, for "metrics"
+pub struct UnitMetrics {
+    pub name: String,
+    // How many tokens it has after repeats are expanded
+    pub tokens: usize,
+    // How deep the loops in its compiled code go, with the macros it uses
+    pub loop_nesting: usize,
+    // The lowest and highest cell it can touch, relative to where the memory pointer was when it started.
+    // None if it has a loop that doesn't end where it started, like "[>]", since then it could go anywhere
+    pub pointer_span: Option<(isize, isize)>,
+    // How many cells it says it uses, from ":name uses 10 cells { ... }"
+    pub footprint: Option<usize>,
+    pub compiled_size: usize
+}
+
+// How some code moves the memory pointer, and how deep its loops go
+#[derive(Clone, Copy)]
+struct Shape {
+    // Where the pointer ends up, and the lowest and highest cell it touches on the way
+    span: Option<(isize, isize, isize)>,
+    nesting: usize
+}
+
+// The metrics of every compiled unit in the program, not counting the standard library,
+// biggest first
+pub fn unit_metrics(compiler: &Compiler) -> Vec<UnitMetrics> {
+    let mut shapes = HashMap::new();
+    let mut metrics: Vec<UnitMetrics> = compiler.units().into_iter()
+        .filter(|unit| unit.name.split('/').next() != Some(crate::STD_ROOT))
+        .filter_map(|unit| {
+            let compiled_size = unit.compiled_size?;
+            let shape = shape_of(compiler, unit.id, &mut shapes);
+            Some(UnitMetrics {
+                name: unit.name,
+                tokens: unit.tokens,
+                loop_nesting: shape.nesting,
+                pointer_span: shape.span.map(|(_, lowest, highest)| (lowest, highest)),
+                footprint: unit.footprint,
+                compiled_size
+            })
+        })
+        .collect();
+
+    metrics.sort_by(|a, b| b.compiled_size.cmp(&a.compiled_size).then_with(|| a.name.cmp(&b.name)));
+    metrics
+}
+
+// The macros that say how many cells they use, with ":name uses 10 cells { ... }", and can touch
+// a cell outside of them, or could since they have a loop that doesn't end where it started.
+// The cells are the one the macro starts on and the ones to the right of it, so macros that say
+// how many cells they use can be put next to each other without stepping on each other
+pub fn check_footprints(compiler: &Compiler) -> Vec<Error> {
+    let mut shapes = HashMap::new();
+    compiler.units().into_iter()
+        .filter(|unit| unit.compiled_size.is_some())
+        .filter_map(|unit| {
+            let cells = unit.footprint?;
+            let problem = match shape_of(compiler, unit.id, &mut shapes).span {
+                None => String::from("it has a loop that doesn't end where it started, so it could go anywhere"),
+                Some((_, lowest, _)) if lowest < 0 => format!("it can touch the cell {:X} to the left of where it starts", -lowest),
+                Some((_, _, highest)) if highest >= cells as isize => format!("it can touch {:X} cells", highest + 1),
+                _ => return None
+            };
+            Some(Error::new(unit.defined_at, format!("'{}' says it uses {:X} cells, but {}", unit.name, cells, problem)))
+        })
+        .collect()
+}
+
+// The CALLs to noinline macros go by the shape of the macro, which is worked out once
+fn shape_of(compiler: &Compiler, id: Symbol, shapes: &mut HashMap<Symbol, Shape>) -> Shape {
+    if let Some(shape) = shapes.get(&id) {
+        return *shape;
+    }
+
+    let unit = compiler.get_compiled_value(&compiler.name_of(id)).expect("The unit wasn't compiled");
+    let mut offset = 0isize;
+    let mut lowest = 0isize;
+    let mut highest = 0isize;
+    let mut bounded = true;
+    let mut loop_offsets = Vec::new();
+    let mut nesting = 0;
+
+    let mut index = 0;
+    while index < unit.code.len() {
+        match unit.code[index] {
+            SHIFT_RIGHT => offset += 1,
+            SHIFT_LEFT => offset -= 1,
+            LOOP_OPEN | LOOP_OPEN_WIDE => {
+                loop_offsets.push(offset);
+                nesting = nesting.max(loop_offsets.len());
+            },
+            LOOP_CLOSE | LOOP_CLOSE_WIDE if loop_offsets.pop() != Some(offset) => bounded = false,
+            CALL => {
+                let target = unit.calls.iter().find(|call| call.0 == index).expect("A CALL that goes nowhere").1;
+                let shape = shape_of(compiler, target, shapes);
+                nesting = nesting.max(loop_offsets.len() + shape.nesting);
+                match shape.span {
+                    Some((end, low, high)) => {
+                        lowest = lowest.min(offset + low);
+                        highest = highest.max(offset + high);
+                        offset += end;
+                    },
+                    None => bounded = false
+                }
+            },
+            _ => {}
+        }
+
+        lowest = lowest.min(offset);
+        highest = highest.max(offset);
+        index += len(unit.code[index]);
+    }
+
+    let shape = Shape {
+        span: if bounded { Some((offset, lowest, highest)) } else { None },
+        nesting
+    };
+    shapes.insert(id, shape);
+    shape
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use crate::{ Compiler, CompileFailure, CompileOptions, Lexer };
+use crate::interner::Symbol;
+use crate::pretty::write_source;
+
+// "obfuscate file" writes the program again without its comments, tests and formatting, with every
+// macro moved to the top level and given a short name that doesn't say anything about it.
+// Repeats and plain brainfuck come out expanded, since that's all the lexer keeps of them.
+// The macros of the standard library keep their names, the program is compiled with it after all
+pub fn obfuscate(text: Vec<char>, options: CompileOptions) -> Result<String, CompileFailure> {
+    let compiler = Compiler::with_options(options);
+    let mut lexer = Lexer::new(text);
+    lexer.tokenize(&[String::from("src")], &compiler, false).map_err(CompileFailure::Source)?;
+
+    // Named in the order they're defined in, the root is the code at the top level and goes last
+    let mut units = compiler.units();
+    units.sort_by_key(|unit| (unit.name == "src", unit.defined_at.index()));
+    let mut names: HashMap<Symbol, String> = HashMap::new();
+    let mut next_name = 0;
+    for unit in units.iter().filter(|unit| unit.name != "src") {
+        let name = loop {
+            let name = short_name(next_name);
+            next_name += 1;
+            if !matches!(&name[..], "use" | "pragma" | "std" | "inline" | "noinline" | "uses") {
+                break name;
+            }
+        };
+        names.insert(unit.id, name);
+    }
+
+    let name_of = |symbol: Symbol| match names.get(&symbol) {
+        Some(name) => format!("src/{}", name),
+        None => compiler.name_of(symbol)
+    };
+
+    let mut lines = lexer.pragmas().declarations();
+    for unit in &units {
+        let code = write_source(&compiler.tokens_of(unit.id).unwrap_or_default(), &name_of);
+        let body = if code.is_empty() { String::from("{ }") } else { format!("{{ {} }}", code) };
+
+        match names.get(&unit.id) {
+            Some(name) => {
+                let footprint = unit.footprint.map_or(String::new(), |cells| format!(" uses {:X} cells", cells));
+                lines.push(format!(":{}{}{} {}", name, if unit.noinline { " noinline" } else { "" }, footprint, body));
+            },
+            None if !code.is_empty() => lines.push(code),
+            None => {}
+        }
+    }
+
+    lines.push(String::new());
+    Ok(lines.join("\n"))
+}
+
+// "a" to "z", then "aa", "ab" and so on
+fn short_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.insert(0, b'a' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    String::from_utf8(name).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compile_program;
+
+    #[test]
+    fn obfuscated_programs_compile_to_the_same_code() {
+        let text = "#pragma cells 16\n; A comment\n:outer { :inner { +3 0\"a\tb\" } #/inner ( #/inner > )2 } \
+            #src/outer ?{ @>1[ - ] }:{ =[ @<2*3 - FF - FF + @<2*FF ] %[ 'a-'z: @-20 '0: 1 _: 2 ] } #std/char/is_digit !~2";
+        let std_lib = Some(include_str!("../std.bf").chars().collect::<Vec<_>>());
+        let obfuscated = obfuscate(text.chars().collect(), CompileOptions::default())
+            .unwrap_or_else(|_| panic!("Couldn't obfuscate"));
+        assert!(!obfuscated.contains("outer") && !obfuscated.contains("comment"));
+
+        let original = compile_program(text.chars().collect(), std_lib.clone(), CompileOptions::default())
+            .unwrap_or_else(|_| panic!("{} didn't compile", text));
+        let compiled = compile_program(obfuscated.chars().collect(), std_lib, CompileOptions::default())
+            .unwrap_or_else(|_| panic!("{} didn't compile", obfuscated));
+        assert_eq!(original.code, compiled.code);
+        assert_eq!(original.pragmas, compiled.pragmas);
+
+        // Where noinline macros end up depends on their names, so only check that they stay noinline
+        let obfuscated = obfuscate(":twice noinline { ++ } #src/twice".chars().collect(), CompileOptions::default())
+            .unwrap_or_else(|_| panic!("Couldn't obfuscate"));
+        assert_eq!(obfuscated, ":a noinline { + + }\n#src/a\n");
+    }
+}
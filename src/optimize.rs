@@ -0,0 +1,459 @@
+use crate::instructions::*;
+use crate::binary::{ Program, Label };
+use crate::compiler::{ open_loop, close_loop };
+use crate::pragma::{ Pragmas, Tape };
+
+// A program after optimizing, along with where every instruction came from
+pub struct Optimized {
+    pub program: Program,
+    // For every byte of the optimized code, the offset of the instruction
+    // in the original code that it came from
+    pub origins: Vec<usize>,
+    // Every change the passes made, in order, with the offset in the original code it was made at
+    pub sites: Vec<(Pass, usize)>,
+    // How many changes every custom pass made, in the order they ran
+    pub custom: Vec<(String, usize)>
+}
+
+impl Optimized {
+    pub fn origin_of(&self, offset: usize) -> Option<usize> {
+        self.origins.get(offset).cloned()
+    }
+
+    // How many changes a pass made
+    pub fn count(&self, pass: Pass) -> usize {
+        self.sites.iter().filter(|(site_pass, _)| *site_pass == pass).count()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pass {
+    // "+-" and "<>" cancelling out
+    Cancel,
+    // Loops that can never run being removed
+    DeadLoops,
+    // Loops that run a known number of times being replaced by copies of their body
+    Unroll
+}
+
+impl Pass {
+    pub const ALL: [Pass; 3] = [Pass::Cancel, Pass::DeadLoops, Pass::Unroll];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Pass::Cancel => "cancel",
+            Pass::DeadLoops => "dead_loops",
+            Pass::Unroll => "unroll"
+        }
+    }
+}
+
+// How many changes every pass is allowed to make, None for as many as it wants.
+// The changes are made in the order of the code, so a limit of N makes the first N
+// of them. Used to find which change a miscompilation comes from, see verify::bisect_optimizations
+#[derive(Clone, Copy)]
+pub struct PassLimits([Option<usize>; Pass::ALL.len()]);
+
+impl PassLimits {
+    pub fn unlimited() -> PassLimits {
+        PassLimits([None; Pass::ALL.len()])
+    }
+
+    pub fn get(&self, pass: Pass) -> Option<usize> {
+        self.0[pass as usize]
+    }
+
+    pub fn set(&mut self, pass: Pass, limit: Option<usize>) {
+        self.0[pass as usize] = limit;
+    }
+}
+
+// The instructions the passes work on, as (instruction, offset in the unoptimized code it came from).
+// Instructions with operands, like ASSERT, take them from the unoptimized code at that offset, except
+// the loops, which are matched up with each other again once all the passes are done
+pub struct Ir<'a> {
+    pub original: &'a [u8],
+    pub pragmas: Pragmas,
+    pub instructions: Vec<(u8, usize)>
+}
+
+// An optimization from outside of the optimizer, for trying out new ones without changing it.
+// Custom passes run after the built in ones, on the instructions they left. They can move, remove
+// and add instructions as they want, as long as the loops stay balanced
+pub trait CustomPass {
+    fn name(&self) -> &str;
+    // Returns how many changes it made
+    fn run(&self, ir: &mut Ir) -> usize;
+}
+
+// Which passes the optimizer runs. The built in passes always run together, in one go through
+// the code, so only the custom passes have an order
+pub struct Pipeline {
+    pub limits: PassLimits,
+    pub custom: Vec<Box<dyn CustomPass>>
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pipeline {
+    // Every built in pass, and no custom ones
+    pub fn new() -> Pipeline {
+        Pipeline { limits: PassLimits::unlimited(), custom: Vec::new() }
+    }
+
+    pub fn register(&mut self, pass: Box<dyn CustomPass>) {
+        self.custom.push(pass);
+    }
+
+    // Only runs the passes that are in the list, built in or custom, with the custom ones in
+    // the order of the list, ex: "*passes=cancel,unroll"
+    pub fn select(&mut self, names: &[&str]) -> Result<(), String> {
+        for name in names {
+            if !Pass::ALL.iter().any(|pass| pass.name() == *name) && !self.custom.iter().any(|pass| pass.name() == *name) {
+                let mut known: Vec<&str> = Pass::ALL.iter().map(|pass| pass.name()).collect();
+                known.extend(self.custom.iter().map(|pass| pass.name()));
+                return Err(format!("Unknown optimizer pass '{}', the passes are {}", name, known.join(", ")));
+            }
+        }
+
+        for pass in Pass::ALL.iter() {
+            if !names.contains(&pass.name()) {
+                self.limits.set(*pass, Some(0));
+            }
+        }
+
+        let mut custom = std::mem::take(&mut self.custom);
+        for name in names {
+            if let Some(index) = custom.iter().position(|pass| pass.name() == *name) {
+                self.custom.push(custom.remove(index));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Keeps track of the changes that are made, so the passes stop at their limits
+struct Sites<'a> {
+    limits: &'a PassLimits,
+    counts: [usize; Pass::ALL.len()],
+    found: Vec<(Pass, usize)>
+}
+
+impl Sites<'_> {
+    // Whether the pass can make a change at 'offset', which is counted if it can
+    fn allow(&mut self, pass: Pass, offset: usize) -> bool {
+        if self.limits.get(pass).is_some_and(|limit| self.counts[pass as usize] >= limit) {
+            return false;
+        }
+
+        self.counts[pass as usize] += 1;
+        self.found.push((pass, offset));
+        true
+    }
+}
+
+fn cancels(a: u8, b: u8, shifts: bool) -> bool {
+    match (a, b) {
+        (INCREMENT, DECREMENT) | (DECREMENT, INCREMENT) => true,
+        (SHIFT_LEFT, SHIFT_RIGHT) | (SHIFT_RIGHT, SHIFT_LEFT) => shifts,
+        _ => false
+    }
+}
+
+// Finds the offset of the instruction after the loop that starts at 'start'
+fn skip_loop(code: &[u8], start: usize) -> usize {
+    start + loop_offset(code, start)
+}
+
+// How big the copies of an unrolled loop's body can get in total, for *opt=2 and *opt=3 and up.
+// Small limits keep the program about the same size, and only get rid of the jumps
+fn unroll_limit(level: u8) -> usize {
+    match level {
+        0 | 1 => 0,
+        2 => 64,
+        _ => 1024
+    }
+}
+
+// If the loop at 'start' runs a number of times that's known when the current cell is 'value'
+// when it's entered, how many times that is and the instructions of its body. Only loops
+// without loops inside them, that move back to where they started and don't read into or
+// print from the counter cell, can be figured out
+fn trip_count(code: &[u8], start: usize, value: i64, cell_bits: u8) -> Option<(u64, Vec<(u8, usize)>)> {
+    let mut body = Vec::new();
+    let mut offset = 0i64;
+    let mut change = 0i64;
+    let mut index = start + len(code[start]);
+    loop {
+        let instruction = code[index];
+        match instruction {
+            SHIFT_RIGHT => offset += 1,
+            SHIFT_LEFT => offset -= 1,
+            INCREMENT if offset == 0 => change += 1,
+            DECREMENT if offset == 0 => change -= 1,
+            INCREMENT | DECREMENT | PRINT => {},
+            READ if offset != 0 => {},
+            LOOP_CLOSE | LOOP_CLOSE_WIDE => break,
+            _ => return None
+        }
+        body.push((instruction, index));
+        index += len(instruction);
+    }
+
+    if offset != 0 || change == 0 {
+        return None;
+    }
+
+    // The counter wraps around like any other cell, so counting up gets to zero too
+    let modulus = 1i128 << cell_bits;
+    let value = (value as i128).rem_euclid(modulus);
+    let distance = if change < 0 { value } else { (modulus - value) % modulus };
+    let step = change.unsigned_abs() as i128;
+    if distance % step != 0 {
+        return None;
+    }
+
+    Some(((distance / step) as u64, body))
+}
+
+fn has_calls(code: &[u8]) -> bool {
+    let mut index = 0;
+    while index < code.len() {
+        if code[index] == CALL || code[index] == RET {
+            return true;
+        }
+        index += len(code[index]);
+    }
+
+    false
+}
+
+// Peephole optimizations on the bytecode, the instruction set stays the same.
+//  * Increments and decrements, and shifts left and right, next to each other cancel out.
+//    Shifts are only cancelled on an infinite tape, since "<>" at the edge of a
+//    fixed tape is an error that shouldn't just disappear.
+//  * Loops at the very start of the program, or right after another loop, never run,
+//    since the current cell is always zero there.
+//  * With level 2 and up, loops that run a known number of times, because the cell was set to
+//    a number right before them, like "[-]+5[->+<]", are replaced by that many copies of their
+//    body, as long as that doesn't make the program much bigger. Higher levels allow more growth.
+// Debug dumps are never removed unless they are in a loop that never runs.
+// Programs with calls are left alone, since moving code around would break the call offsets.
+pub fn optimize(program: &Program, level: u8) -> Optimized {
+    optimize_with_limits(program, level, &PassLimits::unlimited())
+}
+
+pub fn optimize_with_limits(program: &Program, level: u8, limits: &PassLimits) -> Optimized {
+    run_passes(program, level, limits, &[])
+}
+
+pub fn optimize_with_pipeline(program: &Program, level: u8, pipeline: &Pipeline) -> Optimized {
+    run_passes(program, level, &pipeline.limits, &pipeline.custom)
+}
+
+// Runs the built in passes with 'limits' and then the custom passes, for when the limits change
+// but the custom passes don't, like when bisecting
+pub fn run_passes(program: &Program, level: u8, limits: &PassLimits, custom: &[Box<dyn CustomPass>]) -> Optimized {
+    let code = &program.code[..];
+    if has_calls(code) {
+        return Optimized {
+            program: Program {
+                pragmas: program.pragmas,
+                code: code.to_vec(),
+                labels: program.labels.clone(),
+                symbols: program.symbols.clone(),
+                exports: program.exports.clone(),
+                imports: program.imports.clone()
+            },
+            origins: (0..code.len()).collect(),
+            sites: Vec::new(),
+            custom: Vec::new()
+        };
+    }
+
+    let cancel_shifts = program.pragmas.tape == Tape::Infinite;
+    let unroll_limit = unroll_limit(level);
+
+    // The instructions that are kept, as (instruction, original offset)
+    let mut kept: Vec<(u8, usize)> = Vec::with_capacity(code.len());
+    // What the current cell is, if it's known. It's zero after a loop, and at the start
+    let mut known = Some(0i64);
+    let mut sites = Sites { limits, counts: [0; Pass::ALL.len()], found: Vec::new() };
+    let mut index = 0;
+    while index < code.len() {
+        let instruction = code[index];
+        if is_loop_open(instruction) && kept.last().is_none_or(|last| is_loop_close(last.0))
+                && sites.allow(Pass::DeadLoops, index) {
+            index = skip_loop(code, index);
+            continue;
+        }
+
+        if let (true, Some(value)) = (is_loop_open(instruction), known) {
+            let unrolled = trip_count(code, index, value, program.pragmas.cell_bits)
+                .filter(|(trips, body)| {
+                    let size: usize = body.iter().map(|(instruction, _)| len(*instruction)).sum();
+                    trips.saturating_mul(size as u64) <= unroll_limit as u64
+                });
+            if let Some((trips, body)) = unrolled.filter(|_| sites.allow(Pass::Unroll, index)) {
+                for _ in 0..trips {
+                    for &(instruction, origin) in &body {
+                        keep(&mut kept, instruction, origin, cancel_shifts, &mut sites);
+                    }
+                }
+                known = Some(0);
+                index = skip_loop(code, index);
+                continue;
+            }
+        }
+
+        known = match (instruction, known) {
+            (INCREMENT, Some(value)) => Some(value + 1),
+            (DECREMENT, Some(value)) => Some(value - 1),
+            (LOOP_CLOSE, _) | (LOOP_CLOSE_WIDE, _) => Some(0),
+            (PRINT, known) => known,
+            _ => None
+        };
+        keep(&mut kept, instruction, index, cancel_shifts, &mut sites);
+        index += len(instruction);
+    }
+
+    let mut ir = Ir { original: code, pragmas: program.pragmas, instructions: kept };
+    let mut custom_counts = Vec::with_capacity(custom.len());
+    for pass in custom {
+        let changes = pass.run(&mut ir);
+        if !balanced(&ir.instructions) {
+            panic!("The optimizer pass '{}' left the loops unbalanced", pass.name());
+        }
+        custom_counts.push((String::from(pass.name()), changes));
+    }
+
+    let mut optimized = Vec::with_capacity(code.len());
+    let mut origins = Vec::with_capacity(code.len());
+    let mut loop_starts = Vec::new();
+    for (instruction, origin) in ir.instructions {
+        match instruction {
+            LOOP_OPEN | LOOP_OPEN_WIDE => loop_starts.push(open_loop(&mut optimized)),
+            LOOP_CLOSE | LOOP_CLOSE_WIDE => {
+                let start = loop_starts.pop().expect("optimize: unbalanced loops");
+                if close_loop(&mut optimized, start) {
+                    // The body moved further in, the new bytes belong to the loop open
+                    let open_origin = origins[start];
+                    origins.splice(start..start, std::iter::repeat_n(open_origin, len(LOOP_OPEN_WIDE) - len(LOOP_OPEN)));
+                }
+            },
+            _ => optimized.extend_from_slice(&code[origin..origin + len(instruction)])
+        }
+
+        origins.resize(optimized.len(), origin);
+    }
+
+    let mut labels = Vec::with_capacity(program.labels.len());
+    for (offset, origin) in origins.iter().enumerate() {
+        if optimized[offset] == DEBUG {
+            if let Some(name) = program.label_at(*origin) {
+                labels.push(Label::new(offset, String::from(name)));
+            }
+        }
+    }
+
+    let mut optimized = Program::new(program.pragmas, optimized);
+    optimized.labels = labels;
+    optimized.symbols = program.symbols.as_ref().map(|symbols| symbols.remap(&origins));
+
+    Optimized {
+        program: optimized,
+        origins,
+        sites: sites.found,
+        custom: custom_counts
+    }
+}
+
+fn balanced(instructions: &[(u8, usize)]) -> bool {
+    let mut depth = 0usize;
+    for (instruction, _) in instructions {
+        if is_loop_open(*instruction) {
+            depth += 1;
+        }else if is_loop_close(*instruction) {
+            match depth.checked_sub(1) {
+                Some(value) => depth = value,
+                None => return false
+            }
+        }
+    }
+    depth == 0
+}
+
+fn keep(kept: &mut Vec<(u8, usize)>, instruction: u8, origin: usize, cancel_shifts: bool, sites: &mut Sites) {
+    match kept.last() {
+        Some(&(last, _)) if cancels(last, instruction, cancel_shifts) && sites.allow(Pass::Cancel, origin) => {
+            kept.pop();
+        },
+        _ => kept.push((instruction, origin))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{ Interpreter, State };
+    use crate::compile_test_program;
+
+    #[test]
+    fn optimizer_unrolls_loops_with_known_trip_counts() {
+        let program = compile_test_program("+5[->+3<] > . [-]- [>+<-] > .");
+
+        let peephole = optimize(&program, 1);
+        assert_eq!(peephole.count(Pass::Unroll), 0);
+        let unrolled = optimize(&program, 2);
+        assert_eq!(unrolled.count(Pass::Unroll), 1);
+
+        // The bisection relies on limits making exactly the first N changes of a pass
+        let mut limits = PassLimits::unlimited();
+        limits.set(Pass::Unroll, Some(0));
+        let limited = optimize_with_limits(&program, 2, &limits);
+        assert_eq!(limited.count(Pass::Unroll), 0);
+        assert_eq!(limited.program.code, peephole.program.code);
+
+        for program in [program, unrolled.program] {
+            let mut interpreter = Interpreter::new(program);
+            assert_eq!(interpreter.run(), &State::Finished);
+            assert_eq!(interpreter.take_output(), vec![15, 255]);
+        }
+    }
+
+    #[test]
+    fn custom_optimizer_passes_run_after_the_built_in_ones() {
+        // Prints every character twice
+        struct Stutter;
+        impl CustomPass for Stutter {
+            fn name(&self) -> &str { "stutter" }
+            fn run(&self, ir: &mut Ir) -> usize {
+                let prints: Vec<usize> = (0..ir.instructions.len())
+                    .filter(|i| ir.instructions[*i].0 == PRINT)
+                    .collect();
+                for i in prints.iter().rev() {
+                    ir.instructions.insert(*i, ir.instructions[*i]);
+                }
+                prints.len()
+            }
+        }
+
+        let text = "+- +41 . + .";
+        let program = compile_test_program(text);
+        let mut pipeline = Pipeline::new();
+        pipeline.register(Box::new(Stutter));
+        assert!(pipeline.select(&["stutter", "nope"]).is_err());
+        pipeline.select(&["stutter", "dead_loops"]).unwrap();
+
+        let optimized = optimize_with_pipeline(&program, 1, &pipeline);
+        assert_eq!(optimized.count(Pass::Cancel), 0);
+        assert_eq!(optimized.custom, vec![(String::from("stutter"), 2)]);
+        let mut interpreter = Interpreter::new(optimized.program);
+        interpreter.run();
+        assert_eq!(interpreter.take_output(), b"AABB");
+    }
+}
@@ -1,21 +1,54 @@
-use std::collections::HashMap;
+use std::collections::{ HashMap, VecDeque };
 use crate::Compiler;
 use crate::Error;
+use crate::interner::Symbol;
+use crate::testing::TestCase;
+use crate::pragma::Pragmas;
+use crate::compiler::MAX_EXPANDED_TOKENS;
 
 #[derive(Debug, Clone)]
 pub enum TokenType {
     Str(String, bool),
-    Macro(String),
+    Macro(Symbol),
     Loop(Vec<Token>),
+    // "?{ then }:{ else }", the else part is optional
+    IfElse(Vec<Token>, Vec<Token>),
+    // "@>3[ ... ]", a loop that checks the cell at an offset from where it starts
+    LoopAt(isize, Vec<Token>),
+    // "=[ @>1 + @>2*3 - 7 ]", sets the current cell to a sum of cells times constants, plus a constant.
+    // The terms are (offset of cell, how many times to add it)
+    Assign(Vec<(isize, i64)>, i64),
+    // "%[ '0-'9: 1  _: 0 ]", maps the current cell through a table that's worked out when compiling.
+    // The value of '_' is for the keys that aren't in the table
+    Table(Vec<TableEntry>, TableValue),
     Increment(u8),
     Decrement(u8),
     ShiftRight(u8),
     ShiftLeft(u8),
     Print,
     Read,
-    Debug
+    // A debug dump, optionally with a label naming it, ex: "!loop_start"
+    Debug(Option<String>),
+    // "!=XX", checks that the current cell has a value when running
+    Assert(u8),
+    // "!~N", "!#N", "!%N" or "!?", the kind of debug operation and its argument
+    DebugOp(u8, u8),
+    // "$p", "$f", "$r" or "$i", which fact about the interpreter to write into the current cell
+    Introspect(u8)
 }
 
+// What a key of a table turns into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableValue {
+    // A number, ex: "'a: 1"
+    Set(u8),
+    // "@+N" or "@-N", the key plus a number, ex: "'a-'z: @-20"
+    Add(i64)
+}
+
+// The first key, the last key, and what they turn into
+pub type TableEntry = (u8, u8, TableValue);
+
 #[derive(Debug, Clone)]
 pub struct Token {
     pub src_loc: Loc,
@@ -23,13 +56,34 @@ pub struct Token {
 }
 
 impl Token {
-    pub fn new_debug(loc: Loc) -> Token {
+    pub fn new_debug(loc: Loc, label: Option<String>) -> Token {
         Token {
             src_loc: loc,
-            data: TokenType::Debug
+            data: TokenType::Debug(label)
         }
     } 
 
+    pub fn new_assert(loc: Loc, value: u8) -> Token {
+        Token {
+            src_loc: loc,
+            data: TokenType::Assert(value)
+        }
+    }
+
+    pub fn new_debug_op(loc: Loc, kind: u8, argument: u8) -> Token {
+        Token {
+            src_loc: loc,
+            data: TokenType::DebugOp(kind, argument)
+        }
+    }
+
+    pub fn new_introspect(loc: Loc, kind: u8) -> Token {
+        Token {
+            src_loc: loc,
+            data: TokenType::Introspect(kind)
+        }
+    }
+
     pub fn new_str(loc: Loc, data: String, is_safe: bool) -> Token {
         Token {
             src_loc: loc,
@@ -37,7 +91,7 @@ impl Token {
         }
     }
 
-    pub fn new_macro(loc: Loc, identifier: String) -> Token {
+    pub fn new_macro(loc: Loc, identifier: Symbol) -> Token {
         Token {
             src_loc: loc,
             data: TokenType::Macro(identifier)
@@ -51,6 +105,34 @@ impl Token {
         }
     }
 
+    pub fn new_if_else(loc: Loc, then: Vec<Token>, other: Vec<Token>) -> Token {
+        Token {
+            src_loc: loc,
+            data: TokenType::IfElse(then, other)
+        }
+    }
+
+    pub fn new_loop_at(loc: Loc, offset: isize, sub_tokens: Vec<Token>) -> Token {
+        Token {
+            src_loc: loc,
+            data: TokenType::LoopAt(offset, sub_tokens)
+        }
+    }
+
+    pub fn new_assign(loc: Loc, terms: Vec<(isize, i64)>, constant: i64) -> Token {
+        Token {
+            src_loc: loc,
+            data: TokenType::Assign(terms, constant)
+        }
+    }
+
+    pub fn new_table(loc: Loc, entries: Vec<TableEntry>, default: TableValue) -> Token {
+        Token {
+            src_loc: loc,
+            data: TokenType::Table(entries, default)
+        }
+    }
+
     pub fn new_increment(loc: Loc, n_times: u8) -> Token {
         Token {
             src_loc: loc,
@@ -102,6 +184,20 @@ pub struct Loc {
 }
 
 impl Loc {
+    // The line, starting at 1 like when displaying it
+    pub fn line(&self) -> usize {
+        self.line + 1
+    }
+
+    pub fn column(&self) -> usize {
+        self._char
+    }
+
+    // How many characters into the file it is
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
     pub fn zero() -> Loc {
         Loc {
             line: 0,
@@ -141,9 +237,16 @@ impl std::fmt::Display for Loc {
 
 struct LexerContext {
     errors: Vec<Error>,
-    dependencies: HashMap<String, Vec<Loc>>,
+    dependencies: HashMap<Symbol, Vec<Loc>>,
     commands: Vec<Token>,
-    path: Vec<String>
+    path: Vec<String>,
+    tests: Vec<TestCase>,
+    // The input declared by ";@input" for the next ";@expect"
+    test_input: Option<(Loc, String)>,
+    // How many tokens the repeat blocks in the unit have expanded to
+    expanded: usize,
+    // Where the '`' blocks start, for *deny
+    raw_blocks: Vec<Loc>
 }
 
 impl LexerContext {
@@ -152,16 +255,16 @@ impl LexerContext {
             errors: Vec::new(),
             dependencies: HashMap::new(),
             commands: Vec::new(),
-            path: path
+            path,
+            tests: Vec::new(),
+            test_input: None,
+            expanded: 0,
+            raw_blocks: Vec::new()
         }
     }
 
-    fn add_dependency(&mut self, string: &str, location: Loc) {
-        if let Some(list) = self.dependencies.get_mut(string) {
-            list.push(location);
-        }else{
-            self.dependencies.insert(String::from(string), vec![location]);
-        }
+    fn add_dependency(&mut self, symbol: Symbol, location: Loc) {
+        self.dependencies.entry(symbol).or_default().push(location);
     }
 
     fn add_error(&mut self, loc: Loc, message: String) {
@@ -169,18 +272,66 @@ impl LexerContext {
     }
 }
 
+// A macro name written in the file, for tools that rewrite names, like "rename"
+pub struct Name {
+    // Where the name starts, and how it's written there
+    pub loc: Loc,
+    pub written: String,
+    // The full path it's the name of
+    pub path: String,
+    pub kind: NameKind
+}
+
+pub enum NameKind {
+    // ":name { }"
+    Definition,
+    // "#name"
+    Use,
+    // "#use name", with the path of the macro it imports it as
+    Import(String)
+}
+
 pub struct Lexer {
     text: Vec<char>,
     loc: Loc,
-    n_invalid_macro_names: usize
+    n_invalid_macro_names: usize,
+    pragmas: Pragmas,
+    declared_pragmas: HashMap<&'static str, Loc>,
+    // Where the first "=[ ]" is, the numbers in expressions depend on the cell size
+    first_expression: Option<Loc>,
+    names: Vec<Name>
 }
 
 impl Lexer {
     pub fn new(text: Vec<char>) -> Lexer {
         Lexer {
-            text: text,
+            text,
             loc: Loc::zero(),
-            n_invalid_macro_names: 0
+            n_invalid_macro_names: 0,
+            pragmas: Pragmas::new(),
+            declared_pragmas: HashMap::new(),
+            first_expression: None,
+            names: Vec::new()
+        }
+    }
+
+    // Every macro name written in the part of the file that has been lexed so far
+    pub fn names(&self) -> &[Name] {
+        &self.names
+    }
+
+    // The pragmas declared in the file, or the defaults for the ones that weren't
+    pub fn pragmas(&self) -> Pragmas {
+        self.pragmas
+    }
+
+    // The tokens of the file one at a time, without compiling anything
+    pub fn into_tokens(self) -> Tokens {
+        Tokens {
+            lexer: self,
+            compiler: Compiler::new(),
+            path: vec![String::from("src")],
+            pending: VecDeque::new()
         }
     }
 
@@ -198,7 +349,7 @@ impl Lexer {
         let mut identifier = String::new();
         
         while let Some(c) = self.text.get(self.loc.index) {
-            if c.is_alphabetic() || (identifier.len() >= 1 && c.is_numeric()) || *c == '/' || *c == '_' || *c == '.' {
+            if c.is_alphabetic() || (!identifier.is_empty() && c.is_numeric()) || *c == '/' || *c == '_' || *c == '.' {
                 self.loc.add_n_chars(1);
                 identifier.push(*c);
             }else{
@@ -206,13 +357,33 @@ impl Lexer {
             }
         }
 
-        if identifier.len() == 0 {
+        if identifier.is_empty() {
             None
         }else{
             Some(identifier)
         }
     }
 
+    // Labels are stricter than identifiers, since "!." is a debug dump followed by a print
+    fn read_label(&mut self) -> Option<String> {
+        let mut label = String::new();
+
+        while let Some(c) = self.text.get(self.loc.index) {
+            if c.is_alphabetic() || *c == '_' || (!label.is_empty() && c.is_numeric()) {
+                self.loc.add_n_chars(1);
+                label.push(*c);
+            }else{
+                break;
+            }
+        }
+
+        if label.is_empty() {
+            None
+        }else{
+            Some(label)
+        }
+    }
+
     fn parse_char(&mut self, context: &mut LexerContext) -> Option<char> {
         if let Some(c) = self.text.get(self.loc.index) {
             self.loc.move_with(*c);
@@ -245,12 +416,368 @@ impl Lexer {
         }
     }
 
+    // Reads the arguments of a pragma, which go on until the end of the line or a comment
+    fn parse_pragma(&mut self, context: &mut LexerContext, start: Loc) {
+        let mut line = String::new();
+        while let Some(c) = self.text.get(self.loc.index) {
+            if *c == '\n' || *c == ';' {
+                break;
+            }
+
+            line.push(*c);
+            self.loc.move_with(*c);
+        }
+
+        let args: Vec<&str> = line.split_whitespace().collect();
+        match self.pragmas.apply(&args) {
+            Ok(name) => {
+                if let Some(previous) = self.declared_pragmas.insert(name, start) {
+                    context.add_error(start, 
+                        format!("Pragma '{}' was already declared at {}", name, previous));
+                }
+                if let (Some(expression), "cells") = (self.first_expression, name) {
+                    context.add_error(start, format!("The cell size has to be declared before the first \
+                        expression at {}, the numbers in it were worked out for the cell size before it", expression));
+                }
+            },
+            Err(msg) => context.add_error(start, msg)
+        }
+    }
+
+    // Parses the values inside of a block like "{ ... }", the opening character has to be next
+    fn parse_block(&mut self, compiler: &Compiler, context: &mut LexerContext, open: char, close: char)
+            -> Option<Vec<Token>> {
+        let start = self.loc;
+        if self.text.get(self.loc.index) == Some(&open) {
+            self.loc.add_n_chars(1);
+        }else{
+            context.add_error(start, format!("Expected '{}'", open));
+            return None;
+        }
+
+        let contents_start = context.commands.len();
+        while let Some(c) = self.text.get(self.loc.index) {
+            if *c == close {
+                self.loc.add_n_chars(1);
+                return Some(context.commands.split_off(contents_start));
+            }else{
+                self.parse_value(compiler, context);
+            }
+        }
+
+        context.add_error(start, format!("Expected '{}' to end the block", close));
+        None
+    }
+
+    // Reads a cell reference after the '@', ex: "@", "@>3" or "@<'a"
+    fn parse_cell_offset(&mut self, context: &mut LexerContext) -> Option<isize> {
+        let direction = match self.text.get(self.loc.index) {
+            Some('>') => 1,
+            Some('<') => -1,
+            _ => return Some(0)
+        };
+        self.loc.add_n_chars(1);
+
+        match self.try_parse_number(context) {
+            Some(value) => Some(value as isize * direction),
+            None => {
+                context.add_error(self.loc, String::from("Expected the offset of the cell"));
+                None
+            }
+        }
+    }
+
+    // Parses the inside of "=[ ... ]", a sum of terms where every term is a product
+    // of numbers and at most one cell, ex: "@>1 + 3*@>2 - 'a".
+    // Cells wrap around, so every number is reduced to the cell size declared so far while it's
+    // worked out, and ends up between minus and plus half of the cell size. That way nothing
+    // overflows, and no term takes more than half a cell of increments or decrements to add
+    fn parse_expression(&mut self, context: &mut LexerContext) -> Option<(Vec<(isize, i64)>, i64)> {
+        let modulus = self.pragmas.cell_mask() as u64 + 1;
+        self.first_expression.get_or_insert(self.loc);
+
+        let mut terms: Vec<(isize, u64)> = Vec::new();
+        let mut constant = 0u64;
+
+        let mut negative = false;
+        let mut expecting_factor = true;
+        let mut factor = 1u64;
+        let mut cell = None;
+        loop {
+            self.skip_whitespace();
+            let loc = self.loc;
+            let c = match self.text.get(self.loc.index) {
+                Some(c) => *c,
+                None => {
+                    context.add_error(loc, String::from("Expected ']' to end the expression"));
+                    return None;
+                }
+            };
+
+            if expecting_factor {
+                if c == '-' && factor == 1 && cell.is_none() {
+                    self.loc.add_n_chars(1);
+                    negative = !negative;
+                    continue;
+                }
+
+                if c == '@' {
+                    self.loc.add_n_chars(1);
+                    if cell.is_some() {
+                        context.add_error(loc, String::from("Can't multiply two cells together"));
+                        return None;
+                    }
+                    cell = Some(self.parse_cell_offset(context)?);
+                }else{
+                    match self.try_parse_number(context) {
+                        // Both are below 2^32, so the product fits
+                        Some(value) => factor = factor * value as u64 % modulus,
+                        None => {
+                            context.add_error(loc, String::from("Expected a number or a cell"));
+                            return None;
+                        }
+                    }
+                }
+
+                expecting_factor = false;
+                continue;
+            }
+
+            self.loc.add_n_chars(1);
+            match c {
+                '*' => expecting_factor = true,
+                '+' | '-' | ']' => {
+                    let value = if negative { (modulus - factor) % modulus } else { factor };
+                    match cell {
+                        Some(offset) => match terms.iter_mut().find(|term| term.0 == offset) {
+                            Some(term) => term.1 = (term.1 + value) % modulus,
+                            None => terms.push((offset, value))
+                        },
+                        None => constant = (constant + value) % modulus
+                    }
+
+                    if c == ']' {
+                        let smallest = |value: u64| if value > modulus / 2 { value as i64 - modulus as i64 } else { value as i64 };
+                        let terms = terms.into_iter()
+                            .filter(|term| term.1 != 0)
+                            .map(|(offset, times)| (offset, smallest(times)))
+                            .collect();
+                        return Some((terms, smallest(constant)));
+                    }
+
+                    negative = c == '-';
+                    factor = 1;
+                    cell = None;
+                    expecting_factor = true;
+                },
+                _ => {
+                    context.add_error(loc, format!("Unexpected '{}' in expression", c));
+                    return None;
+                }
+            }
+        }
+    }
+
+    // Parses the inside of "%[ ... ]", entries like "'a: 1", "'a-'z: @-20" or "_: @",
+    // separated by whitespace. Keys can't be in the table twice
+    fn parse_table(&mut self, context: &mut LexerContext) -> Option<(Vec<TableEntry>, TableValue)> {
+        let mut entries: Vec<TableEntry> = Vec::new();
+        let mut default = None;
+        loop {
+            self.skip_whitespace();
+            let loc = self.loc;
+            let c = match self.text.get(self.loc.index) {
+                Some(c) => *c,
+                None => {
+                    context.add_error(loc, String::from("Expected ']' to end the table"));
+                    return None;
+                }
+            };
+
+            if c == ']' {
+                self.loc.add_n_chars(1);
+                return Some((entries, default.unwrap_or(TableValue::Set(0))));
+            }
+
+            let keys = if c == '_' {
+                self.loc.add_n_chars(1);
+                None
+            }else{
+                let first = match self.try_parse_number(context) {
+                    Some(value) => value,
+                    None => {
+                        context.add_error(loc, String::from("Expected a key, a range of keys or '_'"));
+                        return None;
+                    }
+                };
+
+                let mut last = first;
+                if self.text.get(self.loc.index) == Some(&'-') {
+                    self.loc.add_n_chars(1);
+                    last = match self.try_parse_number(context) {
+                        Some(value) if value >= first => value,
+                        Some(value) => {
+                            context.add_error(loc, format!("The range of keys goes backwards, from {:X} to {:X}", first, value));
+                            return None;
+                        },
+                        None => {
+                            context.add_error(self.loc, String::from("Expected the last key of the range"));
+                            return None;
+                        }
+                    };
+                }
+                Some((first, last))
+            };
+
+            self.skip_whitespace();
+            if self.text.get(self.loc.index) != Some(&':') {
+                context.add_error(self.loc, String::from("Expected ':' after the key"));
+                return None;
+            }
+            self.loc.add_n_chars(1);
+            self.skip_whitespace();
+
+            let value_loc = self.loc;
+            let value = if self.text.get(self.loc.index) == Some(&'@') {
+                self.loc.add_n_chars(1);
+                let sign = match self.text.get(self.loc.index) {
+                    Some('+') => 1,
+                    Some('-') => -1,
+                    _ => 0
+                };
+
+                if sign == 0 {
+                    TableValue::Add(0)
+                }else{
+                    self.loc.add_n_chars(1);
+                    match self.try_parse_number(context) {
+                        Some(value) => TableValue::Add(sign * value as i64),
+                        None => {
+                            context.add_error(self.loc, String::from("Expected the number to add to the key"));
+                            return None;
+                        }
+                    }
+                }
+            }else{
+                match self.try_parse_number(context) {
+                    Some(value) => TableValue::Set(value),
+                    None => {
+                        context.add_error(value_loc, String::from("Expected a number, or '@' for the key itself"));
+                        return None;
+                    }
+                }
+            };
+
+            match keys {
+                Some((first, last)) => {
+                    if let Some(other) = entries.iter().find(|entry| entry.0 <= last && first <= entry.1) {
+                        context.add_error(loc, format!("The keys {:X} to {:X} overlap with {:X} to {:X}, which are already in the table",
+                            first, last, other.0, other.1));
+                        return None;
+                    }
+                    entries.push((first, last, value));
+                },
+                None if default.is_some() => {
+                    context.add_error(loc, String::from("'_' is already in the table"));
+                    return None;
+                },
+                None => default = Some(value)
+            }
+        }
+    }
+
+    // Reads plain brainfuck until the closing '`'. Only the 8 brainfuck commands mean
+    // anything in here, everything else is a comment like in normal brainfuck.
+    fn parse_raw(&mut self, context: &mut LexerContext, start: Loc) {
+        context.raw_blocks.push(start);
+        let mut loop_starts: Vec<(Loc, usize)> = Vec::new();
+        while let Some(c) = self.text.get(self.loc.index) {
+            let loc = self.loc;
+            self.loc.move_with(*c);
+            match *c {
+                '`' => {
+                    for (loop_start, _) in loop_starts {
+                        context.add_error(loop_start, String::from("Expected ']' to end loop before the end of the '`' block"));
+                    }
+                    return;
+                },
+                '+' => context.commands.push(Token::new_increment(loc, 1)),
+                '-' => context.commands.push(Token::new_decrement(loc, 1)),
+                '>' => context.commands.push(Token::new_shift_right(loc, 1)),
+                '<' => context.commands.push(Token::new_shift_left(loc, 1)),
+                '.' => context.commands.push(Token::new_print(loc)),
+                ',' => context.commands.push(Token::new_read(loc)),
+                '[' => loop_starts.push((loc, context.commands.len())),
+                ']' => match loop_starts.pop() {
+                    Some((loop_start, contents_start)) => {
+                        let contents = context.commands.split_off(contents_start);
+                        context.commands.push(Token::new_loop(loop_start, contents));
+                    },
+                    None => context.add_error(loc, String::from("Unexpected ']', there is no loop to end"))
+                },
+                _ => {}
+            }
+        }
+
+        context.add_error(start, String::from("Expected '`' to end the brainfuck block"));
+    }
+
+    // Annotations are comments starting with '@', ";@input" and ";@expect" declare tests.
+    // Other annotations are only warned about, so a comment like ";@todo" still compiles
+    fn parse_annotation(&mut self, compiler: &Compiler, context: &mut LexerContext, start: Loc) {
+        let name = self.read_identifier().unwrap_or_default();
+        if name != "input" && name != "expect" {
+            compiler.add_warning("unknown_annotation", start,
+                format!("Unknown annotation ';@{}', expected 'input' or 'expect', it's ignored", name));
+            return;
+        }
+
+        while let Some(c) = self.text.get(self.loc.index) {
+            if *c == ' ' || *c == '\t' {
+                self.loc.add_n_chars(1);
+            }else{
+                break;
+            }
+        }
+
+        if self.text.get(self.loc.index) != Some(&'"') {
+            context.add_error(self.loc, format!("Expected a string after ';@{}'", name));
+            return;
+        }
+        self.loc.add_n_chars(1);
+
+        let mut contents = String::new();
+        loop {
+            match self.parse_char(context) {
+                Some('"') => break,
+                Some('\n') | None => {
+                    context.add_error(start, String::from("Expected '\"' to end string"));
+                    return;
+                },
+                Some(c) => contents.push(c)
+            }
+        }
+
+        if name == "input" {
+            if let Some((previous, _)) = context.test_input.replace((start, contents)) {
+                context.add_error(previous, String::from("';@input' needs an ';@expect' after it"));
+            }
+        }else{
+            let input = context.test_input.take().map(|(_, input)| input).unwrap_or_default();
+            context.tests.push(TestCase {
+                input,
+                expect: contents,
+                loc: start
+            });
+        }
+    }
+
     fn parse_str(&mut self, context: &mut LexerContext, is_safe: bool) {
         let mut contents = String::new();
         let start = self.loc;
         while let Some(c) = self.parse_char(context) {
             if c == '"' {
-                context.commands.push(Token::new_str(start, String::from(contents), is_safe));
+                context.commands.push(Token::new_str(start, contents, is_safe));
                 return;
             }else{
                 contents.push(c);
@@ -318,10 +845,17 @@ impl Lexer {
             self.loc.move_with(*c);
             match *c {
                 character if character.is_whitespace() => {},
-                '🧙' => {
-                    println!("{}: Ayoyoyo Wololo!", self.loc);
+                c if crate::fun::is_hook(c) => {
+                    if let Some(message) = crate::fun::message(c) {
+                        compiler.add_note(start, String::from(message));
+                    }
                 },
                 ';' => {
+                    if self.text.get(self.loc.index) == Some(&'@') {
+                        self.loc.add_n_chars(1);
+                        self.parse_annotation(compiler, context, start);
+                    }
+
                     while let Some(c) = self.text.get(self.loc.index) {
                         self.loc.move_with(*c);
                         if *c == '\n' {
@@ -329,8 +863,56 @@ impl Lexer {
                         }
                     }
                 },
+                '!' if self.text.get(self.loc.index) == Some(&'=') => {
+                    self.loc.add_n_chars(1);
+                    match self.try_parse_number(context) {
+                        Some(value) => context.commands.push(Token::new_assert(start, value)),
+                        None => context.add_error(self.loc, String::from("Expected the value to assert after '!='"))
+                    }
+                },
+                '!' if self.text.get(self.loc.index) == Some(&'?') => {
+                    self.loc.add_n_chars(1);
+                    context.commands.push(Token::new_debug_op(start, crate::instructions::DEBUG_PAUSE, 0));
+                },
+                '!' if matches!(self.text.get(self.loc.index), Some('~') | Some('#') | Some('%')) => {
+                    use crate::instructions::*;
+                    let symbol = self.text[self.loc.index];
+                    let kind = match symbol {
+                        '~' => DEBUG_WINDOW,
+                        '#' => DEBUG_DUMP,
+                        _ => DEBUG_MARKER
+                    };
+
+                    self.loc.add_n_chars(1);
+                    match self.try_parse_number(context) {
+                        Some(argument) => context.commands.push(Token::new_debug_op(start, kind, argument)),
+                        None => context.add_error(self.loc, format!("Expected a number after '!{}'", symbol))
+                    }
+                },
+                '$' => {
+                    use crate::instructions::*;
+                    let kind = match self.text.get(self.loc.index) {
+                        Some('p') => FACT_POINTER,
+                        Some('f') => FACT_FUEL,
+                        Some('r') => FACT_RANDOM,
+                        Some('i') => FACT_INPUT,
+                        _ => {
+                            context.add_error(start, String::from("Expected 'p', 'f', 'r' or 'i' after '$'"));
+                            return;
+                        }
+                    };
+
+                    self.loc.add_n_chars(1);
+                    if self.pragmas.introspection {
+                        context.commands.push(Token::new_introspect(start, kind));
+                    }else{
+                        context.add_error(start, String::from("'$' needs '#pragma introspection' before it"));
+                    }
+                },
                 '!' => {
-                    context.commands.push(Token::new_debug(self.loc));
+                    let loc = self.loc;
+                    let label = self.read_label();
+                    context.commands.push(Token::new_debug(loc, label));
                 },
                 '#' => {
                     let identifier_start = self.loc;
@@ -357,60 +939,102 @@ impl Lexer {
                             }
                         };
 
-                        if let Err(msg) = pathify_identifier(&context.path, &mut identifier) {
+                        let written = identifier.clone();
+                        let valid = if let Err(msg) = check_identifier(&identifier, compiler.options().unicode_identifiers) {
                             context.add_error(start, msg);
-                        }
+                            false
+                        }else if let Err(msg) = pathify_identifier(&context.path, &mut identifier) {
+                            context.add_error(start, msg);
+                            false
+                        }else{
+                            true
+                        };
 
-                        // Create some strings that are going to be passed into datastructures later
-                        let identifier_dep = String::from(&identifier);
-                        let identifier_token = String::from(&identifier);
+                        let symbol = compiler.intern(&identifier);
 
                         // Figure out the path that the import is going to be set to
-                        let mut name = String::from(identifier.split('/').rev().next().unwrap());
-                        name.insert(0, '/');
-                        name.insert_str(0, &context.path.join("/")[..]);
+                        let name = format!("{}/{}", context.path.join("/"), identifier.split('/').next_back().unwrap());
+                        if valid {
+                            self.names.push(Name { loc: start, written, path: identifier.clone(), kind: NameKind::Import(name.clone()) });
+                        }
                         
                         // Add the macro to the compilers list of things to compile
                         let mut dep = HashMap::with_capacity(1);
-                        dep.insert(identifier_dep, vec![start]);
-                        compiler.add_compilation_unit(
-                                String::from(name), 
-                                vec![Token::new_macro(start, identifier_token)], 
+                        dep.insert(symbol, vec![start]);
+                        let added = compiler.add_compilation_unit(
+                                compiler.intern(&name), 
+                                start,
+                                vec![Token::new_macro(start, symbol)], 
                                 dep
                             );
+                        if let Err(msg) = added {
+                            context.add_error(start, msg);
+                        }
+                    }else if identifier == "pragma" {
+                        self.parse_pragma(context, start);
                     }else{
-                        if let Err(msg) = pathify_identifier(&context.path, &mut identifier) {
+                        let written = identifier.clone();
+                        if let Err(msg) = check_identifier(&identifier, compiler.options().unicode_identifiers) {
+                            context.add_error(identifier_start, msg);
+                        }else if let Err(msg) = pathify_identifier(&context.path, &mut identifier) {
                             context.add_error(start, msg);
+                        }else{
+                            self.names.push(Name { loc: identifier_start, written, path: identifier.clone(), kind: NameKind::Use });
                         }
 
-                        context.add_dependency(&identifier[..], identifier_start);
-                        context.commands.push(Token::new_macro(identifier_start, identifier));
+                        let symbol = compiler.intern(&identifier);
+                        context.add_dependency(symbol, identifier_start);
+                        context.commands.push(Token::new_macro(identifier_start, symbol));
                     }
                 },
                 '(' => {
-                    let start = self.loc;
                     let contents_start = context.commands.len();
                     
                     while let Some(c) = self.text.get(self.loc.index) {
                         if *c == ')' {
+                            let close = self.loc;
                             self.loc.move_with(*c);
 
                             // Get the range of commands in the context that are within the repeat
-                            let mut contents = Vec::with_capacity(context.commands.len() - contents_start);
-                            while context.commands.len() > contents_start {
-                                // .unwrap() is safe since we know the length is larger than 0 
-                                // since contents_start has to be >= 0
-                                contents.insert(0, context.commands.pop().unwrap());
-                            }
+                            let contents = context.commands.split_off(contents_start);
                             
+                            // ")x03" is the same as ")03", but makes it clear where the count is
+                            let explicit = self.text.get(self.loc.index) == Some(&'x');
+                            if explicit {
+                                self.loc.add_n_chars(1);
+                            }
+
                             let count = match self.try_parse_number(context) {
                                 Some(value) => value as usize,
                                 None => {
-                                    context.add_error(self.loc, String::from("Expected number of repitions"));
+                                    let found = match self.text.get(self.loc.index) {
+                                        Some(c) if !c.is_whitespace() => format!(", found '{}'", c),
+                                        _ => String::new()
+                                    };
+                                    let msg = if explicit {
+                                        format!("Expected the number of repetitions after ')x'{}", found)
+                                    }else{
+                                        format!("The repeat block from {} to {} needs the number of repetitions right after \
+                                            the ')'{}, add a count like ')03' or ')x03'", start, close, found)
+                                    };
+                                    context.add_error(close, msg);
                                     return;
                                 }
                             };
 
+                            // Only the first repeat block that goes over the limit is reported
+                            if context.expanded > MAX_EXPANDED_TOKENS {
+                                return;
+                            }
+
+                            context.expanded += count * weight(&contents);
+                            if context.expanded > MAX_EXPANDED_TOKENS {
+                                let msg = format!("The repeat block from {} to {} makes '{}' expand to more than {} tokens",
+                                    start, close, context.path.join("/"), MAX_EXPANDED_TOKENS);
+                                context.add_error(start, msg);
+                                return;
+                            }
+
                             for _ in 0..count {
                                 for content in contents.iter() {
                                     context.commands.push(content.clone());
@@ -424,6 +1048,92 @@ impl Lexer {
 
                     context.add_error(start, String::from("Expected ')' to end the repeat block"));
                 },
+                '?' => {
+                    // If/else, ex: "?{ \"yes\" }:{ \"no\" }"
+                    let then = match self.parse_block(compiler, context, '{', '}') {
+                        Some(value) => value,
+                        None => return
+                    };
+
+                    // A ':' followed by an identifier would be a macro definition,
+                    // so the else part starts with ':' and then '{'
+                    let before_else = self.loc;
+                    self.skip_whitespace();
+                    let mut other = Vec::new();
+                    let mut has_else = false;
+                    if self.text.get(self.loc.index) == Some(&':') {
+                        self.loc.add_n_chars(1);
+                        self.skip_whitespace();
+                        has_else = self.text.get(self.loc.index) == Some(&'{');
+                    }
+
+                    if has_else {
+                        other = match self.parse_block(compiler, context, '{', '}') {
+                            Some(value) => value,
+                            None => return
+                        };
+                    }else{
+                        self.loc = before_else;
+                    }
+
+                    context.commands.push(Token::new_if_else(start, then, other));
+                },
+                '@' => {
+                    // A loop on another cell, ex: "@>3[ ... ]"
+                    let offset = match self.parse_cell_offset(context) {
+                        Some(value) => value,
+                        None => return
+                    };
+
+                    if let Some(contents) = self.parse_block(compiler, context, '[', ']') {
+                        context.commands.push(Token::new_loop_at(start, offset, contents));
+                    }
+                },
+                '=' => {
+                    // Sets the current cell to an expression, ex: "=[ @>1 + @>2*3 - 7 ]"
+                    if self.text.get(self.loc.index) != Some(&'[') {
+                        context.add_error(self.loc, String::from("Expected '[' after '='"));
+                        return;
+                    }
+                    self.loc.add_n_chars(1);
+
+                    match self.parse_expression(context) {
+                        Some((terms, constant)) => context.commands.push(Token::new_assign(start, terms, constant)),
+                        None => {
+                            // Skip the rest of the expression so the ']' isn't reported as well
+                            while let Some(c) = self.text.get(self.loc.index) {
+                                self.loc.move_with(*c);
+                                if *c == ']' {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                },
+                '%' => {
+                    // Maps the current cell through a table, ex: "%[ 'a-'z: @-20  _: @ ]"
+                    if self.text.get(self.loc.index) != Some(&'[') {
+                        context.add_error(self.loc, String::from("Expected '[' after '%'"));
+                        return;
+                    }
+                    self.loc.add_n_chars(1);
+
+                    match self.parse_table(context) {
+                        Some((entries, default)) => context.commands.push(Token::new_table(start, entries, default)),
+                        None => {
+                            // Skip the rest of the table so the ']' isn't reported as well
+                            while let Some(c) = self.text.get(self.loc.index) {
+                                self.loc.move_with(*c);
+                                if *c == ']' {
+                                    break;
+                                }
+                            }
+                        }
+                    }
+                },
+                '`' => {
+                    self.parse_raw(context, start);
+                },
                 '0' => {
                     if let Some(c) = self.text.get(self.loc.index) {
                         if *c == '"' {
@@ -481,62 +1191,132 @@ impl Lexer {
                 ',' => context.commands.push(Token::new_read(self.loc)),
                 '.' => context.commands.push(Token::new_print(self.loc)),
                 _ => {
-                    context.add_error(self.loc, String::from(format!("Unexpected token '{}'", *c)))
+                    context.add_error(self.loc, format!("Unexpected token '{}'", *c))
                 }
             }
         }
     }
 
-    pub fn tokenize(&mut self, name: &Vec<String>, compiler: &Compiler, terminatable: bool)
+    // Parses a macro definition, ":name { ... }", the ':' has to be next.
+    // The definition becomes a compilation unit of its own
+    fn parse_definition(&mut self, name: &[String], compiler: &Compiler, context: &mut LexerContext)
             -> Result<(), Vec<Error>> {
-        let mut context = LexerContext::new(name.clone());
-        
+        self.loc.add_n_chars(1);
 
-        while let Some(c) = self.text.get(self.loc.index) {
-            let start = self.loc;
-            if *c == ':' {
-                self.loc.add_n_chars(1);
+        // A macro definition!
+        let identifier_start = self.loc;
+        let identifier = match self.read_identifier() {
+            Some(value) => value,
+            None => {
+                context.add_error(
+                    identifier_start, 
+                    String::from("Expected an identifier for the macro!"));
+                self.n_invalid_macro_names += 1;
+                "*".repeat(self.n_invalid_macro_names)
+            }
+        };
 
-                // A macro definition!
-                let identifier_start = self.loc;
-                let identifier = match self.read_identifier() {
-                    Some(value) => value,
-                    None => {
-                        context.add_error(
-                            identifier_start, 
-                            String::from("Expected an identifier for the macro!"));
-                        self.n_invalid_macro_names += 1;
-                        "*".repeat(self.n_invalid_macro_names)
-                    }
-                };
+        if identifier.contains("/") {
+            context.add_error(
+                identifier_start, 
+                String::from("Cannot define a macro with '/' in identifier")
+            );
+        }else if identifier.contains('.') {
+            context.add_error(
+                identifier_start,
+                String::from("Cannot define a macro with '.' in identifier, dots are for going up in paths, like '#../name'")
+            );
+        }else if let Err(msg) = check_identifier(&identifier, compiler.options().unicode_identifiers) {
+            context.add_error(identifier_start, msg);
+        }
 
-                if identifier.contains("/") {
-                    context.add_error(
-                        identifier_start, 
-                        String::from("Cannot define a macro with '/' in identifier")
-                    );
-                }
-                self.skip_whitespace();
-                let opening_bracket_loc = self.loc;
-                let c = match self.text.get(self.loc.index) {
-                    Some(value) => value,
-                    None => {
-                        context.add_error(
-                            opening_bracket_loc, 
-                            String::from("Unexpected end of file, expected macro body definition")
-                        );
-                        return Err(context.errors);
+        // "#std/..." always means the standard library, so a top level macro
+        // called std could never be used that way
+        if name.len() == 1 && identifier == crate::STD_ROOT {
+            context.add_error(
+                identifier_start,
+                format!("'{}' is reserved for the standard library", crate::STD_ROOT)
+            );
+        }
+        self.skip_whitespace();
+
+        // ":name noinline {" makes every use of the macro a call instead of a copy, and
+        // ":name uses 10 cells {" says it only touches the 10 (hex) cells from where it starts and to the right
+        let mut noinline = false;
+        let mut footprint = None;
+        loop {
+            let attribute_loc = self.loc;
+            let attribute = match self.read_label() {
+                Some(value) => value,
+                None => break
+            };
+
+            match &attribute[..] {
+                "inline" => noinline = false,
+                "noinline" => noinline = true,
+                "uses" => {
+                    self.skip_whitespace();
+                    let cells = self.try_parse_number(context);
+                    self.skip_whitespace();
+                    match (cells, self.read_label().as_deref()) {
+                        (Some(0), Some("cells")) => context.add_error(attribute_loc,
+                            String::from("A macro uses at least the cell it starts on")),
+                        (Some(cells), Some("cells")) => footprint = Some(cells as usize),
+                        _ => context.add_error(attribute_loc,
+                            String::from("Expected the number of cells the macro uses, like 'uses 10 cells'"))
                     }
-                };
-                
-                if *c != '{' {
-                    context.add_error(opening_bracket_loc, String::from("Expected '{'"));
-                }
-                self.loc.move_with(*c);
+                },
+                _ => context.add_error(attribute_loc,
+                    format!("Unknown macro attribute '{}', expected 'inline', 'noinline' or 'uses'", attribute))
+            }
+            self.skip_whitespace();
+        }
+
+        let opening_bracket_loc = self.loc;
+        let c = match self.text.get(self.loc.index) {
+            Some(value) => value,
+            None => {
+                context.add_error(
+                    opening_bracket_loc, 
+                    String::from("Unexpected end of file, expected macro body definition")
+                );
+                return Err(std::mem::take(&mut context.errors));
+            }
+        };
+        
+        if *c != '{' {
+            context.add_error(opening_bracket_loc, String::from("Expected '{'"));
+        }
+        self.loc.move_with(*c);
 
-                let mut sub_name = name.clone();
-                sub_name.push(identifier);
-                self.tokenize(&sub_name, compiler, true)?;
+        let mut sub_name = name.to_vec();
+        sub_name.push(identifier.clone());
+        self.names.push(Name { loc: identifier_start, written: identifier, path: sub_name.join("/"), kind: NameKind::Definition });
+        if noinline {
+            compiler.set_noinline(compiler.intern(&sub_name.join("/")));
+        }
+        if let Some(cells) = footprint {
+            compiler.set_footprint(compiler.intern(&sub_name.join("/")), cells);
+        }
+        self.tokenize_unit(&sub_name, compiler, true, identifier_start)
+    }
+
+    pub fn tokenize(&mut self, name: &[String], compiler: &Compiler, terminatable: bool)
+            -> Result<(), Vec<Error>> {
+        let defined_at = self.loc;
+        self.tokenize_unit(name, compiler, terminatable, defined_at)
+    }
+
+    // 'defined_at' is where the name of the unit is, or the start of the file
+    fn tokenize_unit(&mut self, name: &[String], compiler: &Compiler, terminatable: bool, defined_at: Loc)
+            -> Result<(), Vec<Error>> {
+        let mut context = LexerContext::new(name.to_vec());
+        
+
+        while let Some(c) = self.text.get(self.loc.index) {
+            let start = self.loc;
+            if *c == ':' {
+                self.parse_definition(name, compiler, &mut context)?;
             }else if *c == '}' {
                 self.loc.add_n_chars(1);
 
@@ -550,38 +1330,239 @@ impl Lexer {
             }
         }
 
-        if context.errors.len() > 0 {
+        let unit_name = name.join("/");
+        let mut features = crate::features::used(&context.commands);
+        features.extend(context.raw_blocks.iter().map(|loc| (*loc, crate::features::Feature::Raw)));
+        for (loc, feature) in features {
+            if let Some(namespace) = compiler.options().features.denied_by(&unit_name, feature) {
+                context.add_error(loc, format!("'{}' isn't allowed to use {}, it's denied in '{}'", unit_name, feature.name(), namespace));
+            }
+        }
+
+        if !context.errors.is_empty() {
+            return Err(context.errors);
+        }
+
+        if let Some((loc, _)) = context.test_input {
+            context.add_error(loc, String::from("';@input' needs an ';@expect' after it"));
             return Err(context.errors);
         }
 
-        compiler.add_compilation_unit(name.join("/"), context.commands, context.dependencies);
+        let id = compiler.intern(&unit_name);
+        if let Err(msg) = compiler.add_compilation_unit(id, defined_at, context.commands, context.dependencies) {
+            return Err(vec![Error::new(defined_at, msg)]);
+        }
+        compiler.add_tests(id, context.tests);
+        compiler.set_defined_until(id, self.loc);
 
         Ok(())
     }
 }
 
-fn pathify_identifier(path: &Vec<String>, identifier: &mut String) -> Result<(), String> {
-    if identifier.get(0..1).unwrap() == "/" {
-        identifier.insert_str(0, &path.join("/")[..]);
-    }else if identifier.get(0..1).unwrap() == "." {
-        let mut n_dots = 0;
-        while identifier.get(0..1).ok_or_else(|| String::from("Expected '/' after dots in identifier"))? == "." {
-            identifier.remove(0);
-            n_dots += 1;
+// How many tokens there are, counting the ones inside of loops and blocks too
+pub fn weight(tokens: &[Token]) -> usize {
+    tokens.iter()
+        .map(|token| 1 + match &token.data {
+            TokenType::Loop(contents) | TokenType::LoopAt(_, contents) => weight(contents),
+            TokenType::IfElse(then, other) => weight(then) + weight(other),
+            _ => 0
+        })
+        .sum()
+}
+
+// The string literals in some tokens, as (where, string, whether it clears the cells first)
+pub fn strings(tokens: &[Token]) -> Vec<(Loc, String, bool)> {
+    let mut found = Vec::new();
+    for token in tokens {
+        match &token.data {
+            TokenType::Str(string, is_safe) => found.push((token.src_loc, string.clone(), *is_safe)),
+            TokenType::Loop(contents) | TokenType::LoopAt(_, contents) => found.extend(strings(contents)),
+            TokenType::IfElse(then, other) => {
+                found.extend(strings(then));
+                found.extend(strings(other));
+            },
+            _ => {}
         }
+    }
 
-        if identifier.get(0..1).unwrap() != "/" {
+    found
+}
+
+// Names are letters, digits and '_', and can't start with a digit. Paths are names with '/' between them,
+// and can start with '/' or with dots and a '/', like "../name". Letters have to be ascii unless
+// *unicode_identifiers is on, since a lot of other letters look just like ascii ones
+pub fn check_identifier(identifier: &str, unicode: bool) -> Result<(), String> {
+    let path = identifier.trim_start_matches('.');
+    if path.len() != identifier.len() && !path.starts_with('/') {
+        let dots = &identifier[..identifier.len() - path.len()];
+        return Err(format!("Expected '/' after the dots in '{}', like '{}/{}'", identifier, dots, path));
+    }
+
+    let path = path.strip_prefix('/').unwrap_or(path);
+    for name in path.split('/') {
+        if name.is_empty() {
+            return Err(format!("'{}' has an empty name in it, is there a '/' too many?", identifier));
+        }
+        if name.contains('.') {
+            return Err(format!("Dots can only go at the start of a path, like '../name', not in the middle of '{}'", identifier));
+        }
+        if name.starts_with(|c: char| c.is_numeric()) {
+            return Err(format!("The name '{}' in '{}' starts with a digit, names can't", name, identifier));
+        }
+        if let Some(c) = name.chars().find(|c| !unicode && !c.is_ascii()) {
+            return Err(format!("'{}' in '{}' isn't an ascii letter, names can only have those without *unicode_identifiers", c, identifier));
+        }
+    }
+
+    Ok(())
+}
+
+fn pathify_identifier(path: &[String], identifier: &mut String) -> Result<(), String> {
+    if identifier.starts_with('/') {
+        identifier.insert_str(0, &path.join("/")[..]);
+    }else if identifier.starts_with('.') {
+        let n_dots = identifier.chars().take_while(|&c| c == '.').count();
+        let rest = identifier.trim_start_matches('.');
+        if !rest.starts_with('/') {
             return Err(String::from("Faulty path: Expected '..../', got '....'"));
         }
+        if n_dots > path.len() {
+            return Err(format!("'{}' goes up {} times, but it's only {} deep", identifier, n_dots, path.len()));
+        }
+
+        *identifier = format!("{}{}", path[..path.len() - n_dots].join("/"), rest);
+    }
+
+    Ok(())
+}
+
+// The top level tokens of a file, lexed as they're asked for, so tools that only
+// want to look at the source don't have to register any compilation units.
+// Macro names are interned in a compiler of its own, 'name_of' turns them back
+// into paths. Macro definitions are lexed into that compiler too, so only their
+// errors come out of the iterator, not their contents.
+pub struct Tokens {
+    lexer: Lexer,
+    compiler: Compiler,
+    path: Vec<String>,
+    pending: VecDeque<Result<Token, Error>>
+}
+
+impl Tokens {
+    pub fn name_of(&self, symbol: Symbol) -> String {
+        self.compiler.name_of(symbol)
+    }
 
-        let sub_path = &path[0..(path.len() - n_dots)];
-        for (i, elem) in sub_path.iter().rev().enumerate() {
-            if i > 0 {
-                identifier.insert(0, '/');
+    // The pragmas declared in the part of the file that has been lexed so far
+    pub fn pragmas(&self) -> Pragmas {
+        self.lexer.pragmas()
+    }
+}
+
+impl Iterator for Tokens {
+    type Item = Result<Token, Error>;
+
+    fn next(&mut self) -> Option<Result<Token, Error>> {
+        while self.pending.is_empty() {
+            let c = *self.lexer.text.get(self.lexer.loc.index)?;
+            let mut context = LexerContext::new(self.path.clone());
+            if c == ':' {
+                if let Err(errors) = self.lexer.parse_definition(&self.path, &self.compiler, &mut context) {
+                    context.errors.extend(errors);
+                }
+            }else if c == '}' {
+                context.add_error(self.lexer.loc, String::from("Unexpected '}'"));
+                self.lexer.loc.add_n_chars(1);
+            }else{
+                self.lexer.parse_value(&self.compiler, &mut context);
             }
-            identifier.insert_str(0, elem);
+
+            self.pending.extend(context.errors.into_iter().map(Err));
+            self.pending.extend(context.commands.into_iter().map(Ok));
         }
+
+        self.pending.pop_front()
     }
+}
 
-    Ok(())
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ CompileOptions, compile_program, compile_test_program };
+    use crate::interpreter::{ Interpreter, State };
+    use crate::instructions::*;
+
+    fn run(text: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new(compile_test_program(text));
+        assert_eq!(interpreter.run(), &State::Finished);
+        interpreter
+    }
+
+    // The longest run of increments or decrements in the program
+    fn longest_add(text: &str) -> usize {
+        let code = compile_test_program(text).code;
+        code.split(|&instruction| instruction != INCREMENT && instruction != DECREMENT)
+            .map(|run| run.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn expressions_add_up_cells_and_numbers() {
+        let interpreter = run(">+5>+3<< =[ @>1*3 + @>2 - 2 + 'a ]");
+        assert_eq!(interpreter.cell(0), 15 + 3 - 2 + 0x61);
+        // The cells that were read keep their values
+        assert_eq!(interpreter.cell(1), 5);
+        assert_eq!(interpreter.cell(2), 3);
+
+        let interpreter = run(">+2< =[ -@>1 - -3 ]");
+        assert_eq!(interpreter.cell(0), 1);
+    }
+
+    #[test]
+    fn expressions_wrap_around_instead_of_overflowing() {
+        let powers = vec!["2"; 70].join("*");
+        let interpreter = run(&format!("=[ {} + 1 ]", powers));
+        assert_eq!(interpreter.cell(0), 1);
+
+        // 0xFF is the same as -1, so it's one decrement instead of 255 increments
+        let interpreter = run(">+3< =[ @>1*FF ]");
+        assert_eq!(interpreter.cell(0), 0xFD);
+        assert!(longest_add("=[ FF*FF*FF + @>1*80 - @>2*81 ]") <= 0x80);
+
+        let interpreter = run("#pragma cells 16\n >+1< =[ @>1*FF*FF ]");
+        assert_eq!(interpreter.cell(0), 0xFE01);
+        assert!(longest_add("#pragma cells 16\n =[ FF*FF ]") <= 0x8000);
+    }
+
+    #[test]
+    fn expressions_too_big_to_compile_are_errors() {
+        let text = "#pragma cells 32\n=[ D9*97*10*10*10*10 ]";
+        let failure = compile_program(text.chars().collect(), None, CompileOptions::default()).err().expect("It shouldn't compile");
+        assert!(failure.describe().contains("The numbers in '=[' add up to"), "{}", failure.describe());
+    }
+
+    #[test]
+    fn cell_size_has_to_come_before_expressions() {
+        let text = "=[ FF*FF ]\n#pragma cells 16";
+        assert!(compile_program(text.chars().collect(), None, CompileOptions::default()).is_err());
+    }
+
+    #[test]
+    fn annotations_declare_tests_and_unknown_ones_are_warnings() {
+        let text = ":rev { ;@input \"ab\"\n ;@expect \"ba\"\n ;@todo make it faster\n ,>,.<. }";
+        let compiler = Compiler::new();
+        let mut lexer = Lexer::new(text.chars().collect());
+        assert!(lexer.tokenize(&[String::from("src")], &compiler, false).is_ok());
+        assert_eq!(compiler.warnings(), vec![
+            String::from("Unknown annotation ';@todo', expected 'input' or 'expect', it's ignored")
+        ]);
+
+        let unit = compiler.units().into_iter().find(|unit| unit.name == "src/rev").unwrap();
+        assert_eq!(unit.tests.len(), 1);
+        assert_eq!(unit.tests[0].input, "ab");
+        assert_eq!(unit.tests[0].expect, "ba");
+
+        assert!(compile_program(";@input 3".chars().collect(), None, CompileOptions::default()).is_err());
+    }
+}
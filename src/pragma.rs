@@ -0,0 +1,192 @@
+// Pragmas are program wide settings that are declared in the source code,
+// ex: "#pragma cells 16" or "#pragma tape fixed 30000".
+// They are stored in the header of the binary, so a compiled program
+// carries the runtime semantics it needs with it.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tape {
+    // Grows in both directions forever
+    Infinite,
+    // Cells 0 up to but not including the length, moving outside is a runtime error
+    Fixed(u32)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pragmas {
+    pub cell_bits: u8,
+    pub tape: Tape,
+    // How many instructions the program can run before it's stopped
+    pub max_steps: Option<u64>,
+    // How many cells the program can use, counted from the lowest to the highest cell it visits
+    pub max_memory: Option<u64>,
+    // Lets the program use "$p", "$f", "$r" and "$i", see introspect. Only the compiler needs
+    // to know, so it isn't stored in the binary
+    pub introspection: bool
+}
+
+const TAPE_INFINITE: u8 = 0x00;
+const TAPE_FIXED: u8 = 0x01;
+
+impl Default for Pragmas {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Pragmas {
+    // How many bytes the pragmas take up in the binary header
+    pub const ENCODED_LEN: usize = 22;
+
+    // Binaries before version 7 didn't have the limits
+    pub fn encoded_len(version: u8) -> usize {
+        if version >= 7 { Pragmas::ENCODED_LEN } else { 6 }
+    }
+
+    pub fn new() -> Pragmas {
+        Pragmas {
+            cell_bits: 8,
+            tape: Tape::Infinite,
+            max_steps: None,
+            max_memory: None,
+            introspection: false
+        }
+    }
+
+    // Applies the arguments of a "#pragma" directive, returns the name
+    // of the pragma that was set
+    pub fn apply(&mut self, args: &[&str]) -> Result<&'static str, String> {
+        match args {
+            ["cells", bits] => {
+                self.cell_bits = parse_cell_bits(bits)?;
+                Ok("cells")
+            },
+            ["tape", "infinite"] => {
+                self.tape = Tape::Infinite;
+                Ok("tape")
+            },
+            ["tape", "fixed", length] => {
+                let length = length.parse::<u32>()
+                    .map_err(|_| format!("Invalid tape length '{}'", length))?;
+                if length == 0 {
+                    return Err(String::from("A fixed tape needs at least one cell"));
+                }
+
+                self.tape = Tape::Fixed(length);
+                Ok("tape")
+            },
+            ["max_steps", steps] => {
+                self.max_steps = Some(parse_limit(steps)?);
+                Ok("max_steps")
+            },
+            ["max_memory", cells] => {
+                self.max_memory = Some(parse_limit(cells)?);
+                Ok("max_memory")
+            },
+            ["introspection"] => {
+                self.introspection = true;
+                Ok("introspection")
+            },
+            ["cells", ..] => Err(String::from("Expected '#pragma cells [8|16|32]'")),
+            ["tape", ..] => Err(String::from("Expected '#pragma tape infinite' or '#pragma tape fixed [length]'")),
+            ["max_steps", ..] => Err(String::from("Expected '#pragma max_steps [instructions]'")),
+            ["max_memory", ..] => Err(String::from("Expected '#pragma max_memory [cells]'")),
+            ["introspection", ..] => Err(String::from("Expected '#pragma introspection'")),
+            [name, ..] => Err(format!("Unknown pragma '{}'", name)),
+            [] => Err(String::from("Expected the name of a pragma"))
+        }
+    }
+
+    // The "#pragma" lines that declare these pragmas, the defaults are left out
+    pub fn declarations(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if self.cell_bits != 8 {
+            lines.push(format!("#pragma cells {}", self.cell_bits));
+        }
+        if let Tape::Fixed(length) = self.tape {
+            lines.push(format!("#pragma tape fixed {}", length));
+        }
+        if let Some(steps) = self.max_steps {
+            lines.push(format!("#pragma max_steps {}", steps));
+        }
+        if let Some(cells) = self.max_memory {
+            lines.push(format!("#pragma max_memory {}", cells));
+        }
+        if self.introspection {
+            lines.push(String::from("#pragma introspection"));
+        }
+        lines
+    }
+
+    pub fn cell_mask(&self) -> u32 {
+        match self.cell_bits {
+            32 => 0xFFFF_FFFF,
+            bits => (1 << bits) - 1
+        }
+    }
+
+    pub fn encode(&self, data: &mut Vec<u8>) {
+        data.push(self.cell_bits);
+        match self.tape {
+            Tape::Infinite => {
+                data.push(TAPE_INFINITE);
+                data.extend_from_slice(&0u32.to_le_bytes());
+            },
+            Tape::Fixed(length) => {
+                data.push(TAPE_FIXED);
+                data.extend_from_slice(&length.to_le_bytes());
+            }
+        }
+
+        // 0 means there's no limit, a limit of 0 isn't allowed anyway
+        data.extend_from_slice(&self.max_steps.unwrap_or(0).to_le_bytes());
+        data.extend_from_slice(&self.max_memory.unwrap_or(0).to_le_bytes());
+    }
+
+    pub fn decode(data: &[u8], version: u8) -> Result<Pragmas, String> {
+        if data.len() < Pragmas::encoded_len(version) {
+            return Err(String::from("The pragmas in the header are truncated"));
+        }
+
+        let cell_bits = parse_cell_bits(&data[0].to_string())
+            .map_err(|msg| format!("The program can't run on this interpreter, {}", msg))?;
+        let length = u32::from_le_bytes([data[2], data[3], data[4], data[5]]);
+        let tape = match data[1] {
+            TAPE_INFINITE => Tape::Infinite,
+            TAPE_FIXED if length > 0 => Tape::Fixed(length),
+            _ => return Err(String::from("The program can't run on this interpreter, unknown tape kind"))
+        };
+
+        let limit = |at: usize| if version >= 7 {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(&data[at..at + 8]);
+            Some(u64::from_le_bytes(bytes)).filter(|limit| *limit > 0)
+        }else{
+            None
+        };
+
+        Ok(Pragmas {
+            cell_bits,
+            tape,
+            max_steps: limit(6),
+            max_memory: limit(14),
+            introspection: false
+        })
+    }
+}
+
+fn parse_limit(limit: &str) -> Result<u64, String> {
+    match limit.parse::<u64>() {
+        Ok(0) => Err(String::from("A limit has to be at least 1")),
+        Ok(limit) => Ok(limit),
+        Err(_) => Err(format!("Invalid limit '{}'", limit))
+    }
+}
+
+fn parse_cell_bits(bits: &str) -> Result<u8, String> {
+    match bits {
+        "8" => Ok(8),
+        "16" => Ok(16),
+        "32" => Ok(32),
+        _ => Err(format!("Unsupported cell size '{}', supported sizes are 8, 16 and 32 bits", bits))
+    }
+}
@@ -0,0 +1,147 @@
+use crate::interner::Symbol;
+use crate::parse_bf::{ Token, TokenType, TableValue };
+use crate::instructions::{ DEBUG_WINDOW, DEBUG_DUMP, DEBUG_MARKER };
+use crate::introspect::fact_symbol;
+
+// Turns tokens back into source code that lexes to the same tokens, for everything that needs to
+// show or write code it only has the tokens of. It's not the code that was written, comments,
+// formatting and repetitions are gone, and counts are written out the way the lexer keeps them.
+// 'name_of' says how to write the names of macros, the lexer only keeps their symbols
+pub fn write_source(tokens: &[Token], name_of: &dyn Fn(Symbol) -> String) -> String {
+    let mut words = Vec::new();
+    write_tokens(tokens, name_of, &mut words);
+    words.join(" ")
+}
+
+// Every token becomes a word of its own, since a count right after some commands would be
+// read as a part of them, like "+" and "5"
+fn write_tokens(tokens: &[Token], name_of: &dyn Fn(Symbol) -> String, words: &mut Vec<String>) {
+    for token in tokens {
+        match &token.data {
+            TokenType::Str(string, is_safe) => {
+                let escaped = string.replace('\n', "\\n").replace('\t', "\\t");
+                words.push(format!("{}\"{}\"", if *is_safe { "" } else { "0" }, escaped));
+            },
+            TokenType::Macro(symbol) => words.push(format!("#{}", name_of(*symbol))),
+            TokenType::Loop(contents) => block(words, "[", contents, "]", name_of),
+            TokenType::IfElse(then, other) => {
+                block(words, "?{", then, "}", name_of);
+                if !other.is_empty() {
+                    block(words, ":{", other, "}", name_of);
+                }
+            },
+            TokenType::LoopAt(offset, contents) => block(words, &format!("{}[", cell(*offset)), contents, "]", name_of),
+            TokenType::Assign(terms, constant) => {
+                let mut parts = Vec::new();
+                for (offset, times) in terms {
+                    chunks(&mut parts, *times, |chunk| match chunk {
+                        1 => cell(*offset),
+                        _ => format!("{}*{:X}", cell(*offset), chunk)
+                    });
+                }
+                chunks(&mut parts, *constant, |chunk| format!("{:X}", chunk));
+
+                let mut expression = String::new();
+                for (i, (negative, part)) in parts.iter().enumerate() {
+                    match (i, negative) {
+                        (0, false) => {},
+                        (0, true) => expression.push_str("- "),
+                        (_, false) => expression.push_str(" + "),
+                        (_, true) => expression.push_str(" - ")
+                    }
+                    expression.push_str(part);
+                }
+                if parts.is_empty() {
+                    expression.push('0');
+                }
+                words.push(format!("=[ {} ]", expression));
+            },
+            TokenType::Table(entries, default) => {
+                let mut table = Vec::new();
+                for (first, last, value) in entries {
+                    let keys = if first == last { format!("{:X}", first) } else { format!("{:X}-{:X}", first, last) };
+                    table.push(format!("{}: {}", keys, table_value(*value)));
+                }
+                if *default != TableValue::Set(0) {
+                    table.push(format!("_: {}", table_value(*default)));
+                }
+                words.push(format!("%[ {} ]", table.join(" ")));
+            },
+            TokenType::Increment(n) => words.push(command('+', *n)),
+            TokenType::Decrement(n) => words.push(command('-', *n)),
+            TokenType::ShiftRight(n) => words.push(command('>', *n)),
+            TokenType::ShiftLeft(n) => words.push(command('<', *n)),
+            TokenType::Print => words.push(String::from(".")),
+            TokenType::Read => words.push(String::from(",")),
+            TokenType::Debug(label) => words.push(format!("!{}", label.as_deref().unwrap_or(""))),
+            TokenType::Assert(value) => words.push(format!("!={:X}", value)),
+            TokenType::DebugOp(kind, argument) => words.push(match *kind {
+                DEBUG_WINDOW => format!("!~{:X}", argument),
+                DEBUG_DUMP => format!("!#{:X}", argument),
+                DEBUG_MARKER => format!("!%{:X}", argument),
+                _ => String::from("!?")
+            }),
+            TokenType::Introspect(kind) => words.push(format!("${}", fact_symbol(*kind)))
+        }
+    }
+}
+
+fn block(words: &mut Vec<String>, open: &str, contents: &[Token], close: &str, name_of: &dyn Fn(Symbol) -> String) {
+    words.push(String::from(open));
+    write_tokens(contents, name_of, words);
+    words.push(String::from(close));
+}
+
+fn command(symbol: char, n: u8) -> String {
+    if n == 1 { symbol.to_string() } else { format!("{}{:X}", symbol, n) }
+}
+
+fn cell(offset: isize) -> String {
+    match offset {
+        0 => String::from("@"),
+        offset if offset > 0 => format!("@>{:X}", offset),
+        offset => format!("@<{:X}", -offset)
+    }
+}
+
+fn table_value(value: TableValue) -> String {
+    match value {
+        TableValue::Set(n) => format!("{:X}", n),
+        TableValue::Add(0) => String::from("@"),
+        TableValue::Add(n) if n > 0 => format!("@+{:X}", n),
+        TableValue::Add(n) => format!("@-{:X}", -n)
+    }
+}
+
+// Numbers in expressions are at most FF, so bigger ones are split up into several terms,
+// as (whether it's subtracted, the term)
+fn chunks(parts: &mut Vec<(bool, String)>, value: i64, term: impl Fn(i64) -> String) {
+    let mut left = value.abs();
+    while left > 0 {
+        let chunk = left.min(0xFF);
+        parts.push((value < 0, term(chunk)));
+        left -= chunk;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::Compiler;
+
+    #[test]
+    fn pretty_printed_tokens_lex_to_the_same_tokens() {
+        let root_source = |text: &str| {
+            let compiler = Compiler::new();
+            let mut lexer = crate::Lexer::new(text.chars().collect());
+            lexer.tokenize(&[String::from("src")], &compiler, false).unwrap_or_else(|_| panic!("{} didn't lex", text));
+            let root = compiler.intern("src");
+            write_source(&compiler.tokens_of(root).unwrap(), &|symbol| compiler.name_of(symbol))
+        };
+
+        let text = "+5 >2 ( - )3 [ #std/print ] ?{ . }:{ , } @>1[ - ] =[ @<1*2 - 3 ] %[ 'a-'c: @+1 _: 0 ] \"a\\nb\" !=3 !~2";
+        let source = root_source(text);
+        assert_eq!(source, "+5 >2 - - - [ #std/print ] ?{ . } :{ , } @>1[ - ] =[ @<1*2 - 3 ] %[ 61-63: @+1 ] \"a\\nb\" !=3 !~2");
+        assert_eq!(root_source(&source), source);
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{ Compiler, CompileFailure, CompileOptions, Lexer };
+use crate::parse_bf::{ Name, NameKind, check_identifier };
+
+// "rename file src/old src/new" renames a macro, and rewrites every place that uses it.
+// The places come from the lexer, so names in comments, strings and plain brainfuck
+// are left alone, and relative paths stay relative where they still can be.
+// Gives back the new text of the file and how many places changed
+pub fn rename(text: Vec<char>, old: &str, new: &str, options: CompileOptions)
+        -> Result<(String, usize), CompileFailure> {
+    for path in [old, new] {
+        if let Err(msg) = check_identifier(path, options.unicode_identifiers) {
+            return Err(CompileFailure::Invalid(msg));
+        }
+        if !path.starts_with("src/") {
+            return Err(CompileFailure::Invalid(format!("'{}' has to be a full path, starting with 'src/'", path)));
+        }
+    }
+    if parent(old) != parent(new) {
+        return Err(CompileFailure::Invalid(format!(
+            "Only the last name of a path can be renamed, '{}' would move '{}' to another scope", new, old)));
+    }
+
+    let compiler = Compiler::with_options(options);
+    let mut lexer = Lexer::new(text.clone());
+    lexer.tokenize(&[String::from("src")], &compiler, false).map_err(CompileFailure::Source)?;
+    let names = lexer.names();
+
+    if !names.iter().any(|name| name.path == old && matches!(name.kind, NameKind::Definition)) {
+        return Err(CompileFailure::Invalid(format!("'{}' isn't defined in the file", old)));
+    }
+    if names.iter().any(|name| defines(name) == Some(new) || name.path == new) {
+        return Err(CompileFailure::Invalid(format!("'{}' is already used in the file", new)));
+    }
+
+    // "#use src/old" imports it as 'old' wherever it is, so the import gets the new name too,
+    // and everything that uses the import has to be renamed along with it
+    let mut renames = vec![(String::from(old), String::from(new))];
+    loop {
+        let mut added = false;
+        for name in names {
+            if let NameKind::Import(import) = &name.kind {
+                let renamed = format!("{}/{}", apply(&renames, parent(import)), last(&apply(&renames, &name.path)));
+                if renamed != *import && !renames.iter().any(|(from, _)| from == import) {
+                    renames.push((import.clone(), renamed));
+                    added = true;
+                }
+            }
+        }
+        if !added {
+            break;
+        }
+    }
+
+    let mut edits: Vec<(usize, usize, String)> = Vec::new();
+    for name in names {
+        let path = apply(&renames, &name.path);
+        if path == name.path {
+            continue;
+        }
+
+        let written = match name.kind {
+            NameKind::Definition if last(&path) == last(&name.path) => continue,
+            NameKind::Definition => String::from(last(&path)),
+            NameKind::Use | NameKind::Import(_) => rewrite(&renames, name, &path)
+        };
+        if written != name.written {
+            edits.push((name.loc.index(), name.written.chars().count(), written));
+        }
+    }
+    edits.sort_by_key(|edit| edit.0);
+    edits.dedup_by_key(|edit| edit.0);
+
+    let mut renamed = text;
+    for (start, length, written) in edits.iter().rev() {
+        renamed.splice(*start..*start + length, written.chars());
+    }
+
+    Ok((renamed.into_iter().collect(), edits.len()))
+}
+
+// The path a name makes a macro for, if it makes one
+fn defines(name: &Name) -> Option<&str> {
+    match &name.kind {
+        NameKind::Definition => Some(&name.path),
+        NameKind::Import(import) => Some(import),
+        NameKind::Use => None
+    }
+}
+
+// How a use of a macro should be written once it has a new path. "/name" and "../name" are
+// relative to the macro they're written in, which may have been renamed too
+fn rewrite(renames: &[(String, String)], name: &Name, path: &str) -> String {
+    let relative = name.written.trim_start_matches('.');
+    if !relative.starts_with('/') {
+        return String::from(path);
+    }
+
+    let prefix = &name.written[..name.written.len() - relative.len() + 1];
+    let base = &name.path[..name.path.len() - relative.len()];
+    let base = apply(renames, base);
+    match path.strip_prefix(&base[..]).and_then(|rest| rest.strip_prefix('/')) {
+        Some(rest) => format!("{}{}", prefix, rest),
+        None => String::from(path)
+    }
+}
+
+// The new path of something, the longest rename that it's in wins
+fn apply(renames: &[(String, String)], path: &str) -> String {
+    renames.iter()
+        .filter(|(from, _)| path == from || path.starts_with(&format!("{}/", from)))
+        .max_by_key(|(from, _)| from.len())
+        .map(|(from, to)| format!("{}{}", to, &path[from.len()..]))
+        .unwrap_or_else(|| String::from(path))
+}
+
+fn parent(path: &str) -> &str {
+    path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("")
+}
+
+fn last(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rename_rewrites_every_use_of_a_macro() {
+        let text = ":old { :inner { + } #/inner } :other { #use src/old #/old #./old/inner } #src/old \"#src/old\" ; #src/old";
+        let (renamed, changed) = rename(text.chars().collect(), "src/old", "src/new", CompileOptions::default())
+            .unwrap_or_else(|_| panic!("Couldn't rename"));
+        assert_eq!(renamed, ":new { :inner { + } #/inner } :other { #use src/new #/new #./new/inner } #src/new \"#src/old\" ; #src/old");
+        assert_eq!(changed, 5);
+
+        // The new name can't be taken already, and a macro can't be moved to another parent
+        assert!(rename(text.chars().collect(), "src/old", "src/other", CompileOptions::default()).is_err());
+        assert!(rename(text.chars().collect(), "src/old/inner", "src/inner", CompileOptions::default()).is_err());
+    }
+}
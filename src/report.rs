@@ -0,0 +1,166 @@
+use std::fs;
+use std::time::Duration;
+
+use crate::instructions::*;
+use crate::emit::json_string;
+use crate::color::style;
+
+// What *report=file counts while the program runs
+pub struct RunStats {
+    pub instructions: u64,
+    // How many times every opcode was run
+    pub opcode_counts: [u64; 256],
+    // For every byte of the code, whether an instruction starting there was ever run
+    pub covered: Vec<bool>,
+    // For every loop, by the offset of its LOOP_OPEN, how many times it was entered
+    // and how many times its body ran in total
+    pub loop_entries: Vec<u64>,
+    pub loop_iterations: Vec<u64>
+}
+
+// How many loops *profile lists
+const MAX_PROFILED_LOOPS: usize = 20;
+
+impl RunStats {
+    pub fn new(code_len: usize) -> RunStats {
+        RunStats {
+            instructions: 0,
+            opcode_counts: [0; 256],
+            covered: vec![false; code_len],
+            loop_entries: vec![0; code_len],
+            loop_iterations: vec![0; code_len]
+        }
+    }
+
+    // A LOOP_OPEN at 'start' that goes into the loop
+    pub fn enter_loop(&mut self, start: usize) {
+        self.loop_entries[start] += 1;
+        self.loop_iterations[start] += 1;
+    }
+
+    // A LOOP_CLOSE that jumps back to the loop at 'start'
+    pub fn repeat_loop(&mut self, start: usize) {
+        self.loop_iterations[start] += 1;
+    }
+
+    // The loops that ran, as (offset, entries, iterations), the most iterations first
+    pub fn loops(&self) -> Vec<(usize, u64, u64)> {
+        let mut loops: Vec<(usize, u64, u64)> = self.loop_entries.iter()
+            .enumerate()
+            .filter(|(_, &entries)| entries > 0)
+            .map(|(offset, &entries)| (offset, entries, self.loop_iterations[offset]))
+            .collect();
+        loops.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+        loops
+    }
+
+    pub fn print_loops(&self) {
+        let loops = self.loops();
+        if loops.is_empty() {
+            return;
+        }
+
+        println!("{}", style(ansi_term::Color::Cyan.bold()).paint("Hottest loops:"));
+        for (offset, entries, iterations) in loops.iter().take(MAX_PROFILED_LOOPS) {
+            println!(" | {:.>4X}: entered {} times, {} iterations, {:.1} per entry",
+                offset, entries, iterations, *iterations as f64 / *entries as f64);
+        }
+        if loops.len() > MAX_PROFILED_LOOPS {
+            println!(" | and {} more loops", loops.len() - MAX_PROFILED_LOOPS);
+        }
+    }
+
+    pub fn step(&mut self, instruction: u8, instr_ptr: usize) {
+        self.instructions += 1;
+        self.opcode_counts[instruction as usize] += 1;
+        self.covered[instr_ptr] = true;
+    }
+}
+
+// The coverage as hex, bit N(counting from the lowest bit of the first byte) is
+// set if the instruction at offset N was run
+fn coverage_bitmap(covered: &[bool]) -> String {
+    covered.chunks(8)
+        .map(|chunk| {
+            let byte = chunk.iter().enumerate().fold(0u8, |byte, (i, &bit)| byte | ((bit as u8) << i));
+            format!("{:02x}", byte)
+        })
+        .collect()
+}
+
+// How many instructions there are in the code, and how many of them were run
+fn coverage_counts(code: &[u8], covered: &[bool]) -> (usize, usize) {
+    let mut total = 0;
+    let mut run = 0;
+    let mut index = 0;
+    while index < code.len() {
+        total += 1;
+        if covered[index] {
+            run += 1;
+        }
+        index += len(code[index]);
+    }
+
+    (total, run)
+}
+
+pub fn report_json(code: &[u8], stats: &RunStats, wall_time: Duration, pages: (usize, usize), result: &Result<(), String>) -> String {
+    let counts: Vec<String> = stats.opcode_counts.iter()
+        .enumerate()
+        .filter(|(_, &count)| count > 0)
+        .map(|(opcode, count)| format!("\"{}\": {}", instruction_name(opcode as u8), count))
+        .collect();
+    let (exit, error) = match result {
+        Ok(()) => ("finished", String::from("null")),
+        Err(msg) => ("error", json_string(msg))
+    };
+    let (total, run) = coverage_counts(code, &stats.covered);
+    let loops: Vec<String> = stats.loops().iter()
+        .map(|(offset, entries, iterations)| format!("{{ \"offset\": {}, \"entries\": {}, \"iterations\": {} }}",
+            offset, entries, iterations))
+        .collect();
+
+    format!("{{\n  \"instructions_executed\": {},\n  \"opcode_counts\": {{ {} }},\n  \"wall_time_seconds\": {},\n  \
+        \"peak_pages\": {},\n  \"page_cells\": {},\n  \"exit_reason\": \"{}\",\n  \"error\": {},\n  \
+        \"coverage\": {{ \"instructions\": {}, \"run\": {}, \"bitmap\": \"{}\" }},\n  \"loops\": [{}]\n}}\n",
+        stats.instructions, counts.join(", "), wall_time.as_secs_f64(), pages.0, pages.1,
+        exit, error, total, run, coverage_bitmap(&stats.covered), loops.join(", "))
+}
+
+pub fn write_report(path: &str, code: &[u8], stats: &RunStats, wall_time: Duration, pages: (usize, usize), result: &Result<(), String>) -> Result<(), String> {
+    fs::write(path, report_json(code, stats, wall_time, pages, result))
+        .map_err(|error| format!("Couldn't write the report '{}', {}", path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ Modifiers, compile_test_program };
+
+    // Runs the program with *report, and gives back the report
+    fn report(text: &str, modifiers: &mut Modifiers) -> String {
+        let path = std::env::temp_dir().join(format!("brainfuck_report_{}_{}.json", std::process::id(), text.len()));
+        modifiers.report = Some(path.to_string_lossy().into_owned());
+        let _ = crate::execute_to_bytes(&compile_test_program(text), modifiers, &[]);
+        let report = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        report
+    }
+
+    #[test]
+    fn reports_count_what_the_program_did() {
+        let json = report("+3[-] >[+]", &mut Modifiers::new());
+        assert!(json.contains("\"instructions_executed\": 12,"), "{}", json);
+        assert!(json.contains("\"INCREMENT\": 3, \"DECREMENT\": 3"), "{}", json);
+        assert!(json.contains("\"exit_reason\": \"finished\",\n  \"error\": null,"), "{}", json);
+        // The second loop is skipped, so its "+" and "]" never run
+        assert!(json.contains("\"coverage\": { \"instructions\": 10, \"run\": 8, \"bitmap\": \"0fc30000\" }"), "{}", json);
+        assert!(json.contains("\"loops\": [{ \"offset\": 3, \"entries\": 1, \"iterations\": 3 }]"), "{}", json);
+        assert!(json.contains("\"peak_pages\": 1,"), "{}", json);
+
+        let mut modifiers = Modifiers::new();
+        modifiers.max_steps = Some(5);
+        let json = report("+[]", &mut modifiers);
+        assert!(json.contains("\"exit_reason\": \"error\",\n  \"error\": \"The program ran more than 5 instructions"), "{}", json);
+    }
+}
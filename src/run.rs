@@ -1,158 +1,661 @@
-use crate::Modifiers;
+use std::io::Write;
+use std::collections::{ BTreeMap, VecDeque };
+use std::fs::{ File, OpenOptions };
+use std::time::Instant;
+use crate::{ Modifiers, Newline };
 use crate::instructions::*;
 use crate::Memory;
+use crate::inf_memory::Cells;
+use crate::binary::Program;
+use crate::color::style;
+use crate::report::{ RunStats, write_report };
+use crate::snapshot::Snapshots;
+use crate::introspect::Random;
+use crate::machine::{ Machine, Fault, Io };
 
-fn shift_style()  -> ansi_term::Style { ansi_term::Color::Purple.bold() }
-fn modify_style() -> ansi_term::Style { ansi_term::Color::Green .bold() }
-fn loop_style()   -> ansi_term::Style { ansi_term::Color::Yellow.bold() }
-fn io_style()     -> ansi_term::Style { ansi_term::Color::Cyan  .bold() }
+fn shift_style()  -> ansi_term::Style { style(ansi_term::Color::Purple.bold()) }
+fn modify_style() -> ansi_term::Style { style(ansi_term::Color::Green .bold()) }
+fn loop_style()   -> ansi_term::Style { style(ansi_term::Color::Yellow.bold()) }
+fn io_style()     -> ansi_term::Style { style(ansi_term::Color::Cyan  .bold()) }
 
-pub fn execute_bf(bf: &Vec<u8>, modifiers: &Modifiers) {
-    let mut stdin = modifiers.std_in.clone();
-    
-    let mut memory = Memory::new();
-    let mut instr_ptr = 0usize;
-    let mut mem_ptr = 0isize;
+// What the DEBUG_OP instructions keep track of during a run
+#[derive(Default)]
+struct DebugState {
+    dump_file: Option<File>,
+    // For every profiling marker, how many times it was hit, and how many
+    // instructions ran after it before the next marker was hit
+    markers: BTreeMap<u32, (u64, u64)>,
+    last_marker: Option<(u32, u64)>
+}
 
-    let mut print_buf = String::with_capacity(200);
+impl DebugState {
+    // 'steps' is how many instructions ran before the marker
+    fn hit_marker(&mut self, marker: u32, steps: u64) {
+        self.end_marker(steps);
+        self.markers.entry(marker).or_insert((0, 0)).0 += 1;
+        self.last_marker = Some((marker, steps));
+    }
 
-    while instr_ptr < bf.len() {
-        let print_debug_info = modifiers.is_debug != (bf[instr_ptr] == DEBUG);
-        if print_debug_info {
-            print!("instr: {:.>4X}, mem: {:.>4X} | ", instr_ptr, mem_ptr);
-        }
-
-
-        match bf[instr_ptr] {
-            SHIFT_LEFT => {
-                if modifiers.is_debug { println!("{}", shift_style().paint("SHIFT_LEFT")); }
-                mem_ptr -= 1;
-                instr_ptr += 1;       
-            },
-            SHIFT_RIGHT => {
-                if modifiers.is_debug { println!("{}", shift_style().paint("SHIFT_RIGHT")); }
-                mem_ptr += 1;
-                instr_ptr += 1;
-            },
-            INCREMENT => {
-                if modifiers.is_debug { println!("{}", modify_style().paint("INCREMENT")); }
-                memory.modify(mem_ptr, |b| b.wrapping_add(0x01));
-                instr_ptr += 1;
-            },
-            DECREMENT => {
-                if modifiers.is_debug { println!("{}", modify_style().paint("DECREMENT")); }
-                memory.modify(mem_ptr, |b| b.wrapping_add(0xff));
-                instr_ptr += 1;
-            },
-            READ => {
-                if modifiers.is_debug { println!("{}", io_style().paint("READ")); }
-                while stdin.len() == 0 {
-                    if print_buf.len() > 0 {
-                        println!("{}", &print_buf);
-                        print_buf.clear();
-                    }
+    fn end_marker(&mut self, steps: u64) {
+        if let Some((marker, start)) = self.last_marker.take() {
+            self.markers.entry(marker).or_insert((0, 0)).1 += steps - start;
+        }
+    }
 
-                    println!("{}", 
-                        ansi_term::Color::Red
-                        .blink()
-                        .paint("The program requests some more characters to process: "));
-                    std::io::stdin().read_line(&mut stdin).expect("Couldn't read line for some reason");
-                    stdin = String::from(stdin.trim_end());
-                }
+    fn print_markers(&mut self, steps: u64) {
+        self.end_marker(steps);
+        if self.markers.is_empty() {
+            return;
+        }
 
-                let c = stdin.remove(0);
-                if !c.is_ascii() {
-                    panic!("Expected ascii character in stdin");
-                }
+        println!("{}", io_style().paint("Profiling markers:"));
+        for (marker, (hits, instructions)) in &self.markers {
+            println!(" | {:X}: hit {} times, {} instructions ran until the next marker", marker, hits, instructions);
+        }
+    }
+}
 
-                memory.set(mem_ptr, c as u8);
-                instr_ptr += 1;
-            },
-            PRINT => {
-                if modifiers.is_debug { 
-                    println!("{}: '{}'", modify_style().paint("PRINT"), memory.get(mem_ptr) as char);
-                }
+fn format_cells<C: Cells>(memory: &C, from: isize, to: isize, cell_width: usize) -> String {
+    let cells: Vec<String> = (from..to)
+        .map(|cell| format!("{:0>width$X}", memory.get(cell), width = cell_width))
+        .collect();
+    cells.join(" ")
+}
 
-                print_buf.push(memory.get(mem_ptr) as char);
-                if print_buf.len() >= 100 {
-                    print!("{}", print_buf);
-                    print_buf.clear();
-                }
-                instr_ptr += 1;
-            },
-            LOOP_OPEN => {
-                if memory.get(mem_ptr) != 0 {
-                    if modifiers.is_debug { 
-                        println!("{}, entering loop", loop_style().paint("LOOP_OPEN")); 
-                    }
-                    instr_ptr += 5;
-                }else{
-                    let offset: usize = ((bf[instr_ptr + 1] as u32)).wrapping_add
-                                        ((bf[instr_ptr + 2] as u32) << 8).wrapping_add 
-                                        ((bf[instr_ptr + 3] as u32) << 16).wrapping_add
-                                        ((bf[instr_ptr + 4] as u32) << 24) as usize;
-
-                    if modifiers.is_debug { 
-                        println!("{}, exiting loop, offset: {}", loop_style().paint("LOOP_OPEN"), offset); }
-                    instr_ptr += offset;
-                }
-            },
-            LOOP_CLOSE => {
-                if memory.get(mem_ptr) == 0 {
-                    if modifiers.is_debug { 
-                        println!("{}, exiting loop", loop_style().paint("LOOP_CLOSE")); 
+// Runs the DEBUG_OP at the instruction pointer
+fn run_debug_op<C: Cells>(
+        debug: &mut DebugState, modifiers: &Modifiers, code: &[u8], machine: &Machine,
+        memory: &C, cell_width: usize) -> Result<(), String> {
+    let (instr_ptr, mem_ptr) = (machine.instr_ptr(), machine.mem_ptr());
+    let argument = read_u32(code, instr_ptr + 2) as isize;
+    match code[instr_ptr + 1] {
+        DEBUG_WINDOW => println!("WINDOW instr: {:.>4X}, mem: {:.>4X} | {} [{}] {}", instr_ptr, mem_ptr,
+            format_cells(memory, mem_ptr - argument, mem_ptr, cell_width),
+            format_cells(memory, mem_ptr, mem_ptr + 1, cell_width),
+            format_cells(memory, mem_ptr + 1, mem_ptr + 1 + argument, cell_width)),
+        DEBUG_DUMP => {
+            let line = format!("DUMP instr: {:.>4X}, cells {} to {} | {}", instr_ptr, mem_ptr, mem_ptr + argument - 1,
+                format_cells(memory, mem_ptr, mem_ptr + argument, cell_width));
+
+            match &modifiers.dump_file {
+                Some(path) => {
+                    if debug.dump_file.is_none() {
+                        let file = OpenOptions::new().create(true).append(true).open(path)
+                            .map_err(|error| format!("Couldn't open the dump file '{}', {}", path, error))?;
+                        debug.dump_file = Some(file);
                     }
-                    instr_ptr += 5;
-                }else{
-                    let offset = (
-                                    ((bf[instr_ptr + 1] as u32)) + 
-                                    ((bf[instr_ptr + 2] as u32) << 8) +  
-                                    ((bf[instr_ptr + 3] as u32) << 16) +
-                                    ((bf[instr_ptr + 4] as u32) << 24)
-                                    ) as usize;
-
-                    if modifiers.is_debug { 
-                        println!("{}, continuing loop, offset: {}", loop_style().paint("LOOP_CLOSE"), offset); 
+
+                    writeln!(debug.dump_file.as_mut().unwrap(), "{}", line)
+                        .map_err(|error| format!("Couldn't write to the dump file '{}', {}", path, error))?;
+                },
+                None => println!("{}", line)
+            }
+        },
+        DEBUG_MARKER => debug.hit_marker(argument as u32, machine.steps()),
+        DEBUG_PAUSE => {
+            println!("PAUSED instr: {:.>4X}, mem: {:.>4X} | {}, press enter to continue",
+                instr_ptr, mem_ptr, format_cells(memory, mem_ptr, mem_ptr + 1, cell_width));
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line).expect("Couldn't read line for some reason");
+        },
+        _ => panic!("Invalid debug operation!")
+    }
+
+    Ok(())
+}
+
+// Buffers what the program prints, and copies it to the transcript if there is one
+struct Printer<'a, W: Write> {
+    output: &'a mut W,
+    buf: Vec<u8>,
+    // Everything the program printed and everything typed in, in the order it happened
+    transcript: Option<File>,
+    // Whether the output shares the screen with debug information, so it needs line breaks
+    // to keep them apart. Off when the exact bytes the program printed are wanted
+    separate_lines: bool
+}
+
+impl<'a, W: Write> Printer<'a, W> {
+    fn push(&mut self, byte: u8) -> Result<(), String> {
+        self.buf.push(byte);
+        if self.buf.len() >= 100 {
+            self.write()?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self) -> Result<(), String> {
+        self.output.write_all(&self.buf)
+            .and_then(|_| self.output.flush())
+            .map_err(|error| format!("Couldn't write the output of the program, {}", error))?;
+        if let Some(transcript) = &mut self.transcript {
+            transcript.write_all(&self.buf)
+                .map_err(|error| format!("Couldn't write to the transcript, {}", error))?;
+        }
+
+        self.buf.clear();
+        Ok(())
+    }
+
+    // Writes what's buffered on a line of its own, before something else gets printed
+    fn end_line(&mut self) -> Result<(), String> {
+        if !self.buf.is_empty() {
+            if self.separate_lines {
+                self.buf.push(b'\n');
+            }
+            self.write()?;
+        }
+
+        Ok(())
+    }
+
+    fn log_input(&mut self, line: &str) -> Result<(), String> {
+        if let Some(transcript) = &mut self.transcript {
+            transcript.write_all(line.as_bytes())
+                .map_err(|error| format!("Couldn't write to the transcript, {}", error))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Shows what a program prints on a terminal. The bytes are decoded as UTF-8, and anything
+// that isn't valid UTF-8 is shown as a replacement character instead of going to the terminal raw
+pub struct TerminalOutput<W: Write> {
+    output: W,
+    // The start of a character that got cut off at the end of the last write
+    pending: Vec<u8>,
+    // Whether control characters are escaped, so the program can't ring the bell,
+    // move the cursor around or send escape codes to the terminal
+    sanitize: bool
+}
+
+impl<W: Write> TerminalOutput<W> {
+    pub fn new(output: W) -> TerminalOutput<W> {
+        TerminalOutput { output, pending: Vec::new(), sanitize: false }
+    }
+
+    pub fn sanitize(mut self, sanitize: bool) -> TerminalOutput<W> {
+        self.sanitize = sanitize;
+        self
+    }
+}
+
+// Writes every control character except newlines and tabs as "\xNN" instead
+fn escape_control(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_control() && c != '\n' && c != '\t' {
+            escaped.push_str(&format!("\\x{:02X}", c as u32));
+        }else{
+            escaped.push(c);
+        }
+    }
+
+    escaped
+}
+
+impl<W: Write> Write for TerminalOutput<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        let mut text = String::with_capacity(self.pending.len());
+        let mut rest = &self.pending[..];
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    rest = &[];
+                    break;
+                },
+                Err(error) => {
+                    let (valid, after) = rest.split_at(error.valid_up_to());
+                    text.push_str(std::str::from_utf8(valid).unwrap());
+                    match error.error_len() {
+                        Some(invalid) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[invalid..];
+                        },
+                        // The character might be finished by the next write
+                        None => {
+                            rest = after;
+                            break;
+                        }
                     }
-                    instr_ptr -= offset;
                 }
-            },
-            DEBUG => {
-                println!("DEBUG_DUMP");
-                instr_ptr += 1;
-            },
-            _ => {
-                panic!("Invalid instruction!");
             }
         }
 
-        if print_debug_info {
+        self.pending = rest.to_vec();
+        if self.sanitize {
+            text = escape_control(&text);
+        }
+        self.output.write_all(text.as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.output.flush()
+    }
+}
+
+impl<W: Write> Drop for TerminalOutput<W> {
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            let _ = write!(self.output, "{}", char::REPLACEMENT_CHARACTER);
+            let _ = self.output.flush();
+        }
+    }
+}
+
+// Runs a program, anything the program prints goes to 'output'. 'input' is what it reads before
+// it asks for more on stdin, usually what Modifiers::read_inputs read, line endings are translated here.
+// Debug information and input prompts always go to stdout, and the output of the program
+// gets a line break before them if it didn't end with one, so they don't run together.
+pub fn execute_bf<W: Write>(program: &Program, modifiers: &Modifiers, input: &[u8], output: &mut W) -> Result<(), String> {
+    execute_with(program, modifiers, input, output, true)
+}
+
+// Runs a program, and writes exactly the bytes it prints to 'output'
+pub fn execute_raw<W: Write>(program: &Program, modifiers: &Modifiers, input: &[u8], output: &mut W) -> Result<(), String> {
+    execute_with(program, modifiers, input, output, false)
+}
+
+// Runs a program and returns the bytes it printed
+pub fn execute_to_bytes(program: &Program, modifiers: &Modifiers, input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut output = Vec::new();
+    execute_raw(program, modifiers, input, &mut output)?;
+    Ok(output)
+}
+
+// Runs a program and counts what it does, for tools that look at how it runs.
+// What it prints is thrown away
+pub fn execute_with_stats(program: &Program, modifiers: &Modifiers, input: &[u8]) -> (Result<(), String>, RunStats) {
+    let mut output = std::io::sink();
+    let mut stats = RunStats::new(program.code.len());
+    let mut failed_at = 0;
+    let printer = Printer { output: &mut output, buf: Vec::with_capacity(200), transcript: None, separate_lines: false };
+    let result = execute_on(program, modifiers, input, printer, &mut Memory::for_cells(program.pragmas.cell_bits), Some(&mut stats), &mut failed_at);
+    (result, stats)
+}
+
+fn execute_with<W: Write>(program: &Program, modifiers: &Modifiers, input: &[u8], output: &mut W, separate_lines: bool) -> Result<(), String> {
+    if modifiers.flat_tape {
+        return execute_flat(program, modifiers, input, output, separate_lines);
+    }
+
+    run_on(program, modifiers, input, output, &mut Memory::for_cells(program.pragmas.cell_bits), separate_lines)
+}
+
+#[cfg(feature = "flat_tape")]
+fn execute_flat<W: Write>(program: &Program, modifiers: &Modifiers, input: &[u8], output: &mut W, separate_lines: bool) -> Result<(), String> {
+    let mut memory = crate::flat_memory::FlatMemory::new()?;
+    run_on(program, modifiers, input, output, &mut memory, separate_lines)
+}
+
+#[cfg(not(feature = "flat_tape"))]
+fn execute_flat<W: Write>(_: &Program, _: &Modifiers, _: &[u8], _: &mut W, _: bool) -> Result<(), String> {
+    Err(String::from("The flat tape needs the interpreter to be built with the flat_tape feature"))
+}
+
+// Runs the program on a tape, and writes the report afterwards if there should be one
+fn run_on<W: Write, C: Cells>(
+        program: &Program, modifiers: &Modifiers, input: &[u8], output: &mut W,
+        memory: &mut C, separate_lines: bool) -> Result<(), String> {
+    let start = Instant::now();
+    let collect_stats = modifiers.report.is_some() || modifiers.profile;
+    let mut stats = if collect_stats { Some(RunStats::new(program.code.len())) } else { None };
+    let mut failed_at = 0;
+    let printer = Printer { output, buf: Vec::with_capacity(200), transcript: None, separate_lines };
+    let result = execute_on(program, modifiers, input, printer, memory, stats.as_mut(), &mut failed_at);
+
+    // Say where in the source it went wrong, if it's known
+    let result = result.map_err(|msg| match program.symbols.as_ref().and_then(|symbols| symbols.describe(failed_at)) {
+        Some(place) => format!("{}, {}", msg, place),
+        None => msg
+    });
+
+    if let (true, Some(stats)) = (modifiers.profile, &stats) {
+        stats.print_loops();
+    }
+
+    if let (Some(path), Some(stats)) = (&modifiers.report, &stats) {
+        write_report(path, &program.code, stats, start.elapsed(), memory.pages(), &result)?;
+    }
+
+    result
+}
+
+// Where a run with execute_bf gets its input from and prints to, and everything the debug
+// instructions need
+struct RunIo<'a, 'm, W: Write> {
+    modifiers: &'m Modifiers,
+    printer: Printer<'a, W>,
+    stdin: VecDeque<u8>,
+    // The last byte that was printed, so "\r\n" isn't turned into "\r\r\n" by *newline=crlf
+    last_printed: u8,
+    debug: DebugState,
+    snapshots: Option<Snapshots>,
+    // How many hex digits it takes to display a cell
+    cell_width: usize
+}
+
+impl<W: Write> Io for RunIo<'_, '_, W> {
+    fn read(&mut self, machine: &Machine) -> Result<Option<u8>, String> {
+        let modifiers = self.modifiers;
+        while self.stdin.is_empty() {
+            self.printer.end_line()?;
 
-            const HALF_MEM_DISPLAY: isize = 6;
+            if modifiers.sandbox {
+                return Err(format!("The program wanted more input than it was given at instruction {:X}, \
+                    *sandbox doesn't ask for more", machine.instr_ptr()));
+            }
 
-            print!("Memory: ");
-            let mut display_mem_ptr = mem_ptr - HALF_MEM_DISPLAY;
-            for _ in 0..HALF_MEM_DISPLAY {
-                print!("{:0>2X} ", memory.get(display_mem_ptr));
-                display_mem_ptr += 1;
+            if let Some(prompt) = &modifiers.prompt {
+                println!("{}", style(ansi_term::Color::Red.blink()).paint(prompt));
+            }
+            let mut line = String::new();
+            let read = std::io::stdin().read_line(&mut line)
+                .map_err(|error| format!("Couldn't read from stdin, {}", error))?;
+            if read == 0 {
+                // stdin is closed, asking again would never get anything
+                return Err(format!("The program wanted more input than it was given at instruction {:X}, \
+                    and stdin is closed", machine.instr_ptr()));
             }
+            self.printer.log_input(&line)?;
+            self.stdin.extend(modifiers.newline.translate_line(&line));
+        }
+
+        // Any byte can be read, not just ascii, input files can have anything in them
+        let c = self.stdin.pop_front().unwrap();
+        if modifiers.echo_input {
+            self.printer.write()?;
+            print!("{}", style(ansi_term::Style::new().dimmed()).paint((c as char).to_string()));
+            std::io::stdout().flush().map_err(|error| format!("Couldn't echo the input, {}", error))?;
+        }
+
+        Ok(Some(c))
+    }
+
+    fn print(&mut self, _: &Machine, byte: u8) -> Result<(), String> {
+        if self.modifiers.newline == Newline::Crlf && byte == b'\n' && self.last_printed != b'\r' {
+            self.printer.push(b'\r')?;
+        }
+        self.printer.push(byte)?;
+        self.last_printed = byte;
+        if let Some(snapshots) = &mut self.snapshots {
+            snapshots.output(byte);
+        }
+
+        Ok(())
+    }
+
+    fn has_input(&self) -> bool {
+        !self.stdin.is_empty()
+    }
+
+    fn debug<C: Cells>(&mut self, program: &Program, machine: &Machine, memory: &C) -> Result<(), String> {
+        // A sandboxed program can't pause or write files
+        if self.modifiers.sandbox {
+            return Ok(());
+        }
+
+        let instr_ptr = machine.instr_ptr();
+        if program.code[instr_ptr] == DEBUG_OP {
+            // Keep the output in order with what the debug operation prints
+            self.printer.end_line()?;
+
+            return run_debug_op(&mut self.debug, self.modifiers, &program.code, machine, memory, self.cell_width);
+        }
+
+        match program.label_at(instr_ptr) {
+            Some(label) => println!("DEBUG_DUMP '{}'", label),
+            None => println!("DEBUG_DUMP")
+        }
+        if let Some(snapshots) = &mut self.snapshots {
+            snapshots.take(memory, program.label_at(instr_ptr), instr_ptr, machine.mem_ptr(),
+                machine.steps() + 1, machine.visited())?;
+        }
+
+        Ok(())
+    }
+}
+
+// What *debug prints about the instruction at 'instr_ptr' before it runs, 'cell' is the current cell
+fn describe_instruction(code: &[u8], instr_ptr: usize, cell: u32) -> String {
+    let instruction = code[instr_ptr];
+    let name = instruction_name(instruction);
+    match instruction {
+        SHIFT_LEFT | SHIFT_RIGHT => shift_style().paint(name).to_string(),
+        INCREMENT | DECREMENT => modify_style().paint(name).to_string(),
+        PRINT => format!("{}: '{}'", modify_style().paint(name), cell as u8 as char),
+        LOOP_OPEN | LOOP_OPEN_WIDE if cell != 0 => format!("{}, entering loop", loop_style().paint(name)),
+        LOOP_OPEN | LOOP_OPEN_WIDE => format!("{}, exiting loop, offset: {}", loop_style().paint(name), loop_offset(code, instr_ptr)),
+        LOOP_CLOSE | LOOP_CLOSE_WIDE if cell == 0 => format!("{}, exiting loop", loop_style().paint(name)),
+        LOOP_CLOSE | LOOP_CLOSE_WIDE => format!("{}, continuing loop, offset: {}", loop_style().paint(name), loop_offset(code, instr_ptr)),
+        CALL => format!("{}, to {:X}", loop_style().paint(name), read_u32(code, instr_ptr + 1)),
+        RET => loop_style().paint(name).to_string(),
+        _ => io_style().paint(name).to_string()
+    }
+}
+
+// The error for a run that stopped at 'instr_ptr'
+fn fault_message(fault: Fault, instr_ptr: usize) -> String {
+    match fault {
+        // The Io already said where
+        Fault::Io(msg) => msg,
+        Fault::MaxSteps(_) => format!("The program {} at instruction {:X}, \
+            run it with *no_limits or a higher *max_steps to keep going", fault.describe(), instr_ptr),
+        Fault::MaxMemory(_) => format!("The program {} at instruction {:X}, \
+            run it with *no_limits or a higher *max_memory to keep going", fault.describe(), instr_ptr),
+        Fault::Drift(_, _) => format!("The program {} at instruction {:X}, with *trap_drift", fault.describe(), instr_ptr),
+        _ => format!("The program {} at instruction {:X}", fault.describe(), instr_ptr)
+    }
+}
+
+fn execute_on<W: Write, C: Cells>(
+        program: &Program, modifiers: &Modifiers, input: &[u8], mut printer: Printer<W>,
+        memory: &mut C, mut stats: Option<&mut RunStats>, current: &mut usize) -> Result<(), String> {
+    let bf = &program.code[..];
+    printer.transcript = match &modifiers.transcript {
+        Some(path) => Some(File::create(path)
+            .map_err(|error| format!("Couldn't create the transcript '{}', {}", path, error))?),
+        None => None
+    };
+    let snapshots = match &modifiers.snapshot_dir {
+        Some(dir) => Some(Snapshots::new(dir)?),
+        None => None
+    };
+    let cell_width = program.pragmas.cell_bits as usize / 4;
+    let mut io = RunIo {
+        modifiers,
+        printer,
+        stdin: modifiers.newline.translate_input(input).into(),
+        last_printed: 0,
+        debug: DebugState::default(),
+        snapshots,
+        cell_width
+    };
+
+    let mut machine = Machine::new(modifiers.limits(&program.pragmas), Random::from_clock());
+
+    while machine.instr_ptr() < bf.len() {
+        let instr_ptr = machine.instr_ptr();
+        *current = instr_ptr;
+        if let (Some(stats), false) = (&mut stats, machine.steps_left() == Some(0)) {
+            stats.step(bf[instr_ptr], instr_ptr);
+        }
+
+        let print_debug_info = modifiers.is_debug != (bf[instr_ptr] == DEBUG && !modifiers.sandbox);
+        if print_debug_info {
+            print!("instr: {:.>4X}, mem: {:.>4X} | ", instr_ptr, machine.mem_ptr());
+        }
+        if modifiers.is_debug && (bf[instr_ptr] != DEBUG || modifiers.sandbox) {
+            println!("{}", describe_instruction(bf, instr_ptr, memory.get(machine.mem_ptr())));
+        }
+
+        // Reading never has to wait here, it asks on stdin instead
+        if let Err(fault) = machine.step(program, memory, &mut io) {
+            io.printer.end_line()?;
 
-            print!("{}", shift_style().paint(format!("{:0>2X} ", memory.get(display_mem_ptr))));
-            assert_eq!(display_mem_ptr, mem_ptr);
-            display_mem_ptr += 1;
-            for _ in 0..HALF_MEM_DISPLAY {
-                print!("{:0>2X} ", memory.get(display_mem_ptr));
-                display_mem_ptr += 1;
+            return Err(fault_message(fault, instr_ptr));
+        }
+
+        if let Some(stats) = &mut stats {
+            let instruction = bf[instr_ptr];
+            let next = machine.instr_ptr();
+            if (instruction == LOOP_OPEN || instruction == LOOP_OPEN_WIDE) && next == instr_ptr + len(instruction) {
+                stats.enter_loop(instr_ptr);
+            }
+            // The jump goes to right after the loop open, which is as wide as the close
+            if (instruction == LOOP_CLOSE || instruction == LOOP_CLOSE_WIDE) && next < instr_ptr {
+                stats.repeat_loop(next - len(instruction));
             }
-            println!("");
-            println!("        {}^ {:.>4X}", "   ".repeat(HALF_MEM_DISPLAY as usize), mem_ptr);
+        }
 
-            println!("");
+        if print_debug_info {
+            print_memory(memory, machine.mem_ptr(), cell_width);
         }
     }
     
     // Print the final printing buffer
-    if print_buf.len() > 0 {
-        println!("{}", &print_buf);
+    io.printer.end_line()?;
+
+    io.debug.print_markers(machine.steps());
+
+    Ok(())
+}
+
+// The cells around the memory pointer, for *debug
+fn print_memory<C: Cells>(memory: &C, mem_ptr: isize, cell_width: usize) {
+    const HALF_MEM_DISPLAY: isize = 6;
+
+    print!("Memory: ");
+    let mut display_mem_ptr = mem_ptr - HALF_MEM_DISPLAY;
+    for _ in 0..HALF_MEM_DISPLAY {
+        print!("{:0>width$X} ", memory.get(display_mem_ptr), width = cell_width);
+        display_mem_ptr += 1;
+    }
+
+    print!("{}", shift_style().paint(format!("{:0>width$X} ", memory.get(display_mem_ptr), width = cell_width)));
+    assert_eq!(display_mem_ptr, mem_ptr);
+    display_mem_ptr += 1;
+    for _ in 0..HALF_MEM_DISPLAY {
+        print!("{:0>width$X} ", memory.get(display_mem_ptr), width = cell_width);
+        display_mem_ptr += 1;
+    }
+    println!();
+    println!("        {}^ {:.>4X}", " ".repeat((cell_width + 1) * HALF_MEM_DISPLAY as usize), mem_ptr);
+
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ InputSource, compile_test_program };
+
+    fn input_file(name: &str, contents: &[u8]) -> String {
+        let path = std::env::temp_dir().join(format!("brainfuck_run_{}_{}", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn inputs_are_read_in_the_order_they_were_given() {
+        let path = input_file("ordered", b"cd");
+        let mut modifiers = Modifiers::new();
+        modifiers.inputs = vec![InputSource::Text(String::from("ab")), InputSource::File(path.clone()), InputSource::Text(String::from("e"))];
+        let input = modifiers.read_inputs().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(input, b"abcde");
+
+        let program = compile_test_program(",.,.,.,.,.");
+        assert_eq!(execute_to_bytes(&program, &modifiers, &input).unwrap(), b"abcde");
+    }
+
+    #[test]
+    fn missing_input_files_are_errors() {
+        let mut modifiers = Modifiers::new();
+        modifiers.inputs = vec![InputSource::File(String::from("this file doesn't exist.txt"))];
+        let error = modifiers.read_inputs().unwrap_err();
+        assert!(error.contains("this file doesn't exist.txt"), "{}", error);
+    }
+
+    #[test]
+    fn any_byte_can_be_read() {
+        let path = input_file("binary", &[0xE9, 0x00, 0xFF]);
+        let mut modifiers = Modifiers::new();
+        modifiers.inputs = vec![InputSource::File(path.clone())];
+        let input = modifiers.read_inputs().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let program = compile_test_program(",.,.,.");
+        assert_eq!(execute_to_bytes(&program, &modifiers, &input).unwrap(), vec![0xE9, 0x00, 0xFF]);
+    }
+
+    #[test]
+    fn sandboxed_runs_stop_when_the_input_runs_out() {
+        let mut modifiers = Modifiers::new();
+        modifiers.sandbox = true;
+        let program = compile_test_program(",.,.");
+        let error = execute_to_bytes(&program, &modifiers, b"a").unwrap_err();
+        assert!(error.contains("more input than it was given"), "{}", error);
+    }
+
+    #[test]
+    fn trap_drift_stops_a_pointer_that_walks_off() {
+        let text = "+[>+]";
+        let program = compile_test_program(text);
+        let mut modifiers = Modifiers::new();
+        modifiers.trap_drift = Some(10);
+        let error = execute_to_bytes(&program, &modifiers, &[]).unwrap_err();
+        assert!(error.contains("drifted to cell 11"), "{}", error);
+    }
+
+    #[test]
+    fn limits_from_pragmas_are_enforced_unless_told_otherwise() {
+        let program = compile_test_program("#pragma max_steps 20\n#pragma max_memory 5\n+[>+]");
+        let error = execute_to_bytes(&program, &Modifiers::new(), &[]).unwrap_err();
+        assert!(error.starts_with("The program used more than 5 cells"), "{}", error);
+
+        let mut modifiers = Modifiers::new();
+        modifiers.max_memory = Some(100);
+        let error = execute_to_bytes(&program, &modifiers, &[]).unwrap_err();
+        assert!(error.starts_with("The program ran more than 20 instructions"), "{}", error);
+
+        let program = compile_test_program("#pragma max_steps 20\n+50[-].");
+        assert!(execute_to_bytes(&program, &Modifiers::new(), &[]).is_err());
+        let mut modifiers = Modifiers::new();
+        modifiers.no_limits = true;
+        assert_eq!(execute_to_bytes(&program, &modifiers, &[]).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn output_is_kept_as_bytes() {
+        let program = compile_test_program("+FF . -7F . >.");
+        assert_eq!(execute_to_bytes(&program, &Modifiers::new(), &[]).unwrap(), vec![0xFF, 0x80, 0]);
+
+        // What *out does, every byte there is and more than fits in the buffer of the printer
+        let path = input_file("out", b"");
+        let program = compile_test_program("+[.+] +[.+]");
+        let mut file = std::fs::File::create(&path).unwrap();
+        execute_raw(&program, &Modifiers::new(), &[], &mut file).unwrap();
+        let written = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let every_byte: Vec<u8> = (1..=255).collect();
+        assert_eq!(written, [&every_byte[..], &every_byte[..]].concat());
+    }
+
+    #[test]
+    fn terminals_get_utf8_text() {
+        let mut shown = Vec::new();
+        {
+            let mut output = TerminalOutput::new(&mut shown);
+            // "é" cut in half between two writes, and a byte that can't start a character
+            output.write_all(&[b'a', 0xC3]).unwrap();
+            output.write_all(&[0xA9, 0xFF, b'b']).unwrap();
+        }
+        assert_eq!(String::from_utf8(shown).unwrap(), "a\u{e9}\u{fffd}b");
     }
-}
\ No newline at end of file
+}
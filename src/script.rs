@@ -0,0 +1,259 @@
+use crate::{ CompileOptions, compile_program };
+use crate::binary::{ FileKind, Program, detect_file_kind, read_bin_from_file };
+use crate::interpreter::{ Interpreter, State };
+use crate::source::read_source;
+
+// A script of commands that poke at a program, one per line, so a session of poking around can
+// be saved and run again later, ex:
+//  load bf_examples/example.bf
+//  input abc\n
+//  run
+//  assert_output Hello
+//  snippet >2 +5
+//  assert_cell 2 5
+//  dump 0 4
+// Everything after a ';' is a comment, "\;" is a ';' that isn't.
+pub struct ScriptRun {
+    // What the commands printed, like dumps
+    pub log: Vec<String>,
+    // The line of the command that failed, and why
+    pub failure: Option<(usize, String)>,
+    // How many commands ran before it was done, including the one that failed
+    pub commands: usize
+}
+
+pub fn run_script(text: &str, std_lib: Option<&[char]>) -> ScriptRun {
+    let mut session = Session::new(std_lib);
+    let mut commands = 0;
+    for (i, line) in text.lines().enumerate() {
+        let (command, argument) = match parse_line(line) {
+            Some(value) => value,
+            None => continue
+        };
+
+        commands += 1;
+        if let Err(error) = session.run_command(command, argument) {
+            return ScriptRun { log: session.take_log(), failure: Some((i + 1, error)), commands };
+        }
+    }
+
+    ScriptRun { log: session.take_log(), failure: None, commands }
+}
+
+// The command and its argument, None if the line is empty or a comment
+pub fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let end = line.char_indices()
+        .find(|(i, c)| *c == ';' && !line[..*i].ends_with('\\'))
+        .map_or(line.len(), |(i, _)| i);
+    let line = line[..end].trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    Some(match line.find(' ') {
+        Some(index) => (&line[..index], line[index + 1..].trim()),
+        None => (line, "")
+    })
+}
+
+// The program and its tape, that the commands poke at one after the other
+pub struct Session<'a> {
+    interpreter: Option<Interpreter>,
+    // What the program printed since the last 'assert_output'
+    output: Vec<u8>,
+    // What the program printed since the last take_printed
+    printed: Vec<u8>,
+    log: Vec<String>,
+    std_lib: Option<&'a [char]>
+}
+
+impl<'a> Session<'a> {
+    pub fn new(std_lib: Option<&'a [char]>) -> Session<'a> {
+        Session { interpreter: None, output: Vec::new(), printed: Vec::new(), log: Vec::new(), std_lib }
+    }
+
+    // What the commands printed since the last time, like dumps
+    pub fn take_log(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.log)
+    }
+
+    // What the program printed since the last time
+    pub fn take_printed(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.printed)
+    }
+
+    pub fn run_command(&mut self, command: &str, argument: &str) -> Result<(), String> {
+        match command {
+            "load" => {
+                self.interpreter = Some(Interpreter::new(self.load(argument)?));
+                self.output.clear();
+            },
+            "input" => self.interpreter()?.push_input(unescape(argument).as_bytes()),
+            "run" => {
+                let fuel = match argument {
+                    "" => None,
+                    fuel => Some(fuel.parse::<u64>().map_err(|_| format!("Expected a number of instructions, got '{}'", fuel))?)
+                };
+                self.run(fuel)?;
+            },
+            "snippet" => {
+                let program = self.compile(argument.chars().collect())?;
+                match &mut self.interpreter {
+                    Some(interpreter) => interpreter.load(program),
+                    None => self.interpreter = Some(Interpreter::new(program))
+                }
+                self.run(None)?;
+            },
+            "assert_output" => {
+                let expected = unescape(argument);
+                let output = String::from_utf8_lossy(&self.output).into_owned();
+                self.output.clear();
+                if output != expected {
+                    return Err(format!("Expected the program to print {:?}, but it printed {:?}", expected, output));
+                }
+            },
+            "assert_cell" => {
+                let (index, expected) = match argument.split_whitespace().collect::<Vec<_>>()[..] {
+                    [index, value] => (parse_index(index)?, u32::from_str_radix(value, 16)
+                        .map_err(|_| format!("Expected a value in hex, got '{}'", value))?),
+                    _ => return Err(String::from("Expected 'assert_cell [cell] [value in hex]'"))
+                };
+                let value = self.interpreter()?.cell(index);
+                if value != expected {
+                    return Err(format!("Expected cell {} to be {:X}, but it's {:X}", index, expected, value));
+                }
+            },
+            "assert_ptr" => {
+                let expected = parse_index(argument)?;
+                let mem_ptr = self.interpreter()?.mem_ptr();
+                if mem_ptr != expected {
+                    return Err(format!("Expected the memory pointer to be at {}, but it's at {}", expected, mem_ptr));
+                }
+            },
+            "dump" => {
+                let (from, to) = match argument.split_whitespace().collect::<Vec<_>>()[..] {
+                    [from, to] => (parse_index(from)?, parse_index(to)?),
+                    _ => return Err(String::from("Expected 'dump [first cell] [last cell]'"))
+                };
+                let interpreter = self.interpreter()?;
+                let cells: Vec<String> = (from..=to).map(|cell| format!("{:02X}", interpreter.cell(cell))).collect();
+                let line = format!("DUMP cells {} to {}, mem: {} | {}", from, to, interpreter.mem_ptr(), cells.join(" "));
+                self.log.push(line);
+            },
+            _ => return Err(format!("Unknown command '{}', expected load, input, run, snippet, \
+                assert_output, assert_cell, assert_ptr or dump", command))
+        }
+
+        Ok(())
+    }
+
+    fn interpreter(&mut self) -> Result<&mut Interpreter, String> {
+        self.interpreter.as_mut().ok_or_else(|| String::from("Nothing is loaded, use 'load [file]' first"))
+    }
+
+    // Runs until the program is done or needs input, a program that fails fails the script
+    fn run(&mut self, fuel: Option<u64>) -> Result<(), String> {
+        let interpreter = self.interpreter()?;
+        let state = match fuel {
+            Some(fuel) => interpreter.run_for_instructions(fuel).0.clone(),
+            None => interpreter.run().clone()
+        };
+        let output = interpreter.take_output();
+        self.output.extend(&output);
+        self.printed.extend(output);
+
+        match state {
+            State::Failed(offset, msg) => Err(format!("The program stopped at instruction {:X}, it {}", offset, msg)),
+            _ => Ok(())
+        }
+    }
+
+    fn compile(&self, source: Vec<char>) -> Result<Program, String> {
+        compile_program(source, self.std_lib.map(|std_lib| std_lib.to_vec()), CompileOptions::default())
+            .map_err(|failure| failure.describe())
+    }
+
+    fn load(&self, path: &str) -> Result<Program, String> {
+        let program = match detect_file_kind(path).map_err(|error| format!("Couldn't read '{}', {}", path, error))? {
+            FileKind::Binary => read_bin_from_file(path).map_err(|error| format!("Couldn't load '{}', {}", path, error))?,
+            FileKind::Source => self.compile(read_source(path)?)?
+        };
+
+        if let Some((_, name)) = program.imports.first() {
+            return Err(format!("'{}' calls '{}' from a library binary, which scripts can't load", path, name));
+        }
+        Ok(program)
+    }
+}
+
+fn parse_index(text: &str) -> Result<isize, String> {
+    text.parse().map_err(|_| format!("Expected the index of a cell, got '{}'", text))
+}
+
+// "\n", "\t", "\;", "\s" (a space that isn't trimmed away) and "\\" in the arguments of 'input' and 'assert_output'
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('n')) => result.push('\n'),
+            ('\\', Some('t')) => result.push('\t'),
+            ('\\', Some(';')) => result.push(';'),
+            ('\\', Some('s')) => result.push(' '),
+            ('\\', Some('\\')) => result.push('\\'),
+            _ => {
+                result.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+    result
+}
+
+// The other way around, so anything can be written as an argument
+pub fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let last = text.chars().count().saturating_sub(1);
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            ' ' if i == 0 || i == last => result.push_str("\\s"),
+            '\n' => result.push_str("\\n"),
+            '\t' => result.push_str("\\t"),
+            ';' => result.push_str("\\;"),
+            '\\' => result.push_str("\\\\"),
+            _ => result.push(c)
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_stop_at_the_first_failing_command() {
+        let script = "; snippets run on the same tape\nsnippet +5 >\ninput ab\nsnippet , . > , .\n\
+            assert_output ab\nassert_cell 0 5\nassert_ptr 2\ndump 0 2\nassert_cell 2 7\nassert_cell 0 5";
+        let result = run_script(script, None);
+        assert_eq!(result.log, vec![String::from("DUMP cells 0 to 2, mem: 2 | 05 61 62")]);
+        assert_eq!(result.commands, 8);
+        let (line, error) = result.failure.expect("The script should fail");
+        assert_eq!(line, 9);
+        assert_eq!(error, "Expected cell 2 to be 7, but it's 62");
+    }
+
+    #[test]
+    fn escaped_arguments_parse_back_the_same() {
+        let text = " a;b\\c\n ";
+        let mut session = Session::new(None);
+        for line in [String::from("snippet >"), format!("input {} ; a comment", escape(text)), String::from("snippet ,[.,]"),
+                format!("assert_output {}", escape(text))] {
+            let (command, argument) = parse_line(&line).unwrap();
+            session.run_command(command, argument).unwrap_or_else(|error| panic!("'{}' failed, {}", line, error));
+        }
+        assert_eq!(session.take_printed(), text.as_bytes());
+        assert_eq!(parse_line(" ; just a comment"), None);
+    }
+}
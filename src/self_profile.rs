@@ -0,0 +1,112 @@
+use std::alloc::{ GlobalAlloc, Layout, System };
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicBool, AtomicUsize, Ordering };
+use std::time::{ Duration, Instant };
+
+// "*self_profile", how long the phases of the compiler take and how much memory they use, for
+// finding out why a big program is slow to compile. The memory is only known when the executable
+// uses CountingAlloc as its allocator, like main does
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PHASES: Mutex<Vec<Phase>> = Mutex::new(Vec::new());
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+// The system allocator, counting how much is allocated while profiling
+pub struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() && is_enabled() {
+            allocated(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if is_enabled() {
+            freed(layout.size());
+        }
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() && is_enabled() {
+            freed(layout.size());
+            allocated(new_size);
+        }
+        new_ptr
+    }
+}
+
+fn allocated(size: usize) {
+    let live = LIVE.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+    ALLOCATED.fetch_add(size, Ordering::Relaxed);
+}
+
+// Memory allocated before profiling started isn't counted, so this can't go below zero
+fn freed(size: usize) {
+    let _ = LIVE.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |live| Some(live.saturating_sub(size)));
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+pub struct Phase {
+    pub name: &'static str,
+    pub time: Duration,
+    // How many bytes were allocated in total during the phase
+    pub allocated: usize,
+    // The most memory that was in use at once during the phase, on top of what was in use before it
+    pub peak: usize
+}
+
+// Runs a phase of the compiler, and remembers how long it took and how much memory it used
+pub fn phase<T>(name: &'static str, run: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return run();
+    }
+
+    let live = LIVE.load(Ordering::Relaxed);
+    PEAK.store(live, Ordering::Relaxed);
+    let allocated = ALLOCATED.load(Ordering::Relaxed);
+    let start = Instant::now();
+
+    let result = run();
+
+    let phase = Phase {
+        name,
+        time: start.elapsed(),
+        allocated: ALLOCATED.load(Ordering::Relaxed) - allocated,
+        peak: PEAK.load(Ordering::Relaxed).saturating_sub(live)
+    };
+    PHASES.lock().unwrap().push(phase);
+    result
+}
+
+// The phases that ran so far, in the order they ran
+pub fn take_phases() -> Vec<Phase> {
+    std::mem::take(&mut *PHASES.lock().unwrap())
+}
+
+// A number of bytes in a unit that's easy to read, ex: "1.5 MB"
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
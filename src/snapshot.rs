@@ -0,0 +1,91 @@
+use std::collections::{ BTreeMap, VecDeque };
+use std::path::PathBuf;
+
+use crate::inf_memory::Cells;
+use crate::emit::json_string;
+
+// How many of the last bytes the program printed go in every snapshot
+const RECENT_OUTPUT: usize = 256;
+
+// "*snapshot_dir=dir" writes a snapshot to the directory at every DEBUG instruction,
+// so the checkpoints of a long run can be looked at after it's done. They're numbered
+// in the order they were taken, 0000.txt, 0001.txt and so on, and look like this:
+//  ; snapshot 1
+//  label loop_start
+//  instruction 1A
+//  mem_ptr 3
+//  steps 1234
+//  changed 2
+//  3: 0 -> 41
+//  4: 7 -> 0
+//  output "Hello\n"
+// The changed cells are the ones that are different from the last snapshot, and the
+// output is the last bytes the program printed, as a json string.
+pub struct Snapshots {
+    dir: PathBuf,
+    count: usize,
+    // The cells that weren't zero in the last snapshot
+    cells: BTreeMap<isize, u32>,
+    recent_output: VecDeque<u8>
+}
+
+impl Snapshots {
+    pub fn new(dir: &str) -> Result<Snapshots, String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|error| format!("Couldn't create the snapshot directory '{}', {}", dir, error))?;
+
+        Ok(Snapshots {
+            dir: PathBuf::from(dir),
+            count: 0,
+            cells: BTreeMap::new(),
+            recent_output: VecDeque::with_capacity(RECENT_OUTPUT)
+        })
+    }
+
+    pub fn output(&mut self, byte: u8) {
+        if self.recent_output.len() == RECENT_OUTPUT {
+            self.recent_output.pop_front();
+        }
+        self.recent_output.push_back(byte);
+    }
+
+    // 'visited' is the lowest and highest cell the program has been at, no other cells can have changed
+    pub fn take<C: Cells>(
+            &mut self, memory: &C, label: Option<&str>, instr_ptr: usize, mem_ptr: isize,
+            steps: u64, visited: (isize, isize)) -> Result<(), String> {
+        let mut changed = Vec::new();
+        for cell in visited.0..=visited.1 {
+            let value = memory.get(cell);
+            let old = self.cells.get(&cell).cloned().unwrap_or(0);
+            if value == old {
+                continue;
+            }
+
+            changed.push(format!("{}: {:X} -> {:X}", cell, old, value));
+            if value == 0 {
+                self.cells.remove(&cell);
+            }else{
+                self.cells.insert(cell, value);
+            }
+        }
+
+        let mut text = format!("; snapshot {}\n", self.count);
+        if let Some(label) = label {
+            text.push_str(&format!("label {}\n", label));
+        }
+        text.push_str(&format!("instruction {:X}\nmem_ptr {}\nsteps {}\nchanged {}\n",
+            instr_ptr, mem_ptr, steps, changed.len()));
+        for line in changed {
+            text.push_str(&line);
+            text.push('\n');
+        }
+        let output: Vec<u8> = self.recent_output.iter().cloned().collect();
+        text.push_str(&format!("output {}\n", json_string(&String::from_utf8_lossy(&output))));
+
+        let path = self.dir.join(format!("{:04}.txt", self.count));
+        std::fs::write(&path, text)
+            .map_err(|error| format!("Couldn't write the snapshot '{}', {}", path.display(), error))?;
+        self.count += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs::File;
+
+// Source files bigger than this get a warning, since the lexer keeps
+// the whole file in memory as chars(4 bytes per character)
+pub const LARGE_SOURCE_BYTES: u64 = 16 * 1024 * 1024;
+
+// How much of the start of the file is checked for nul bytes
+// to decide if the file is binary junk rather than text
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+// Reads a source file into characters.
+// Files that look like binary junk are errors, invalid UTF-8 in
+// otherwise text-like files gets replaced with U+FFFD with a warning
+// pointing at the first bad byte.
+pub fn read_source(file_name: &str) -> Result<Vec<char>, String> {
+    let file = File::open(file_name)
+        .map_err(|error| format!("Couldn't open '{}', {}", file_name, error))?;
+    let size = file.metadata()
+        .map_err(|error| format!("Couldn't read metadata of '{}', {}", file_name, error))?
+        .len();
+
+    if size > LARGE_SOURCE_BYTES {
+        crate::diagnostics::warn("large_source", Some(file_name),
+            format!("'{}' is {} MiB big, compiling it will take a lot of memory", file_name, size / (1024 * 1024)));
+    }
+
+    read_bytes(file, size, file_name, |bytes| decode_source(file_name, bytes))
+}
+
+#[cfg(feature = "mmap")]
+fn read_bytes<F>(file: File, size: u64, file_name: &str, decode: F) -> Result<Vec<char>, String>
+        where F: FnOnce(&[u8]) -> Result<Vec<char>, String> {
+    // Mapping an empty file is an error on some platforms
+    if size == 0 {
+        return decode(&[]);
+    }
+
+    // Safety: the map is only read while decoding, if someone truncates the file
+    // while we are reading it that's on them.
+    let map = unsafe { memmap2::Mmap::map(&file) }
+        .map_err(|error| format!("Couldn't map '{}' into memory, {}", file_name, error))?;
+    decode(&map[..])
+}
+
+#[cfg(not(feature = "mmap"))]
+fn read_bytes<F>(mut file: File, size: u64, file_name: &str, decode: F) -> Result<Vec<char>, String>
+        where F: FnOnce(&[u8]) -> Result<Vec<char>, String> {
+    use std::io::prelude::*;
+
+    let mut bytes = Vec::with_capacity(size as usize);
+    file.read_to_end(&mut bytes)
+        .map_err(|error| format!("Couldn't read '{}', {}", file_name, error))?;
+    decode(&bytes[..])
+}
+
+fn decode_source(file_name: &str, bytes: &[u8]) -> Result<Vec<char>, String> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if let Some(index) = sniff.iter().position(|b| *b == 0) {
+        return Err(format!(
+            "'{}' doesn't look like a source file, it has a nul byte at byte {}", file_name, index));
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(text.chars().collect()),
+        Err(error) => {
+            let valid = &bytes[..error.valid_up_to()];
+            let line = valid.iter().filter(|b| **b == b'\n').count() + 1;
+            let column = valid.iter().rev().take_while(|b| **b != b'\n').count();
+            crate::diagnostics::warn("invalid_utf8", Some(file_name), format!(
+                "'{}' is not valid UTF-8, first invalid byte at ({}, {}), invalid bytes are replaced with '{}'",
+                file_name, line, column, std::char::REPLACEMENT_CHARACTER));
+
+            Ok(String::from_utf8_lossy(bytes).chars().collect())
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::Newline;
+use crate::instructions::*;
+use crate::binary::Program;
+use crate::trace::{ Input, trace_run };
+use crate::machine::Limits;
+use crate::introspect::fact_symbol;
+
+// Moves the current cell from 'from' to 'to' with as few '+' or '-' as possible,
+// going the other way around if that's shorter
+fn change_cell(code: &mut Vec<u8>, from: u32, to: u32, cell_bits: u8) {
+    let modulus = 1u64 << cell_bits;
+    let up = (to as u64 + modulus - from as u64) % modulus;
+    let down = (modulus - up) % modulus;
+    if up <= down {
+        code.extend(std::iter::repeat_n(INCREMENT, up as usize));
+    }else{
+        code.extend(std::iter::repeat_n(DECREMENT, down as usize));
+    }
+}
+
+fn move_to(code: &mut Vec<u8>, from: isize, to: isize) {
+    let instruction = if to > from { SHIFT_RIGHT } else { SHIFT_LEFT };
+    code.extend(std::iter::repeat_n(instruction, (to - from).unsigned_abs()));
+}
+
+// Runs the program ahead of time with all of its input, and writes a program that does the
+// same thing without any of the work, it prints what the program printed and leaves the
+// tape the way the program left it. Only programs that finish with the input they're given,
+// within 'limits', can be specialized, since a program that needs more input
+// does something different depending on it.
+// The debug instructions are gone, they don't run ahead of time
+pub fn specialize(program: &Program, std_in: &[u8], limits: Limits) -> Result<Program, String> {
+    // The fuel and the random bytes would be different when the specialized program runs
+    let mut index = 0;
+    while index < program.code.len() {
+        if program.code[index] == INTROSPECT && matches!(program.code[index + 1], FACT_FUEL | FACT_RANDOM) {
+            return Err(format!("Couldn't run the program ahead of time, it uses \"${}\" at instruction {:X}",
+                fact_symbol(program.code[index + 1]), index));
+        }
+        index += len(program.code[index]);
+    }
+
+    let mut input = Input { data: std_in.to_vec(), can_ask: false, newline: Newline::Raw };
+    let trace = trace_run(program, &mut input, limits);
+    if let Some((offset, msg)) = &trace.error {
+        return Err(format!("Couldn't run the program ahead of time, it stopped at instruction {:X}, it {}", offset, msg));
+    }
+
+    let cell_bits = program.pragmas.cell_bits;
+    let mut code = Vec::new();
+
+    // The first cell is always zero at the start, so it holds the printed bytes until the tape is set up
+    let mut current = 0;
+    for byte in trace.output_bytes() {
+        change_cell(&mut code, current, byte as u32, cell_bits);
+        code.push(PRINT);
+        current = byte as u32;
+    }
+    change_cell(&mut code, current, trace.memory.get(0), cell_bits);
+
+    let mut mem_ptr = 0;
+    for cell in trace.lowest_ptr..=trace.highest_ptr {
+        let value = trace.memory.get(cell);
+        if cell != 0 && value != 0 {
+            move_to(&mut code, mem_ptr, cell);
+            change_cell(&mut code, 0, value, cell_bits);
+            mem_ptr = cell;
+        }
+    }
+    move_to(&mut code, mem_ptr, trace.mem_ptr);
+
+    Ok(Program::new(program.pragmas, code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{ Interpreter, State };
+    use crate::compile_test_program;
+
+    #[test]
+    fn specialized_programs_print_the_same_without_input() {
+        let program = compile_test_program(",[->+2<] > . <2 - . \"ok\"");
+        let specialized = specialize(&program, b"\x05", Limits::default()).unwrap();
+        assert!(!specialized.code.contains(&READ));
+
+        let mut interpreter = Interpreter::new(program);
+        interpreter.push_input(b"\x05");
+        assert_eq!(interpreter.run(), &State::Finished);
+        let output = interpreter.take_output();
+        let mut interpreter = Interpreter::new(specialized);
+        assert_eq!(interpreter.run(), &State::Finished);
+        assert_eq!(interpreter.take_output(), output);
+
+        // Programs that need more input than they're given can't be run ahead of time
+        assert!(specialize(&compile_test_program(","), b"", Limits::default()).is_err());
+    }
+}
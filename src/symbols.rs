@@ -0,0 +1,87 @@
+use crate::Compiler;
+use crate::compiler::CompiledUnit;
+use crate::parse_bf::Loc;
+
+// Where the code of a program came from in the source, so runtime errors can
+// say which macro and line they happened in instead of just an offset.
+// Only programs compiled from source have these, binaries don't store them
+#[derive(Clone, Debug)]
+pub struct DebugSymbols {
+    // Where the code of every token starts, as (offset, where the token is), sorted by offset.
+    // The code of a macro starts with the token that uses it, and then its own tokens
+    locs: Vec<(usize, Loc)>,
+    // Where the code of every unit ended up, as (name, start, end)
+    units: Vec<(String, usize, usize)>
+}
+
+impl DebugSymbols {
+    // The symbols of a linked program, 'name' is the unit it was linked from, usually "src"
+    pub fn new(compiler: &Compiler, name: &str, program: &CompiledUnit) -> DebugSymbols {
+        let mut units = vec![(String::from(name), 0, program.code.len())];
+        units.extend(program.copies.iter().map(|(id, start, end)| (compiler.name_of(*id), *start, *end)));
+
+        DebugSymbols {
+            locs: program.locs.clone(),
+            units
+        }
+    }
+
+    // Moves the symbols over to an optimized program, 'origins' is where every byte
+    // of the optimized code came from in the original
+    pub fn remap(&self, origins: &[usize]) -> DebugSymbols {
+        let mut locs = Vec::new();
+        for (offset, origin) in origins.iter().enumerate() {
+            if offset > 0 && origins[offset - 1] == *origin {
+                continue;
+            }
+
+            if let Some(loc) = self.loc_at(*origin) {
+                locs.push((offset, loc));
+            }
+        }
+
+        let units = self.units.iter()
+            .map(|(name, start, end)| (name.clone(),
+                origins.partition_point(|origin| origin < start),
+                origins.partition_point(|origin| origin < end)))
+            .filter(|(_, start, end)| start < end)
+            .collect();
+
+        DebugSymbols { locs, units }
+    }
+
+    // The symbols of a program that was moved 'by' bytes further into the code
+    pub fn shifted(self, by: usize) -> DebugSymbols {
+        DebugSymbols {
+            locs: self.locs.into_iter().map(|(offset, loc)| (offset + by, loc)).collect(),
+            units: self.units.into_iter().map(|(name, start, end)| (name, start + by, end + by)).collect()
+        }
+    }
+
+    fn loc_at(&self, offset: usize) -> Option<Loc> {
+        match self.locs.partition_point(|(start, _)| *start <= offset) {
+            0 => None,
+            index => Some(self.locs[index - 1].1)
+        }
+    }
+
+    // Where the instruction at 'offset' came from, ex: "in 'src/foo' at (3, 4), used by 'src'".
+    // The innermost macro is first
+    pub fn describe(&self, offset: usize) -> Option<String> {
+        let loc = self.loc_at(offset)?;
+        let mut units: Vec<&(String, usize, usize)> = self.units.iter()
+            .filter(|(_, start, end)| (*start..*end).contains(&offset))
+            .collect();
+        // The innermost unit is the smallest one around the offset
+        units.sort_by_key(|(_, start, end)| end - start);
+
+        let (innermost, rest) = units.split_first()?;
+        let mut text = format!("in '{}' at {}", innermost.0, loc);
+        if !rest.is_empty() {
+            let names: Vec<String> = rest.iter().map(|(name, _, _)| format!("'{}'", name)).collect();
+            text.push_str(&format!(", used by {}", names.join(", ")));
+        }
+
+        Some(text)
+    }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::time::{ Duration, Instant };
+
+use crate::{ CompileOptions, Newline, compile_units };
+use crate::binary::Program;
+use crate::interpreter::{ Interpreter, State };
+use crate::parse_bf::Loc;
+use crate::source::read_source;
+use crate::trace::{ Input, trace_run };
+use crate::machine::Limits;
+
+// How many instructions a test may run before it's considered stuck
+pub const TEST_FUEL: u64 = 100_000_000;
+
+// How many times every benchmark is run, the fastest run is the one that counts
+const BENCH_RUNS: usize = 5;
+
+// A test declared in the source with annotations in comments:
+//  ;@input "abc"
+//  ;@expect "cba"
+// The input is optional, and the test belongs to the macro it's declared in.
+#[derive(Clone, Debug)]
+pub struct TestCase {
+    pub input: String,
+    pub expect: String,
+    pub loc: Loc
+}
+
+pub struct TestResult {
+    pub unit: String,
+    pub case: TestCase,
+    // None if the test passed
+    pub failure: Option<String>
+}
+
+// Macros called "bench_..." are benchmarks. They're run on their own a few times without any input,
+// and how many instructions they ran and how long they took is compared to a saved baseline
+pub struct UnitBench {
+    pub unit: String,
+    pub instructions: u64,
+    // The fastest of the runs
+    pub time: Duration,
+    // None if it ran to the end
+    pub failure: Option<String>
+}
+
+pub struct FileResults {
+    pub tests: Vec<TestResult>,
+    pub benches: Vec<UnitBench>
+}
+
+fn is_bench(name: &str) -> bool {
+    name.rsplit('/').next().is_some_and(|name| name.starts_with("bench_"))
+}
+
+fn run_bench(unit: String, make_program: impl Fn() -> Program) -> UnitBench {
+    let mut bench = UnitBench { unit, instructions: 0, time: Duration::MAX, failure: None };
+    for _ in 0..BENCH_RUNS {
+        let mut interpreter = Interpreter::new(make_program());
+        let start = Instant::now();
+        let state = interpreter.run_for_instructions(TEST_FUEL).0.clone();
+        let time = start.elapsed();
+
+        bench.failure = match state {
+            State::Finished => None,
+            State::NeedsInput => Some(String::from("It wants input, benchmarks don't get any")),
+            State::Running => Some(format!("It ran for more than {} instructions", TEST_FUEL)),
+            State::Failed(offset, msg) => Some(format!("The program stopped at instruction {:X}, it {}", offset, msg))
+        };
+        if bench.failure.is_some() {
+            break;
+        }
+
+        bench.instructions = interpreter.steps();
+        bench.time = bench.time.min(time);
+    }
+
+    bench
+}
+
+fn run_test(program: &Program, case: &TestCase) -> Option<String> {
+    let mut input = Input { data: case.input.bytes().collect(), can_ask: false, newline: Newline::Trim };
+    let trace = trace_run(program, &mut input, Limits { max_steps: Some(TEST_FUEL), ..Limits::default() });
+    let output = trace.output_bytes();
+
+    if let Some((offset, msg)) = trace.error {
+        return Some(format!("The program stopped at instruction {:X}, it {}", offset, msg));
+    }
+
+    if output != case.expect.as_bytes() {
+        return Some(format!("Expected {:?}, got {:?}", case.expect, String::from_utf8_lossy(&output)));
+    }
+
+    None
+}
+
+// Compiles a file and runs every test and benchmark declared in it, every macro with
+// tests and every benchmark is run on its own as if it was the whole program
+pub fn test_file(path: &str, std_lib: Option<&[char]>) -> Result<FileResults, String> {
+    let source = read_source(path)?;
+    let (compiler, pragmas) = compile_units(source, std_lib.map(|std_lib| std_lib.to_vec()), CompileOptions::default())
+        .map_err(|failure| failure.describe())?;
+
+    let mut results = Vec::new();
+    let mut benches = Vec::new();
+    for unit in compiler.units() {
+        if is_bench(&unit.name) {
+            let compiled = compiler.link(&unit.name)
+                .ok_or_else(|| format!("The benchmark '{}' didn't compile", unit.name))?;
+            benches.push(run_bench(unit.name.clone(), || Program::new(pragmas, compiled.code.clone())));
+        }
+
+        if unit.tests.is_empty() {
+            continue;
+        }
+
+        let compiled = compiler.link(&unit.name)
+            .ok_or_else(|| format!("'{}' has tests but didn't compile", unit.name))?;
+        let mut program = Program::new(pragmas, compiled.code);
+        program.labels = compiled.labels;
+
+        for case in unit.tests {
+            let failure = run_test(&program, &case);
+            results.push(TestResult {
+                unit: unit.name.clone(),
+                case,
+                failure
+            });
+        }
+    }
+
+    Ok(FileResults { tests: results, benches })
+}
+
+// The baseline of the benchmarks in a file is saved next to it, "std.bf" goes in "std.bf.bench".
+// Every line is "name instructions nanoseconds"
+pub fn baseline_path(path: &str) -> String {
+    format!("{}.bench", path)
+}
+
+// The saved baseline of a file as name -> (instructions, time), empty if there isn't one yet
+pub fn read_baseline(path: &str) -> Result<HashMap<String, (u64, Duration)>, String> {
+    let path = baseline_path(path);
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(error) => return Err(format!("Couldn't read the baseline '{}', {}", path, error))
+    };
+
+    let mut baseline = HashMap::new();
+    for (number, line) in text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let parsed = match parts[..] {
+            [name, instructions, nanos] => instructions.parse().ok()
+                .zip(nanos.parse().ok())
+                .map(|(instructions, nanos)| (String::from(name), (instructions, Duration::from_nanos(nanos)))),
+            _ => None
+        };
+        let (name, result) = parsed.ok_or_else(|| format!("Line {} of the baseline '{}' is broken", number + 1, path))?;
+        baseline.insert(name, result);
+    }
+
+    Ok(baseline)
+}
+
+// Saves the benchmarks that ran to the end as the new baseline of the file
+pub fn write_baseline(path: &str, benches: &[UnitBench]) -> Result<(), String> {
+    let path = baseline_path(path);
+    let text: String = benches.iter()
+        .filter(|bench| bench.failure.is_none())
+        .map(|bench| format!("{} {} {}\n", bench.unit, bench.instructions, bench.time.as_nanos()))
+        .collect();
+    std::fs::write(&path, text).map_err(|error| format!("Couldn't write the baseline '{}', {}", path, error))
+}
+
+// How a benchmark compares to its baseline, as how many percent more instructions and time it took.
+// It's a regression if either grew by more than 'threshold' percent
+pub fn compare_to_baseline(bench: &UnitBench, baseline: (u64, Duration), threshold: f64) -> (f64, f64, bool) {
+    let change = |now: f64, then: f64| if then == 0.0 { 0.0 } else { (now - then) * 100.0 / then };
+    let instructions = change(bench.instructions as f64, baseline.0 as f64);
+    let time = change(bench.time.as_secs_f64(), baseline.1.as_secs_f64());
+    (instructions, time, instructions > threshold || time > threshold)
+}
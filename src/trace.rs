@@ -0,0 +1,182 @@
+use crate::{ Memory, Newline };
+use crate::binary::Program;
+use crate::machine::{ Machine, Limits, Flow, Io };
+use crate::color::style;
+use crate::introspect::Random;
+
+// Input for a traced run. If it's allowed to ask, running out of input asks
+// for more on stdin and remembers it, so it can be replayed to another run
+pub struct Input {
+    pub data: Vec<u8>,
+    pub can_ask: bool,
+    // What happens to the line endings of lines typed in the terminal
+    pub newline: Newline
+}
+
+impl Input {
+    fn next(&mut self, index: usize) -> Option<u8> {
+        while index >= self.data.len() {
+            if !self.can_ask {
+                return None;
+            }
+
+            println!("{}",
+                style(ansi_term::Color::Red.blink())
+                .paint("The program requests some more characters to process: "));
+            let mut line = String::new();
+            let read = std::io::stdin().read_line(&mut line).expect("Couldn't read line for some reason");
+            if read == 0 {
+                // stdin is closed, there's never going to be more
+                self.can_ask = false;
+            }
+            self.data.extend(self.newline.translate_line(&line));
+        }
+
+        Some(self.data[index])
+    }
+}
+
+// Everything a run did that can be observed from the outside
+pub struct Trace {
+    // Every printed byte, along with the offset of the PRINT that printed it
+    pub output: Vec<(usize, u8)>,
+    pub memory: Memory,
+    pub mem_ptr: isize,
+    // The range of cells the program touched
+    pub lowest_ptr: isize,
+    pub highest_ptr: isize,
+    pub steps: u64,
+    // Where and why the run stopped early
+    pub error: Option<(usize, String)>
+}
+
+impl Trace {
+    pub fn output_bytes(&self) -> Vec<u8> {
+        self.output.iter().map(|(_, byte)| *byte).collect()
+    }
+}
+
+// One instruction that was run, with the current cell after running it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub instr_ptr: usize,
+    pub mem_ptr: isize,
+    pub cell: u32
+}
+
+// A quiet version of the interpreter that records what the program does, and
+// doesn't add anything to the output like execute_bf does.
+// It stops at 'limits' the same way execute_bf does
+pub fn trace_run(program: &Program, input: &mut Input, limits: Limits) -> Trace {
+    match run::<std::convert::Infallible>(program, input, limits, None) {
+        Ok(trace) => trace,
+        Err(never) => match never {}
+    }
+}
+
+// Like trace_run, but hands every step the program takes to 'on_step' as it happens, so a long
+// run doesn't have to keep all of them around. If 'on_step' fails the run stops, and that's the error
+pub fn record_run<E>(program: &Program, input: &mut Input, limits: Limits,
+        on_step: &mut dyn FnMut(Step) -> Result<(), E>) -> Result<Trace, E> {
+    run(program, input, limits, Some(on_step))
+}
+
+// Where a traced run reads from and prints to
+struct TraceIo<'a> {
+    input: &'a mut Input,
+    input_index: usize,
+    output: Vec<(usize, u8)>
+}
+
+impl Io for TraceIo<'_> {
+    fn read(&mut self, _: &Machine) -> Result<Option<u8>, String> {
+        let byte = self.input.next(self.input_index)
+            .ok_or_else(|| String::from("read more input than it was given"))?;
+        self.input_index += 1;
+        Ok(Some(byte))
+    }
+
+    fn print(&mut self, machine: &Machine, byte: u8) -> Result<(), String> {
+        self.output.push((machine.instr_ptr(), byte));
+        Ok(())
+    }
+
+    fn has_input(&self) -> bool {
+        self.input_index < self.input.data.len()
+    }
+}
+
+fn run<E>(program: &Program, input: &mut Input, limits: Limits,
+        mut on_step: Option<&mut dyn FnMut(Step) -> Result<(), E>>) -> Result<Trace, E> {
+    // Runs that are compared get the same random bytes
+    let mut machine = Machine::new(limits, Random::seeded(0));
+    let mut memory = Memory::for_cells(program.pragmas.cell_bits);
+    let mut io = TraceIo { input, input_index: 0, output: Vec::new() };
+
+    let mut error = None;
+    loop {
+        let instr_ptr = machine.instr_ptr();
+        match machine.step(program, &mut memory, &mut io) {
+            Ok(Flow::Ran) => if let Some(on_step) = &mut on_step {
+                on_step(Step { instr_ptr, mem_ptr: machine.mem_ptr(), cell: memory.get(machine.mem_ptr()) })?;
+            },
+            // The input never says to wait, it fails instead
+            Ok(Flow::NeedsInput) | Ok(Flow::Finished) => break,
+            Err(fault) => {
+                error = Some((instr_ptr, fault.describe()));
+                break;
+            }
+        }
+    }
+
+    let (lowest_ptr, highest_ptr) = machine.visited();
+    Ok(Trace {
+        output: io.output,
+        memory,
+        mem_ptr: machine.mem_ptr(),
+        lowest_ptr,
+        highest_ptr,
+        steps: machine.steps(),
+        error
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ Modifiers, compile_test_program };
+
+    #[test]
+    fn traced_runs_stop_at_the_limits_from_the_pragmas() {
+        let program = compile_test_program("#pragma max_steps 20\n+[>+]");
+        let mut input = Input { data: Vec::new(), can_ask: false, newline: Newline::Raw };
+        let mut steps = 0;
+        let trace = record_run(&program, &mut input, Modifiers::new().limits(&program.pragmas), &mut |_| {
+            steps += 1;
+            Ok::<(), ()>(())
+        }).unwrap();
+        assert_eq!(steps, 20);
+        assert!(trace.error.is_some_and(|(_, msg)| msg == "ran more than 20 instructions"));
+
+        let program = compile_test_program("#pragma max_memory 5\n+[>+]");
+        let trace = trace_run(&program, &mut input, Modifiers::new().limits(&program.pragmas));
+        assert!(trace.error.is_some_and(|(_, msg)| msg == "used more than 5 cells"));
+    }
+
+    #[test]
+    fn sandboxed_traced_runs_are_limited_and_never_ask_for_input() {
+        let mut modifiers = Modifiers::new();
+        modifiers.sandbox = true;
+
+        let program = compile_test_program("+[>+]");
+        let trace = trace_run(&program, &mut modifiers.trace_input(&[]), modifiers.limits(&program.pragmas));
+        assert!(trace.error.is_some_and(|(_, msg)| msg == format!("used more than {} cells", crate::SANDBOX_MAX_MEMORY)));
+
+        let program = compile_test_program(",.,.");
+        let mut input = modifiers.trace_input(b"a");
+        assert!(!input.can_ask);
+        let trace = trace_run(&program, &mut input, modifiers.limits(&program.pragmas));
+        assert_eq!(trace.output_bytes(), b"a");
+        assert!(trace.error.is_some_and(|(_, msg)| msg == "read more input than it was given"));
+    }
+}
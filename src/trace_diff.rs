@@ -0,0 +1,255 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{ BufRead, BufReader, BufWriter, Write };
+
+use crate::trace::Step;
+
+// The first line of every trace file
+pub const TRACE_HEADER: &str = "; trolledfuck trace 1";
+
+// How many differing cells are listed before the rest are just counted
+const MAX_LISTED_CELLS: usize = 10;
+
+// A run saved with *trace=file. The file is text, with one line per step,
+// "instruction mem_ptr cell" where the instruction and the cell are in hex.
+// If the run stopped early, the last line is "; stopped at instruction, why"
+pub struct TraceFile {
+    pub steps: Vec<Step>,
+    pub stopped: Option<String>
+}
+
+// Writes a trace file one step at a time, while the program is still running
+pub struct TraceWriter {
+    file: BufWriter<File>,
+    steps: u64
+}
+
+impl TraceWriter {
+    pub fn create(path: &str) -> std::io::Result<TraceWriter> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "{}", TRACE_HEADER)?;
+        Ok(TraceWriter { file, steps: 0 })
+    }
+
+    pub fn step(&mut self, step: Step) -> std::io::Result<()> {
+        self.steps += 1;
+        writeln!(self.file, "{:X} {} {:X}", step.instr_ptr, step.mem_ptr, step.cell)
+    }
+
+    // How many steps were written
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    // Ends the trace with why the run stopped early, if it did
+    pub fn finish(mut self, error: &Option<(usize, String)>) -> std::io::Result<()> {
+        if let Some((offset, msg)) = error {
+            writeln!(self.file, "; stopped at {:X}, {}", offset, msg)?;
+        }
+
+        self.file.flush()
+    }
+}
+
+pub fn write_trace(path: &str, steps: &[Step], error: &Option<(usize, String)>) -> std::io::Result<()> {
+    let mut writer = TraceWriter::create(path)?;
+    for step in steps {
+        writer.step(*step)?;
+    }
+
+    writer.finish(error)
+}
+
+fn parse_step(line: &str) -> Option<Step> {
+    let mut parts = line.split(' ');
+    let step = Step {
+        instr_ptr: usize::from_str_radix(parts.next()?, 16).ok()?,
+        mem_ptr: parts.next()?.parse().ok()?,
+        cell: u32::from_str_radix(parts.next()?, 16).ok()?
+    };
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(step)
+}
+
+pub fn read_trace(path: &str) -> Result<TraceFile, String> {
+    let file = File::open(path).map_err(|error| format!("Couldn't open '{}', {}", path, error))?;
+    let mut lines = BufReader::new(file).lines();
+
+    match lines.next() {
+        Some(Ok(header)) if header == TRACE_HEADER => {},
+        _ => return Err(format!("'{}' isn't a trace, it doesn't start with '{}'", path, TRACE_HEADER))
+    }
+
+    let mut trace = TraceFile { steps: Vec::new(), stopped: None };
+    for (i, line) in lines.enumerate() {
+        let line = line.map_err(|error| format!("Couldn't read '{}', {}", path, error))?;
+        if let Some(stopped) = line.strip_prefix("; stopped at ") {
+            trace.stopped = Some(String::from(stopped));
+            continue;
+        }
+
+        let step = parse_step(&line)
+            .ok_or_else(|| format!("Line {} of '{}' isn't a valid step, '{}'", i + 2, path, line))?;
+        trace.steps.push(step);
+    }
+
+    Ok(trace)
+}
+
+// The cells that have different values in the two memories, as (cell, a, b)
+fn cell_deltas(a: &BTreeMap<isize, u32>, b: &BTreeMap<isize, u32>) -> Vec<(isize, u32, u32)> {
+    let mut cells: Vec<isize> = a.keys().chain(b.keys()).cloned().collect();
+    cells.sort_unstable();
+    cells.dedup();
+
+    cells.into_iter()
+        .map(|cell| (cell, a.get(&cell).cloned().unwrap_or(0), b.get(&cell).cloned().unwrap_or(0)))
+        .filter(|(_, a, b)| a != b)
+        .collect()
+}
+
+fn describe_stop(trace: &TraceFile) -> String {
+    match &trace.stopped {
+        Some(reason) => format!(", it stopped at {}", reason),
+        None => String::new()
+    }
+}
+
+// Lines the two traces up step by step and describes the first step where they differ,
+// along with how the memory differs after that step. Returns None if they're the same.
+// The memory is rebuilt from the steps, so only the cells the programs visited are compared.
+pub fn diff_traces(a: &TraceFile, b: &TraceFile) -> Option<String> {
+    let mut memory_a = BTreeMap::new();
+    let mut memory_b = BTreeMap::new();
+
+    for (i, (step_a, step_b)) in a.steps.iter().zip(b.steps.iter()).enumerate() {
+        memory_a.insert(step_a.mem_ptr, step_a.cell);
+        memory_b.insert(step_b.mem_ptr, step_b.cell);
+        if step_a == step_b {
+            continue;
+        }
+
+        let mut msg = format!(
+            "The traces diverge at step {}, the first ran instruction {:X} and has cell {} = {:X}, \
+            the second ran instruction {:X} and has cell {} = {:X}",
+            i, step_a.instr_ptr, step_a.mem_ptr, step_a.cell, step_b.instr_ptr, step_b.mem_ptr, step_b.cell);
+
+        let deltas = cell_deltas(&memory_a, &memory_b);
+        for (cell, value_a, value_b) in deltas.iter().take(MAX_LISTED_CELLS) {
+            msg.push_str(&format!("\n | cell {}: {:X} -> {:X}", cell, value_a, value_b));
+        }
+        if deltas.len() > MAX_LISTED_CELLS {
+            msg.push_str(&format!("\n | and {} more cells", deltas.len() - MAX_LISTED_CELLS));
+        }
+
+        return Some(msg);
+    }
+
+    let common = a.steps.len().min(b.steps.len());
+    if a.steps.len() != b.steps.len() {
+        let (shorter, longer, names) = if a.steps.len() < b.steps.len() {
+            (a, b, ("first", "second"))
+        }else{
+            (b, a, ("second", "first"))
+        };
+
+        return Some(format!("The {} trace ends after {} steps{}, but the {} keeps going with instruction {:X}",
+            names.0, common, describe_stop(shorter), names.1, longer.steps[common].instr_ptr));
+    }
+
+    if a.stopped != b.stopped {
+        let ending = |trace: &TraceFile| trace.stopped.as_ref()
+            .map(|reason| format!("stopped at {}", reason))
+            .unwrap_or_else(|| String::from("ran to the end"));
+        return Some(format!("The traces have the same {} steps, but the first {} and the second {}",
+            common, ending(a), ending(b)));
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ Newline, compile_test_program };
+    use crate::trace::{ Input, record_run };
+    use crate::machine::Limits;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir().join(format!("brainfuck_trace_{}_{}", std::process::id(), name));
+        path.to_string_lossy().into_owned()
+    }
+
+    // Runs the program and saves its trace the way *trace does, then reads it back
+    fn traced(text: &str, input: &[u8], name: &str) -> TraceFile {
+        let path = temp_path(name);
+        let mut writer = TraceWriter::create(&path).unwrap();
+        let mut input = Input { data: input.to_vec(), can_ask: false, newline: Newline::Raw };
+        let trace = record_run(&compile_test_program(text), &mut input, Limits::default(), &mut |step| writer.step(step)).unwrap();
+        assert_eq!(writer.steps(), trace.steps);
+        writer.finish(&trace.error).unwrap();
+
+        let file = read_trace(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        file
+    }
+
+    #[test]
+    fn traces_read_back_the_same() {
+        let steps = vec![
+            Step { instr_ptr: 0, mem_ptr: 0, cell: 1 },
+            Step { instr_ptr: 0x1F, mem_ptr: -3, cell: 0xFFFF },
+        ];
+        let error = Some((0x1F, String::from("ran more than 2 instructions")));
+        let path = temp_path("round_trip");
+        write_trace(&path, &steps, &error).unwrap();
+        let file = read_trace(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(file.steps, steps);
+        assert_eq!(file.stopped.as_deref(), Some("1F, ran more than 2 instructions"));
+    }
+
+    #[test]
+    fn files_that_arent_traces_are_rejected() {
+        let path = temp_path("not_a_trace");
+        std::fs::write(&path, "+++.").unwrap();
+        assert!(read_trace(&path).is_err());
+
+        std::fs::write(&path, format!("{}\n0 0 1\n1 zero 2\n", TRACE_HEADER)).unwrap();
+        let error = read_trace(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(error.contains("Line 3"), "{}", error);
+    }
+
+    #[test]
+    fn the_same_run_has_no_differences() {
+        let a = traced("+++[>++<-]>.", b"", "same_a");
+        let b = traced("+++[>++<-]>.", b"", "same_b");
+        assert!(!a.steps.is_empty());
+        assert_eq!(diff_traces(&a, &b), None);
+    }
+
+    #[test]
+    fn diffs_point_at_the_first_different_step() {
+        let a = traced(",[>+<-]", b"\x03", "input_a");
+        let b = traced(",[>+<-]", b"\x04", "input_b");
+        let diff = diff_traces(&a, &b).unwrap();
+        assert!(diff.starts_with("The traces diverge at step 0"), "{}", diff);
+        assert!(diff.contains("cell 0: 3 -> 4"), "{}", diff);
+
+        let a = traced("++", b"", "short");
+        let b = traced("+++", b"", "long");
+        let diff = diff_traces(&a, &b).unwrap();
+        assert!(diff.starts_with("The first trace ends after 2 steps"), "{}", diff);
+
+        let a = traced(",", b"a", "finished");
+        let b = traced(",", b"", "stopped");
+        let diff = diff_traces(&a, &b).unwrap();
+        assert!(diff.contains("it stopped at 0, read more input than it was given"), "{}", diff);
+    }
+}
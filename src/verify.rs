@@ -0,0 +1,236 @@
+use crate::Newline;
+use crate::binary::Program;
+use crate::optimize::{ Optimized, Pass, PassLimits, Pipeline, optimize_with_pipeline, run_passes };
+use crate::trace::{ Input, Trace, trace_run };
+use crate::machine::Limits;
+
+// Finds the first difference between the two runs, with the offsets
+// of the optimized run translated back to the unoptimized code
+fn first_divergence(original: &Trace, optimized: &Trace, opt: &Optimized) -> Option<String> {
+    let origin = |offset: usize| opt.origin_of(offset).unwrap_or(offset);
+
+    for (i, (a, b)) in original.output.iter().zip(optimized.output.iter()).enumerate() {
+        if a.1 != b.1 {
+            return Some(format!(
+                "Output byte {} differs, unoptimized printed {:?} at instruction {:X}, \
+                optimized printed {:?} at instruction {:X}(originally {:X})",
+                i, a.1 as char, a.0, b.1 as char, b.0, origin(b.0)));
+        }
+    }
+
+    if original.output.len() != optimized.output.len() {
+        let (longer, name) = if original.output.len() > optimized.output.len() {
+            (original, "unoptimized")
+        }else{
+            (optimized, "optimized")
+        };
+        let extra = longer.output[original.output.len().min(optimized.output.len())];
+        return Some(format!(
+            "Only the {} program printed byte {}, {:?} at instruction {:X}",
+            name, original.output.len().min(optimized.output.len()), extra.1 as char, extra.0));
+    }
+
+    match (&original.error, &optimized.error) {
+        (None, Some((offset, msg))) => return Some(format!(
+            "The optimized program stopped at instruction {:X}(originally {:X}), it {}",
+            offset, origin(*offset), msg)),
+        (Some((offset, msg)), None) => return Some(format!(
+            "The unoptimized program stopped at instruction {:X} but the optimized one didn't, it {}",
+            offset, msg)),
+        _ => {}
+    }
+
+    if original.mem_ptr != optimized.mem_ptr {
+        return Some(format!("The memory pointer ended up at {} unoptimized, but at {} optimized",
+            original.mem_ptr, optimized.mem_ptr));
+    }
+
+    let lowest = original.lowest_ptr.min(optimized.lowest_ptr);
+    let highest = original.highest_ptr.max(optimized.highest_ptr);
+    for cell in lowest..=highest {
+        let (a, b) = (original.memory.get(cell), optimized.memory.get(cell));
+        if a != b {
+            return Some(format!("Cell {} ended up as {:X} unoptimized, but as {:X} optimized", cell, a, b));
+        }
+    }
+
+    None
+}
+
+pub struct Verified {
+    // What the unoptimized program printed
+    pub output: Vec<u8>,
+    pub summary: String
+}
+
+// Runs the program unoptimized and optimized with the same input, and compares
+// what they printed and what the tape looked like at the end.
+// Returns a description of the first difference between the runs if they differ.
+// The unoptimized run stops at 'limits', and the optimized one can't run more instructions than it did
+pub fn verify_optimizations(program: &Program, std_in: &[u8], limits: Limits, level: u8, pipeline: &Pipeline) -> Result<Verified, String> {
+    let opt = optimize_with_pipeline(program, level, pipeline);
+
+    let mut input = Input { data: std_in.to_vec(), can_ask: true, newline: Newline::Trim };
+    let original = trace_run(program, &mut input, limits);
+
+    input.can_ask = false;
+    let optimized = trace_run(&opt.program, &mut input, Limits { max_steps: Some(original.steps), ..limits });
+
+    if let Some(divergence) = first_divergence(&original, &optimized, &opt) {
+        return Err(divergence);
+    }
+
+    Ok(Verified {
+        output: original.output_bytes(),
+        summary: format!("{} -> {} bytes of code, {} -> {} instructions run, {} loops unrolled",
+            program.code.len(), opt.program.code.len(), original.steps, optimized.steps, opt.count(Pass::Unroll))
+    })
+}
+
+pub struct Bisection {
+    // How the program behaves differently with only the changes below
+    pub divergence: String,
+    pub passes: Vec<BisectedPass>
+}
+
+// A pass that's needed to make the program behave differently
+pub struct BisectedPass {
+    pub pass: Pass,
+    // How many of its changes are needed, out of how many it makes
+    pub needed: usize,
+    pub total: usize,
+    // Where the last needed change is in the unoptimized code, that's usually the broken one
+    pub last_site: Option<usize>
+}
+
+// Finds the fewest optimizer changes that still make the program behave differently, by turning
+// off the passes that aren't needed one at a time, and then bisecting how many of the changes of
+// every pass that's left are needed. The changes of a pass are made in the order of the code,
+// so it's the first N of them. None if the optimized program behaves the same.
+// The custom passes of the pipeline always run, only the built in ones are bisected.
+// The runs stop at 'run_limits' like in verify_optimizations
+pub fn bisect_optimizations(program: &Program, std_in: &[u8], run_limits: Limits, level: u8, pipeline: &Pipeline) -> Option<Bisection> {
+    let mut input = Input { data: std_in.to_vec(), can_ask: true, newline: Newline::Trim };
+    let original = trace_run(program, &mut input, run_limits);
+    input.can_ask = false;
+
+    let mut diverges = |limits: &PassLimits| -> Option<(String, Optimized)> {
+        let opt = run_passes(program, level, limits, &pipeline.custom);
+        let optimized = trace_run(&opt.program, &mut input, Limits { max_steps: Some(original.steps), ..run_limits });
+        first_divergence(&original, &optimized, &opt).map(|divergence| (divergence, opt))
+    };
+
+    let mut limits = pipeline.limits;
+    diverges(&limits)?;
+
+    for pass in Pass::ALL.iter() {
+        let mut without = limits;
+        without.set(*pass, Some(0));
+        if diverges(&without).is_some() {
+            limits = without;
+        }
+    }
+
+    // 'limits' always makes the program behave differently, so the search keeps the
+    // smallest limit that's known to do that in 'enough'
+    let mut totals = [0; Pass::ALL.len()];
+    for pass in Pass::ALL.iter() {
+        if limits.get(*pass) == Some(0) {
+            continue;
+        }
+
+        let total = run_passes(program, level, &limits, &pipeline.custom).count(*pass);
+        totals[*pass as usize] = total;
+        let (mut not_enough, mut enough) = (0, total);
+        while enough - not_enough > 1 {
+            let middle = (not_enough + enough) / 2;
+            let mut trial = limits;
+            trial.set(*pass, Some(middle));
+            if diverges(&trial).is_some() {
+                enough = middle;
+            }else{
+                not_enough = middle;
+            }
+        }
+        limits.set(*pass, Some(enough));
+    }
+
+    let (divergence, opt) = diverges(&limits)?;
+    let passes = Pass::ALL.iter()
+        .filter(|pass| limits.get(**pass) != Some(0))
+        .map(|pass| BisectedPass {
+            pass: *pass,
+            needed: opt.count(*pass),
+            total: totals[*pass as usize],
+            last_site: opt.sites.iter().rev().find(|(site_pass, _)| site_pass == pass).map(|(_, offset)| *offset)
+        })
+        .collect();
+
+    Some(Bisection { divergence, passes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ CompileOptions, compile_test_program };
+    use crate::instructions::PRINT;
+    use crate::optimize::{ CustomPass, Ir };
+
+    #[test]
+    fn optimized_programs_behave_the_same() {
+        let program = compile_test_program("+5 [->+2<] +- <> >.");
+        let verified = verify_optimizations(&program, &[], Limits::default(), 1, &Pipeline::new()).unwrap_or_else(|msg| panic!("{}", msg));
+        assert_eq!(verified.output, vec![10]);
+        assert!(bisect_optimizations(&program, &[], Limits::default(), 1, &Pipeline::new()).is_none());
+    }
+
+    #[test]
+    fn optimizing_works_on_top_of_a_library() {
+        let library = crate::compile_library(":twice { ++ } :four noinline { #std/twice #std/twice }".chars().collect(), CompileOptions::default())
+            .unwrap_or_else(|failure| panic!("{}", failure.describe()));
+        let on_library = |text: &str| crate::compile_on_library(text.chars().collect(), &library, CompileOptions::default())
+            .unwrap_or_else(|failure| panic!("{}", failure.describe()))
+            .on_library(&library)
+            .unwrap();
+
+        // The library's code is a loop at the start that never runs, the program comes after it.
+        // The macros in it are called, so the optimizer leaves everything alone
+        for (text, output) in [("+- +5 [->+2<] >.", 10), ("+ #std/four .", 5)] {
+            let program = on_library(text);
+            assert!(program.code.len() > library.code.len());
+            let verified = verify_optimizations(&program, &[], Limits::default(), 1, &Pipeline::new()).unwrap_or_else(|msg| panic!("{}", msg));
+            assert_eq!(verified.output, vec![output]);
+            assert_eq!(optimize_with_pipeline(&program, 1, &Pipeline::new()).program.code, program.code);
+        }
+    }
+
+    // A broken custom pass that prints once where the program prints twice in a row, it only breaks
+    // programs where cancel made two prints end up next to each other
+    struct SkipRepeatedPrints;
+    impl CustomPass for SkipRepeatedPrints {
+        fn name(&self) -> &str { "skip_repeated_prints" }
+        fn run(&self, ir: &mut Ir) -> usize {
+            let before = ir.instructions.len();
+            ir.instructions.dedup_by(|a, b| a.0 == PRINT && b.0 == PRINT);
+            before - ir.instructions.len()
+        }
+    }
+
+    #[test]
+    fn bisecting_finds_the_pass_that_changes_the_program() {
+        let program = compile_test_program("+41 . +- . <> .");
+        let mut pipeline = Pipeline::new();
+        pipeline.register(Box::new(SkipRepeatedPrints));
+        assert!(verify_optimizations(&program, &[], Limits::default(), 1, &pipeline).is_err());
+
+        let bisection = bisect_optimizations(&program, &[], Limits::default(), 1, &pipeline).expect("The programs should differ");
+        assert!(bisection.divergence.starts_with("Only the unoptimized program printed byte 2"), "{}", bisection.divergence);
+        assert_eq!(bisection.passes.len(), 1);
+        let pass = &bisection.passes[0];
+        assert_eq!(pass.pass, Pass::Cancel);
+        // Only the "+-" has to be cancelled, not the "<>"
+        assert_eq!((pass.needed, pass.total), (1, 2));
+        let prints: Vec<usize> = (0..program.code.len()).filter(|i| program.code[*i] == PRINT).collect();
+        assert!(pass.last_site.is_some_and(|offset| prints[0] < offset && offset < prints[1]));
+    }
+}
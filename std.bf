@@ -52,4 +52,87 @@
     }
 
     [.>]
-}
\ No newline at end of file
+}
+:image {
+    ; Prints a 16 by 16 grayscale gradient as a binary PGM(P5) image,
+    ; for trying out *image. Uses the current cell and the two to the
+    ; right of it, which have to be zero, and leaves them zero
+    :gradient {
+        #std/print/start
+            "P5\n16 16\n255\n"
+        #std/print/end
+        #std/print/cleanup
+        >
+
+        ; 16 rows of 16 pixels, every pixel is 16 brighter than the one to the
+        ; left of it, and every row starts 1 brighter than the one above it
+        +10[
+            >+10[ >.+10< - ]
+            >+<
+            <-
+        ]
+        >>[-]<<
+    }
+}
+:char {
+    ; Work on the character in the current cell, and use the four cells
+    ; to the right of it, which are cleared first
+
+    ; 1 if it's '0' to '9', otherwise 0
+    :is_digit {
+        %[ '0-'9: 1 ]
+    }
+
+    ; 1 if it's a letter, otherwise 0
+    :is_alpha {
+        %[ 'A-'Z: 1  'a-'z: 1 ]
+    }
+
+    ; 1 if it's a space, tab or newline, otherwise 0
+    :is_space {
+        %[ 20: 1  9-D: 1 ]
+    }
+
+    ; Lowercase letters become uppercase, everything else stays the same
+    :to_upper {
+        %[ 'a-'z: @-20  _: @ ]
+    }
+
+    :to_lower {
+        %[ 'A-'Z: @+20  _: @ ]
+    }
+}
+:print_num {
+    ; Prints the number in the current cell in decimal, the cell keeps its value.
+    ; Works with any cell size, and uses the cells to the right of it, one more for
+    ; every digit, plus a few, which have to be zero. They're left zero
+
+    ; The number is divided by 10 until it's zero, every remainder(plus one, so a 0
+    ; doesn't end the digits early) is left behind, and the quotient goes one cell right
+    :digit {
+        [->-[>+>>]>[+[-<+>]>+>>]<<<<<]
+        >[-] >[-<<+>>] >[-<<+>>] <+A <<+ >
+    }
+
+    >> =[ @<2 ] >+A<
+    #std/print_num/digit
+    [ #std/print_num/digit ]
+    >[-]<<
+
+    ; The last digit is the one to print first, the zero cell left of the first one stops it
+    [ +'0 - . [-] < ]
+    <
+}
+
+:read_num {
+    ; Reads a number in decimal from the input into the current cell. It reads until
+    ; the first character that isn't a digit, which is thrown away, so there has to be
+    ; something after the number, like a space or a newline. Uses the cells to the right
+    ; of it, which are cleared first
+    [-] >> [-]+ [
+        <[-], >
+        =[ @<1 ] %[ '0-'9: 1 ]
+        ?{ << =[ @*A + @>1 - '0 ] >> + }
+    ]
+    <[-]<
+}